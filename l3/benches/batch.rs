@@ -0,0 +1,79 @@
+//! Сравнение пакетного вычисления операций с поэлементным вызовом calclate.
+//! Запуск: cargo bench
+
+use std::hint::black_box;
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use l3::operation::{And, Operation, Xor};
+use l3::word::Word;
+
+/// Размер буфера в элементах
+const LEN: usize = 1 << 16;
+
+/// Буфер с псевдослучайными значениями (линейный конгруэнтный генератор)
+fn buffer<T: Word>(seed: u128) -> Vec<T> {
+    let mut state = seed;
+    (0..LEN)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            T::from_u128(state >> 64, u128::BITS as usize)
+        })
+        .collect()
+}
+
+fn bench_type<T: Word + 'static>(c: &mut Criterion, ty: &str) {
+    let a = buffer::<T>(1);
+    let b = buffer::<T>(2);
+    let mask = a[0].clone();
+    let mut out = a.clone();
+
+    let operations: Vec<Rc<dyn Operation<T>>> = vec![Rc::new(Xor), Rc::new(And)];
+
+    let mut group = c.benchmark_group(ty);
+    group.throughput(Throughput::Bytes((LEN * std::mem::size_of::<T>()) as u64));
+
+    for op in &operations {
+        // Так операции применялись раньше: вызов через dyn на каждый элемент
+        group.bench_function(BenchmarkId::new("calclate loop", op.name()), |bench| {
+            bench.iter(|| {
+                for ((a, b), out) in a.iter().zip(&b).zip(out.iter_mut()) {
+                    *out = op.calclate(a.clone(), b.clone());
+                }
+                black_box(&out);
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("calclate_into", op.name()), |bench| {
+            bench.iter(|| {
+                op.calclate_into(black_box(&a), black_box(&b), &mut out);
+                black_box(&out);
+            })
+        });
+
+        group.bench_function(
+            BenchmarkId::new("calclate_scalar_into", op.name()),
+            |bench| {
+                bench.iter(|| {
+                    op.calclate_scalar_into(black_box(&a), black_box(&mask), &mut out);
+                    black_box(&out);
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn batch(c: &mut Criterion) {
+    bench_type::<u8>(c, "u8");
+    bench_type::<u32>(c, "u32");
+    bench_type::<u64>(c, "u64");
+    bench_type::<u128>(c, "u128");
+}
+
+criterion_group!(benches, batch);
+criterion_main!(benches);
@@ -0,0 +1,651 @@
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::bitvec::BitVec;
+use crate::operation::{OpError, Operation, UnaryOperation};
+use crate::properties::{Element, Properties, UnaryProperties};
+use crate::word::Word;
+
+/// Что делать, если результат арифметической операции не помещается в слово
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Отбросить старшие биты (как wrapping_add)
+    #[default]
+    Wrapping,
+    /// Прижать результат к наибольшему или наименьшему значению типа (как saturating_add)
+    Saturating,
+    /// Вернуть ошибку (как checked_add)
+    Checked,
+    /// Вернуть результат двойной разрядности (как widening_mul). Если результат
+    /// нужен в виде одного слова, возвращается его младшая половина.
+    Widening,
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrapping" => Ok(Overflow::Wrapping),
+            "saturating" => Ok(Overflow::Saturating),
+            "checked" => Ok(Overflow::Checked),
+            "widening" => Ok(Overflow::Widening),
+            _ => Err(format!(
+                "unknown overflow policy '{s}', expected wrapping, saturating, checked or widening"
+            )),
+        }
+    }
+}
+
+/// Арифметическая операция. Сначала вычисляется точный результат в дополнительном коде
+/// с запасом разрядности, а уже потом он приводится к слову согласно политике переполнения.
+/// Так все политики работают одинаково для любых типов, включая BitVec.
+pub trait Arithmetic<T: Word>: Operation<T> {
+    /// Точный результат операции (не меньше 2 * разрядность + 2 бит)
+    fn exact(&self, a: &T, b: &T) -> Result<BitVec, OpError>;
+
+    /// Результат двойной разрядности: (старшая половина, младшая половина)
+    fn calclate_wide(&self, a: T, b: T) -> Result<(T, T), OpError> {
+        let width = a.width();
+        let wide = self.exact(&a, &b)?.resize(2 * width);
+
+        let low = T::from_bitvec(&wide.resize(width));
+        let high = T::from_bitvec(&wide.shift_right(width).resize(width));
+        Ok((high, low))
+    }
+}
+
+/// Разрядность, в которой точно помещается сумма, разность и произведение двух слов
+fn wide_width<T: Word>(a: &T) -> usize {
+    2 * a.width() + 2
+}
+
+/// Отрицательно ли число в дополнительном коде
+fn is_negative(bits: &BitVec) -> bool {
+    bits.bit(bits.width() - 1)
+}
+
+/// Количество разрядов сдвига из второго операнда
+fn shift_count<T: Word>(b: &T) -> usize {
+    usize::try_from(b.to_u128()).unwrap_or(usize::MAX)
+}
+
+/// Наибольшее (`max`) или наименьшее значение типа
+fn limit<T: Word>(like: &T, max: bool) -> T {
+    let width = like.width();
+    let mut bits = BitVec::zeros(width);
+
+    for i in 0..width {
+        // У знаковых типов старший бит -- знак, поэтому он противоположен остальным
+        let sign = like.is_signed() && i == width - 1;
+        bits.set_bit(i, max != sign);
+    }
+
+    T::from_bitvec(&bits)
+}
+
+/// Привести точный результат к слову согласно политике переполнения
+fn fit<T: Word>(exact: &BitVec, like: &T, overflow: Overflow, name: &str) -> Result<T, OpError> {
+    let result = T::from_bitvec(&exact.resize(like.width()));
+
+    // Результат помещается в слово, если после обратного расширения получается то же число
+    if result.extend(exact.width()) == *exact {
+        return Ok(result);
+    }
+
+    match overflow {
+        Overflow::Wrapping | Overflow::Widening => Ok(result),
+        Overflow::Checked => Err(OpError::Overflow(name.to_string())),
+        Overflow::Saturating => Ok(limit(like, !is_negative(exact))),
+    }
+}
+
+/// Наибольшая разрядность слова, для которой точный результат считается в i128.
+/// Для таких слов точный результат любой операции помещается в i128 (произведение
+/// занимает 64 бита, сдвиг влево -- не больше 96), и BitVec не нужен.
+const FAST_WIDTH: usize = 32;
+
+/// Привести точный результат, вычисленный в i128, к слову согласно политике переполнения
+fn fit_fast<T: Word>(exact: i128, like: &T, overflow: Overflow, name: &str) -> Result<T, OpError> {
+    let width = like.width();
+    let (min, max) = if like.is_signed() {
+        (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+    } else {
+        (0, (1i128 << width) - 1)
+    };
+
+    // Отрицательные числа в u128 записываются в дополнительном коде, а from_u128
+    // оставляет младшие биты -- это и есть wrapping
+    let value = match overflow {
+        _ if (min..=max).contains(&exact) => exact,
+        Overflow::Wrapping | Overflow::Widening => exact,
+        Overflow::Checked => return Err(OpError::Overflow(name.to_string())),
+        Overflow::Saturating => exact.clamp(min, max),
+    };
+    Ok(T::from_u128(value as u128, width))
+}
+
+/// Значение короткого слова как число со знаком (для беззнаковых типов оно неотрицательно)
+fn to_i128<T: Word>(a: &T) -> i128 {
+    // to_u128 у знаковых типов расширяет знак, так что преобразование сохраняет значение
+    a.to_u128() as i128
+}
+
+fn fast_add(a: i128, b: i128, _: usize, _: bool) -> Result<i128, OpError> {
+    Ok(a + b)
+}
+
+fn fast_sub(a: i128, b: i128, _: usize, _: bool) -> Result<i128, OpError> {
+    Ok(a - b)
+}
+
+fn fast_mul(a: i128, b: i128, _: usize, _: bool) -> Result<i128, OpError> {
+    Ok(a * b)
+}
+
+// Деление i128 в Rust тоже округляет к нулю, а остаток имеет знак делимого
+fn fast_div(a: i128, b: i128, _: usize, _: bool) -> Result<i128, OpError> {
+    a.checked_div(b).ok_or(OpError::DivisionByZero)
+}
+
+fn fast_rem(a: i128, b: i128, _: usize, _: bool) -> Result<i128, OpError> {
+    a.checked_rem(b).ok_or(OpError::DivisionByZero)
+}
+
+/// Количество разрядов сдвига. Отрицательный второй операнд, как и в shift_count,
+/// читается как беззнаковое число, то есть очень большое.
+fn fast_shift_count(b: i128) -> u128 {
+    b as u128
+}
+
+fn fast_shl(a: i128, b: i128, width: usize, _: bool) -> Result<i128, OpError> {
+    let n = fast_shift_count(b).min(2 * width as u128);
+    Ok(a << n)
+}
+
+fn fast_shr(a: i128, b: i128, _: usize, _: bool) -> Result<i128, OpError> {
+    let n = fast_shift_count(b).min(127);
+    Ok(a >> n)
+}
+
+fn fast_rotl(a: i128, b: i128, width: usize, signed: bool) -> Result<i128, OpError> {
+    let mask = (1u128 << width) - 1;
+    let bits = a as u128 & mask;
+    let n = (fast_shift_count(b) % width as u128) as usize;
+    let rotated = (bits << n | bits >> (width - n)) & mask;
+
+    // Старший бит знакового слова -- знак
+    if signed && rotated >> (width - 1) == 1 {
+        Ok(rotated as i128 - (1i128 << width))
+    } else {
+        Ok(rotated as i128)
+    }
+}
+
+fn fast_rotr(a: i128, b: i128, width: usize, signed: bool) -> Result<i128, OpError> {
+    let n = fast_shift_count(b) % width as u128;
+    fast_rotl(a, (width as u128 - n) as i128, width, signed)
+}
+
+fn exact_add<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    let width = wide_width(a);
+    Ok(a.extend(width).wrapping_add(&b.extend(width)))
+}
+
+fn exact_sub<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    let width = wide_width(a);
+    Ok(a.extend(width).wrapping_sub(&b.extend(width)))
+}
+
+fn exact_mul<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    let width = wide_width(a);
+    Ok(a.extend(width).wrapping_mul(&b.extend(width)))
+}
+
+/// Знаковое деление с остатком. Как и в Rust, частное округляется к нулю,
+/// а остаток имеет знак делимого.
+fn signed_div_rem<T: Word>(a: &T, b: &T) -> Result<(BitVec, BitVec), OpError> {
+    if b.to_bitvec().is_zero() {
+        return Err(OpError::DivisionByZero);
+    }
+
+    let width = wide_width(a);
+    let (a, b) = (a.extend(width), b.extend(width));
+    let abs = |x: &BitVec| {
+        if is_negative(x) {
+            x.wrapping_neg()
+        } else {
+            x.clone()
+        }
+    };
+
+    let (quotient, remainder) = abs(&a).div_rem(&abs(&b));
+    let quotient = if is_negative(&a) != is_negative(&b) {
+        quotient.wrapping_neg()
+    } else {
+        quotient
+    };
+    let remainder = if is_negative(&a) {
+        remainder.wrapping_neg()
+    } else {
+        remainder
+    };
+
+    Ok((quotient, remainder))
+}
+
+fn exact_div<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    Ok(signed_div_rem(a, b)?.0)
+}
+
+fn exact_rem<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    Ok(signed_div_rem(a, b)?.1)
+}
+
+/// Сдвиг влево как умножение на 2^n. Переполнение -- потеря значащих битов.
+fn exact_shl<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    // Сдвиг больше чем на 2 * разрядность даёт в младших битах нули, а переполнение
+    // определяется тем, что a не ноль, поэтому больших сдвигов считать не нужно
+    let n = shift_count(b).min(2 * a.width());
+    Ok(a.extend(3 * a.width() + 2).shift_left(n))
+}
+
+/// Сдвиг вправо -- деление на 2^n с округлением вниз, переполнения не бывает
+fn exact_shr<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    Ok(a.clone().shift_right(shift_count(b)).extend(wide_width(a)))
+}
+
+/// Циклический сдвиг влево: вытесненные старшие биты появляются справа
+fn exact_rotl<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    Ok(rotate_left(a, rotate_count(b, a.width())).extend(wide_width(a)))
+}
+
+fn exact_rotr<T: Word>(a: &T, b: &T) -> Result<BitVec, OpError> {
+    let width = a.width();
+    Ok(rotate_left(a, (width - rotate_count(b, width)) % width).extend(wide_width(a)))
+}
+
+/// Количество разрядов циклического сдвига по модулю разрядности. Отрицательное число
+/// берётся в дополнительном коде, поэтому циклический сдвиг влево на -1 -- это сдвиг вправо на 1.
+fn rotate_count<T: Word>(b: &T, width: usize) -> usize {
+    (b.to_u128() % width as u128) as usize
+}
+
+fn rotate_left<T: Word>(a: &T, n: usize) -> T {
+    let bits = a.to_bitvec();
+    let rotated = bits.clone().shift_left(n) | bits.shift_right(a.width() - n);
+    T::from_bitvec(&rotated)
+}
+
+// Все арифметические операции устроены одинаково и отличаются только точным вычислением,
+// поэтому структуры и реализации типажей генерирует макрос
+macro_rules! arithmetic_operation {
+    ($op:ident, $name:literal, $designation:literal, $exact:ident, $fast:ident, $properties:expr) => {
+        #[derive(Clone, Copy, Default)]
+        pub struct $op {
+            pub overflow: Overflow,
+        }
+
+        impl<T: Word> Operation<T> for $op {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn designation(&self) -> &str {
+                $designation
+            }
+
+            fn calclate(&self, a: T, b: T) -> T {
+                // Как и встроенная арифметика Rust, при делении на ноль паникуем
+                self.try_calclate(a, b).unwrap_or_else(|e| panic!("{e}"))
+            }
+
+            fn try_calclate(&self, a: T, b: T) -> Result<T, OpError> {
+                let width = a.width();
+                if width <= FAST_WIDTH {
+                    let exact = $fast(to_i128(&a), to_i128(&b), width, a.is_signed())?;
+                    return fit_fast(exact, &a, self.overflow, $name);
+                }
+
+                fit(&$exact(&a, &b)?, &a, self.overflow, $name)
+            }
+
+            fn properties(&self) -> Properties {
+                $properties(self.overflow)
+            }
+        }
+
+        impl<T: Word> Arithmetic<T> for $op {
+            fn exact(&self, a: &T, b: &T) -> Result<BitVec, OpError> {
+                $exact(a, b)
+            }
+        }
+    };
+}
+
+arithmetic_operation!(Add, "add", "+", exact_add, fast_add, add_properties);
+arithmetic_operation!(Sub, "sub", "-", exact_sub, fast_sub, no_properties);
+arithmetic_operation!(Mul, "mul", "*", exact_mul, fast_mul, mul_properties);
+arithmetic_operation!(Div, "div", "/", exact_div, fast_div, no_properties);
+arithmetic_operation!(Rem, "rem", "%", exact_rem, fast_rem, no_properties);
+arithmetic_operation!(Shl, "shl", "<<", exact_shl, fast_shl, no_properties);
+arithmetic_operation!(Shr, "shr", ">>", exact_shr, fast_shr, no_properties);
+arithmetic_operation!(Rotl, "rotl", "<<<", exact_rotl, fast_rotl, no_properties);
+arithmetic_operation!(Rotr, "rotr", ">>>", exact_rotr, fast_rotr, no_properties);
+
+/// Отбрасываются ли старшие биты результата. Только тогда арифметика -- это кольцо
+/// вычетов по модулю 2^n, и сложение с умножением ассоциативны. При насыщении
+/// (100 + 100) - 100 != 100 + (100 - 100), а при проверке переполнения одна
+/// расстановка скобок может дать ошибку, а другая нет.
+fn is_modular(overflow: Overflow) -> bool {
+    matches!(overflow, Overflow::Wrapping | Overflow::Widening)
+}
+
+fn add_properties(overflow: Overflow) -> Properties {
+    Properties {
+        commutative: true,
+        associative: is_modular(overflow),
+        identity: Some(Element::Zero),
+        ..Properties::default()
+    }
+}
+
+fn mul_properties(overflow: Overflow) -> Properties {
+    Properties {
+        commutative: true,
+        associative: is_modular(overflow),
+        identity: Some(Element::One),
+        absorbing: Some(Element::Zero),
+        ..Properties::default()
+    }
+}
+
+/// Вычитание, деление и сдвиги не коммутативны, не ассоциативны
+/// и имеют только правый нейтральный элемент
+fn no_properties(_: Overflow) -> Properties {
+    Properties::default()
+}
+
+/// Унарный минус
+#[derive(Clone, Copy, Default)]
+pub struct Neg {
+    pub overflow: Overflow,
+}
+
+impl<T: Word> UnaryOperation<T> for Neg {
+    fn name(&self) -> &str {
+        "neg"
+    }
+
+    fn designation(&self) -> &str {
+        "-"
+    }
+
+    fn calclate(&self, a: T) -> T {
+        self.try_calclate(a).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_calclate(&self, a: T) -> Result<T, OpError> {
+        if a.width() <= FAST_WIDTH {
+            return fit_fast(-to_i128(&a), &a, self.overflow, "neg");
+        }
+
+        let exact = a.extend(wide_width(&a)).wrapping_neg();
+        fit(&exact, &a, self.overflow, "neg")
+    }
+
+    fn properties(&self) -> UnaryProperties {
+        // -(-a) = a, но при насыщении -(-128) = 127, а при проверке -(-128) -- ошибка
+        UnaryProperties {
+            idempotent: false,
+            self_inverse: is_modular(self.overflow),
+        }
+    }
+}
+
+/// Все арифметические операции
+pub fn arithmetic_operations<T: Word>(overflow: Overflow) -> Vec<Rc<dyn Arithmetic<T>>> {
+    vec![
+        Rc::new(Add { overflow }),
+        Rc::new(Sub { overflow }),
+        Rc::new(Mul { overflow }),
+        Rc::new(Div { overflow }),
+        Rc::new(Rem { overflow }),
+        Rc::new(Shl { overflow }),
+        Rc::new(Shr { overflow }),
+        Rc::new(Rotl { overflow }),
+        Rc::new(Rotr { overflow }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use super::*;
+
+    /// Сравнить операцию при всех политиках с ожидаемыми результатами.
+    /// None у `checked` означает переполнение.
+    fn check<T: Word + Debug, O: Operation<T>>(
+        op: impl Fn(Overflow) -> O,
+        (a, b): (T, T),
+        wrapping: T,
+        saturating: T,
+        checked: Option<T>,
+    ) {
+        let result = |overflow| op(overflow).try_calclate(a.clone(), b.clone());
+        let name = op(Overflow::Wrapping).name().to_string();
+        let context = format!("{a} {name} {b}");
+
+        assert_eq!(
+            result(Overflow::Wrapping),
+            Ok(wrapping.clone()),
+            "{context}"
+        );
+        assert_eq!(result(Overflow::Widening), Ok(wrapping), "{context}");
+        assert_eq!(result(Overflow::Saturating), Ok(saturating), "{context}");
+        assert_eq!(
+            result(Overflow::Checked),
+            checked.ok_or(OpError::Overflow(name)),
+            "{context}"
+        );
+    }
+
+    // Результаты сверяются со встроенной арифметикой Rust на границах типа.
+    // До 32 битов работает быстрый путь через i128, дальше -- BitVec.
+    macro_rules! boundary_test {
+        ($test:ident, $t:ty) => {
+            #[test]
+            fn $test() {
+                let values: [$t; 7] = [
+                    <$t>::MIN,
+                    <$t>::MIN + 1,
+                    (0 as $t).wrapping_sub(1),
+                    0,
+                    1,
+                    <$t>::MAX - 1,
+                    <$t>::MAX,
+                ];
+
+                for a in values {
+                    for b in values {
+                        let pair = (a, b);
+                        let add = a.wrapping_add(b);
+                        check(
+                            |overflow| Add { overflow },
+                            pair,
+                            add,
+                            a.saturating_add(b),
+                            a.checked_add(b),
+                        );
+                        let sub = a.wrapping_sub(b);
+                        check(
+                            |overflow| Sub { overflow },
+                            pair,
+                            sub,
+                            a.saturating_sub(b),
+                            a.checked_sub(b),
+                        );
+                        let mul = a.wrapping_mul(b);
+                        check(
+                            |overflow| Mul { overflow },
+                            pair,
+                            mul,
+                            a.saturating_mul(b),
+                            a.checked_mul(b),
+                        );
+
+                        if b == 0 {
+                            for overflow in
+                                [Overflow::Wrapping, Overflow::Saturating, Overflow::Checked]
+                            {
+                                assert_eq!(
+                                    Div { overflow }.try_calclate(a, b),
+                                    Err(OpError::DivisionByZero)
+                                );
+                                assert_eq!(
+                                    Rem { overflow }.try_calclate(a, b),
+                                    Err(OpError::DivisionByZero)
+                                );
+                            }
+                            continue;
+                        }
+
+                        let div = a.wrapping_div(b);
+                        check(
+                            |overflow| Div { overflow },
+                            pair,
+                            div,
+                            a.saturating_div(b),
+                            a.checked_div(b),
+                        );
+                        // MIN % -1 == 0 помещается в слово, хотя checked_rem в Rust возвращает None
+                        let rem = a.wrapping_rem(b);
+                        check(|overflow| Rem { overflow }, pair, rem, rem, Some(rem));
+                    }
+
+                    let neg = a.wrapping_neg();
+                    let saturating = (0 as $t).saturating_sub(a);
+                    let checked = a.checked_neg();
+                    for (overflow, expected) in [
+                        (Overflow::Wrapping, Ok(neg)),
+                        (Overflow::Saturating, Ok(saturating)),
+                        (
+                            Overflow::Checked,
+                            checked.ok_or(OpError::Overflow("neg".to_string())),
+                        ),
+                    ] {
+                        assert_eq!(Neg { overflow }.try_calclate(a), expected, "-{a}");
+                    }
+                }
+            }
+        };
+    }
+
+    boundary_test!(boundaries_u8, u8);
+    boundary_test!(boundaries_i8, i8);
+    boundary_test!(boundaries_u32, u32);
+    boundary_test!(boundaries_i32, i32);
+    boundary_test!(boundaries_u64, u64);
+    boundary_test!(boundaries_i64, i64);
+    boundary_test!(boundaries_u128, u128);
+    boundary_test!(boundaries_i128, i128);
+
+    #[test]
+    fn shift_left_is_multiplication() {
+        check(
+            |overflow| Shl { overflow },
+            (0x40u8, 1),
+            0x80,
+            0x80,
+            Some(0x80),
+        );
+        check(|overflow| Shl { overflow }, (0x80u8, 1), 0, u8::MAX, None);
+        check(
+            |overflow| Shl { overflow },
+            (-1i8, 7),
+            i8::MIN,
+            i8::MIN,
+            Some(i8::MIN),
+        );
+        check(
+            |overflow| Shl { overflow },
+            (1i8, 7),
+            i8::MIN,
+            i8::MAX,
+            None,
+        );
+        check(|overflow| Shl { overflow }, (1u64, 200), 0, u64::MAX, None);
+        check(|overflow| Shl { overflow }, (0u64, 200), 0, 0, Some(0));
+    }
+
+    #[test]
+    fn shifts_right_and_rotations_never_overflow() {
+        check(
+            |overflow| Shr { overflow },
+            (-128i8, 3),
+            -16,
+            -16,
+            Some(-16),
+        );
+        check(|overflow| Shr { overflow }, (-1i64, 100), -1, -1, Some(-1));
+        check(
+            |overflow| Rotl { overflow },
+            (0x81u8, 1),
+            0x03,
+            0x03,
+            Some(0x03),
+        );
+        check(
+            |overflow| Rotr { overflow },
+            (0x81u8, 1),
+            0xc0,
+            0xc0,
+            Some(0xc0),
+        );
+        check(|overflow| Rotl { overflow }, (i64::MIN, 65), 1, 1, Some(1));
+        check(
+            |overflow| Rotl { overflow },
+            (1u8, -1i8 as u8),
+            0x80,
+            0x80,
+            Some(0x80),
+        );
+    }
+
+    #[test]
+    fn widening_gives_high_half() {
+        let mul = Mul {
+            overflow: Overflow::Widening,
+        };
+        assert_eq!(mul.calclate_wide(u8::MAX, u8::MAX), Ok((0xfe, 0x01)));
+        assert_eq!(mul.calclate_wide(-128i8, -128i8), Ok((0x40, 0)));
+        assert_eq!(mul.calclate_wide(-1i8, 1), Ok((-1, -1)));
+        assert_eq!(mul.calclate_wide(u128::MAX, 2), Ok((1, u128::MAX - 1)));
+        let add = Add {
+            overflow: Overflow::Widening,
+        };
+        assert_eq!(add.calclate_wide(u64::MAX, 1), Ok((1, 0)));
+    }
+
+    #[test]
+    fn bitvec_matches_fixed_width() {
+        let bits = |value: u8| BitVec::from_u128(value as u128, 8);
+        for overflow in [Overflow::Wrapping, Overflow::Saturating, Overflow::Checked] {
+            for (a, b) in [(200u8, 100u8), (1, 2), (255, 255), (0, 255)] {
+                for op in arithmetic_operations::<u8>(overflow) {
+                    let wide = arithmetic_operations::<BitVec>(overflow)
+                        .into_iter()
+                        .find(|other| other.name() == op.name())
+                        .unwrap();
+                    assert_eq!(
+                        wide.try_calclate(bits(a), bits(b)),
+                        op.try_calclate(a, b).map(bits),
+                        "{a} {} {b} ({overflow:?})",
+                        op.name()
+                    );
+                }
+            }
+        }
+    }
+}
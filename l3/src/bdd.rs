@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+/// Вершина диаграммы -- номер в таблице вершин. Вершины 0 и 1 -- константы «ложь» и «истина».
+pub type Node = usize;
+
+/// Упорядоченная сокращённая двоичная диаграмма решений (ROBDD).
+///
+/// Каждая вершина проверяет одну переменную и ведёт в `low`, если переменная ложна,
+/// и в `high`, если истинна. Переменные на любом пути идут по возрастанию номеров,
+/// а одинаковые вершины не дублируются. Поэтому у каждой булевой функции ровно одна
+/// диаграмма, и две функции равны тогда и только тогда, когда равны их вершины.
+pub struct Bdd {
+    /// (переменная, low, high) для каждой вершины
+    nodes: Vec<(usize, Node, Node)>,
+    /// Таблица уникальности: по описанию вершины находит уже созданную
+    unique: HashMap<(usize, Node, Node), Node>,
+    /// Результаты apply, чтобы не считать одно и то же несколько раз
+    cache: HashMap<(u8, Node, Node), Node>,
+}
+
+impl Default for Bdd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bdd {
+    pub const FALSE: Node = 0;
+    pub const TRUE: Node = 1;
+
+    pub fn new() -> Self {
+        Self {
+            // У констант номер переменной больше любого настоящего
+            nodes: vec![(usize::MAX, 0, 0), (usize::MAX, 1, 1)],
+            unique: HashMap::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Количество вершин, включая константы
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn constant(value: bool) -> Node {
+        if value {
+            Self::TRUE
+        } else {
+            Self::FALSE
+        }
+    }
+
+    /// Функция, равная переменной с номером `var`
+    pub fn var(&mut self, var: usize) -> Node {
+        self.make(var, Self::FALSE, Self::TRUE)
+    }
+
+    /// Найти или создать вершину. Вершина с одинаковыми ветвями не нужна.
+    fn make(&mut self, var: usize, low: Node, high: Node) -> Node {
+        if low == high {
+            return low;
+        }
+
+        let next = self.nodes.len();
+        *self.unique.entry((var, low, high)).or_insert_with(|| {
+            self.nodes.push((var, low, high));
+            next
+        })
+    }
+
+    /// Применить к функциям `a` и `b` булеву операцию с таблицей истинности `table`
+    /// (строка i соответствует a = i >> 1, b = i & 1). Алгоритм Брайанта: обе функции
+    /// раскладываются по младшей из их верхних переменных, и операция применяется к ветвям.
+    pub fn apply(&mut self, table: [bool; 4], a: Node, b: Node) -> Node {
+        if a <= Self::TRUE && b <= Self::TRUE {
+            return Self::constant(table[a << 1 | b]);
+        }
+
+        let code = table
+            .iter()
+            .enumerate()
+            .fold(0u8, |acc, (i, &bit)| acc | (bit as u8) << i);
+        if let Some(&node) = self.cache.get(&(code, a, b)) {
+            return node;
+        }
+
+        let (var_a, low_a, high_a) = self.nodes[a];
+        let (var_b, low_b, high_b) = self.nodes[b];
+        let var = var_a.min(var_b);
+
+        // Если функция не зависит от var, обе её ветви -- она сама
+        let (low_a, high_a) = if var_a == var {
+            (low_a, high_a)
+        } else {
+            (a, a)
+        };
+        let (low_b, high_b) = if var_b == var {
+            (low_b, high_b)
+        } else {
+            (b, b)
+        };
+
+        let low = self.apply(table, low_a, low_b);
+        let high = self.apply(table, high_a, high_b);
+        let node = self.make(var, low, high);
+
+        self.cache.insert((code, a, b), node);
+        node
+    }
+
+    pub fn and(&mut self, a: Node, b: Node) -> Node {
+        self.apply([false, false, false, true], a, b)
+    }
+
+    pub fn or(&mut self, a: Node, b: Node) -> Node {
+        self.apply([false, true, true, true], a, b)
+    }
+
+    pub fn xor(&mut self, a: Node, b: Node) -> Node {
+        self.apply([false, true, true, false], a, b)
+    }
+
+    pub fn not(&mut self, a: Node) -> Node {
+        self.xor(a, Self::TRUE)
+    }
+
+    /// Какой-нибудь набор значений переменных, на котором функция истинна.
+    /// Переменные, которых нет в наборе, могут быть любыми.
+    pub fn satisfy(&self, mut node: Node) -> Option<Vec<(usize, bool)>> {
+        if node == Self::FALSE {
+            return None;
+        }
+
+        // Из любой вершины, кроме «ложь», есть путь в «истину»
+        let mut assignment = vec![];
+        while node != Self::TRUE {
+            let (var, low, high) = self.nodes[node];
+            if low != Self::FALSE {
+                assignment.push((var, false));
+                node = low;
+            } else {
+                assignment.push((var, true));
+                node = high;
+            }
+        }
+
+        Some(assignment)
+    }
+}
@@ -0,0 +1,456 @@
+use std::fmt::{self, Binary, Display, LowerHex, Octal};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::word::Word;
+
+/// Размер одного блока (limb) в битах
+const LIMB_BITS: usize = u64::BITS as usize;
+
+/// Битовый вектор произвольной длины. Биты хранятся блоками по 64 бита,
+/// младший блок идёт первым (little-endian), как в большинстве big integer библиотек.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitVec {
+    width: usize,
+    limbs: Vec<u64>,
+}
+
+impl BitVec {
+    /// Вектор из нулей заданной длины
+    pub fn zeros(width: usize) -> Self {
+        Self {
+            width,
+            limbs: vec![0; width.div_ceil(LIMB_BITS)],
+        }
+    }
+
+    /// Разобрать беззнаковое число из цифр в системе счисления `radix` (от 2 до 36).
+    /// Длина вектора -- столько целых блоков, сколько заняло число, но не меньше одного.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut limbs = vec![0u64];
+        for c in digits.chars() {
+            // Умножение на основание и прибавление цифры, как в wrapping_mul, только с ростом длины
+            let mut carry = c.to_digit(radix)? as u128;
+            for limb in &mut limbs {
+                let value = *limb as u128 * radix as u128 + carry;
+                *limb = value as u64;
+                carry = value >> LIMB_BITS;
+            }
+            if carry != 0 {
+                limbs.push(carry as u64);
+            }
+        }
+
+        Some(Self {
+            width: limbs.len() * LIMB_BITS,
+            limbs,
+        })
+    }
+
+    /// Значение бита с номером `index` (нулевой бит -- младший)
+    pub fn bit(&self, index: usize) -> bool {
+        index < self.width && (self.limbs[index / LIMB_BITS] >> (index % LIMB_BITS)) & 1 == 1
+    }
+
+    /// Установить бит с номером `index`. Биты за пределами длины игнорируются.
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        if index >= self.width {
+            return;
+        }
+
+        let mask = 1 << (index % LIMB_BITS);
+        if value {
+            self.limbs[index / LIMB_BITS] |= mask;
+        } else {
+            self.limbs[index / LIMB_BITS] &= !mask;
+        }
+    }
+
+    /// Вектор другой длины: лишние старшие биты отбрасываются, недостающие заполняются нулями
+    pub fn resize(&self, width: usize) -> Self {
+        let mut result = Self::zeros(width);
+        for (dst, src) in result.limbs.iter_mut().zip(&self.limbs) {
+            *dst = *src;
+        }
+        result.trim()
+    }
+
+    /// Все ли биты нулевые
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&limb| limb == 0)
+    }
+
+    /// Сложение по модулю 2^width
+    pub fn wrapping_add(&self, other: &Self) -> Self {
+        let mut result = Self::zeros(self.width);
+        let mut carry = false;
+
+        for (i, limb) in result.limbs.iter_mut().enumerate() {
+            let (sum, c1) = self.limbs[i].overflowing_add(other.limbs.get(i).copied().unwrap_or(0));
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+
+        result.trim()
+    }
+
+    /// Вычитание по модулю 2^width
+    pub fn wrapping_sub(&self, other: &Self) -> Self {
+        self.wrapping_add(&other.resize(self.width).wrapping_neg())
+    }
+
+    /// Смена знака в дополнительном коде: !x + 1
+    pub fn wrapping_neg(&self) -> Self {
+        (!self.clone()).wrapping_add(&Self::from_u128(1, self.width))
+    }
+
+    /// Умножение по модулю 2^width (умножение столбиком по 64-битным блокам)
+    pub fn wrapping_mul(&self, other: &Self) -> Self {
+        let mut result = Self::zeros(self.width);
+        let len = result.limbs.len();
+
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for j in 0..len - i {
+                let b = other.limbs.get(j).copied().unwrap_or(0) as u128;
+                let value = result.limbs[i + j] as u128 + a as u128 * b + carry;
+                result.limbs[i + j] = value as u64;
+                carry = value >> LIMB_BITS;
+            }
+        }
+
+        result.trim()
+    }
+
+    /// Беззнаковое деление с остатком (деление столбиком по одному биту).
+    /// Делитель не должен быть нулевым.
+    pub fn div_rem(&self, other: &Self) -> (Self, Self) {
+        let divisor = other.resize(self.width);
+        let mut quotient = Self::zeros(self.width);
+        let mut remainder = Self::zeros(self.width);
+
+        for i in (0..self.width).rev() {
+            remainder = remainder.shift_left(1);
+            remainder.set_bit(0, self.bit(i));
+            if !remainder.less_than(&divisor) {
+                remainder = remainder.wrapping_sub(&divisor);
+                quotient.set_bit(i, true);
+            }
+        }
+
+        (quotient, remainder)
+    }
+
+    /// Беззнаковое сравнение векторов одной длины
+    fn less_than(&self, other: &Self) -> bool {
+        self.limbs.iter().rev().lt(other.limbs.iter().rev())
+    }
+
+    /// Запись числа в системе счисления с основанием 2^`bits_per_digit`.
+    /// Количество цифр всегда соответствует длине вектора.
+    fn digits(&self, bits_per_digit: usize) -> String {
+        (0..self.width.div_ceil(bits_per_digit))
+            .rev()
+            .map(|digit| {
+                let value = (0..bits_per_digit)
+                    .filter(|bit| self.bit(digit * bits_per_digit + bit))
+                    .fold(0, |acc, bit| acc | 1 << bit);
+                char::from_digit(value, 1 << bits_per_digit).unwrap_or('?')
+            })
+            .collect()
+    }
+
+    /// Обнулить неиспользуемые биты старшего блока, чтобы они не влияли на сравнение и вывод
+    fn trim(mut self) -> Self {
+        let used = self.width % LIMB_BITS;
+        if used != 0 {
+            if let Some(last) = self.limbs.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+        self
+    }
+
+    /// Поблочно применить операцию к двум векторам. Более короткий вектор дополняется нулями.
+    fn zip_with(self, other: Self, op: impl Fn(u64, u64) -> u64) -> Self {
+        let width = self.width.max(other.width);
+        let mut result = Self::zeros(width);
+
+        for (i, limb) in result.limbs.iter_mut().enumerate() {
+            let a = self.limbs.get(i).copied().unwrap_or(0);
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            *limb = op(a, b);
+        }
+
+        result.trim()
+    }
+}
+
+impl BitAnd for BitVec {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for BitVec {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a | b)
+    }
+}
+
+impl BitXor for BitVec {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a ^ b)
+    }
+}
+
+impl Not for BitVec {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        for limb in &mut self.limbs {
+            *limb = !*limb;
+        }
+        self.trim()
+    }
+}
+
+// Вывод в двоичном виде, поддерживает флаги форматирования вроде {:#b}
+impl Binary for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0b", &self.digits(1))
+    }
+}
+
+impl Octal for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0o", &self.digits(3))
+    }
+}
+
+impl LowerHex for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.digits(4))
+    }
+}
+
+// Вывод в десятичном виде. Вектор трактуется как беззнаковое число и
+// многократно делится на 10^19 -- наибольшую степень десятки, которая влезает в u64.
+impl Display for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        let mut limbs = self.limbs.clone();
+        let mut chunks = vec![];
+
+        while limbs.iter().any(|&limb| limb != 0) {
+            let mut remainder = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let value = (remainder << LIMB_BITS) | *limb as u128;
+                *limb = (value / CHUNK as u128) as u64;
+                remainder = value % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+        }
+
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:019}"));
+        }
+
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl Word for BitVec {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn is_signed(&self) -> bool {
+        false
+    }
+
+    fn to_bitvec(&self) -> BitVec {
+        self.clone()
+    }
+
+    fn from_bitvec(bits: &BitVec) -> Self {
+        bits.clone()
+    }
+
+    fn from_u128(value: u128, width: usize) -> Self {
+        let mut result = Self::zeros(width);
+
+        for (i, limb) in result.limbs.iter_mut().take(2).enumerate() {
+            *limb = (value >> (i * LIMB_BITS)) as u64;
+        }
+
+        result.trim()
+    }
+
+    fn to_u128(&self) -> u128 {
+        self.limbs
+            .iter()
+            .take(2)
+            .enumerate()
+            .fold(0, |acc, (i, &limb)| acc | (limb as u128) << (i * LIMB_BITS))
+    }
+
+    // Вектор трактуется как беззнаковое число, поэтому оба сдвига логические
+    fn shift_left(self, n: usize) -> Self {
+        let mut result = Self::zeros(self.width);
+        for i in n..self.width {
+            result.set_bit(i, self.bit(i - n));
+        }
+        result
+    }
+
+    fn shift_right(self, n: usize) -> Self {
+        let mut result = Self::zeros(self.width);
+        for i in 0..self.width.saturating_sub(n) {
+            result.set_bit(i, self.bit(i + n));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::arith::{Div, Overflow, Rem};
+    use crate::expr::{parse_literal, ExprError, Parser};
+    use crate::operation::{operations, unary_operations, OpError, Operation};
+
+    fn eval(input: &str, width: usize) -> Result<BitVec, ExprError> {
+        Parser::new(
+            operations(Overflow::Wrapping),
+            unary_operations(Overflow::Wrapping),
+        )
+        .parse(input)?
+        .eval(&HashMap::new(), width)
+    }
+
+    fn decimal(input: &str, width: usize) -> String {
+        eval(input, width).unwrap().to_string()
+    }
+
+    #[test]
+    fn literals_of_any_length() {
+        assert_eq!(BitVec::from_str_radix("", 10), None);
+        assert_eq!(BitVec::from_str_radix("12a", 10), None);
+        assert_eq!(BitVec::from_str_radix("0", 10), Some(BitVec::zeros(64)));
+        // 2^64 уже не помещается в один блок
+        let bits = BitVec::from_str_radix("18446744073709551616", 10).unwrap();
+        assert_eq!(bits.width(), 128);
+        assert_eq!(bits.to_u128(), 1 << 64);
+
+        let wide = parse_literal(&format!("0x1{}", "0".repeat(50))).unwrap();
+        assert_eq!(wide.width(), 256);
+        assert_eq!(wide.to_u128(), 0);
+        assert!(wide.bit(200));
+        assert_eq!(
+            wide.to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(
+            parse_literal(&format!("0b1{}", "0_".repeat(200))),
+            Some(wide)
+        );
+    }
+
+    #[test]
+    fn wide_arithmetic() {
+        // 2^128 * 3 + 1
+        assert_eq!(
+            decimal("340282366920938463463374607431768211456 * 3 + 1", 256),
+            "1020847100762815390390123822295304634369"
+        );
+
+        // (2^200 + 12345678901234567890) / 3^100 и остаток
+        let a = "1606938044258990275541962092341162602522215339461694069869266";
+        let b = "515377520732011331036461129765621272702107522001";
+        assert_eq!(decimal(&format!("{a} / {b}"), 256), "3117982410207");
+        assert_eq!(
+            decimal(&format!("{a} % {b}"), 256),
+            "485474658062875558680597653747312484551810405059"
+        );
+        assert_eq!(decimal(&format!("{a} / {b} * {b} + {a} % {b}"), 256), a);
+
+        // Вычитание с заёмом через все блоки
+        assert_eq!(
+            format!(
+                "{:x}",
+                eval(&format!("0x1{} - 1", "0".repeat(50)), 256).unwrap()
+            ),
+            format!("{}{}", "0".repeat(14), "f".repeat(50))
+        );
+    }
+
+    #[test]
+    fn decimal_chunks_are_zero_padded() {
+        assert_eq!(BitVec::zeros(200).to_string(), "0");
+        assert_eq!(decimal("10000000000000000000", 128), "10000000000000000000");
+        assert_eq!(
+            decimal("100000000000000000000000000000000000005", 256),
+            "100000000000000000000000000000000000005"
+        );
+        assert_eq!(format!("{:>8}", BitVec::from_u128(42, 100)), "      42");
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let a = BitVec::from_u128(5, 200);
+        let zero = BitVec::zeros(200);
+        for overflow in [Overflow::Wrapping, Overflow::Saturating, Overflow::Checked] {
+            assert_eq!(
+                Div { overflow }.try_calclate(a.clone(), zero.clone()),
+                Err(OpError::DivisionByZero)
+            );
+            assert_eq!(
+                Rem { overflow }.try_calclate(a.clone(), zero.clone()),
+                Err(OpError::DivisionByZero)
+            );
+        }
+        assert_eq!(
+            eval("5 / (3 - 3)", 200),
+            Err(ExprError::Operation(OpError::DivisionByZero))
+        );
+    }
+
+    #[test]
+    fn width_not_multiple_of_limb_is_masked() {
+        let max = BitVec::from_u128((1 << 100) - 1, 100);
+        assert_eq!(!BitVec::zeros(100), max);
+        assert_eq!(BitVec::from_u128(u128::MAX, 100), max);
+        assert_eq!(eval("~0", 100), Ok(max.clone()));
+        assert_eq!(eval("-1", 100), Ok(max.clone()));
+        assert_eq!(decimal("~0", 100), "1267650600228229401496703205375");
+        assert_eq!(format!("{:x}", max), "f".repeat(25));
+
+        // Лишние старшие биты литерала, переноса и произведения отбрасываются
+        assert_eq!(
+            eval("0x10_0000_0000_0000_0000_0000_0001", 100),
+            Ok(BitVec::from_u128(1, 100))
+        );
+        assert_eq!(eval("~0 + 2", 100), Ok(BitVec::from_u128(1, 100)));
+        assert_eq!(
+            eval("0x8_0000_0000_0000_0000_0000_0000 * 2", 100),
+            Ok(BitVec::zeros(100))
+        );
+        assert_eq!(max.clone().shift_left(99), BitVec::from_u128(1 << 99, 100));
+        assert_eq!(max.resize(70), BitVec::from_u128((1 << 70) - 1, 70));
+    }
+}
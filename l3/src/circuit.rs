@@ -0,0 +1,849 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Display, Write as _};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use crate::expr::{is_identifier, parse_literal, Expr, ExprError, Parser};
+use crate::truth::MAX_TABLE_VARS;
+use crate::word::Word;
+
+/// Ошибка описания или моделирования схемы
+#[derive(Debug)]
+pub enum CircuitError {
+    /// Ошибка в строке netlist: файл, номер строки, описание
+    Syntax(String, usize, String),
+    Io(PathBuf, io::Error),
+    /// Схема собрана неправильно: неизвестный провод, цикл, два источника у провода
+    Invalid(String),
+    Expr(ExprError),
+}
+
+impl Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Syntax(file, line, msg) => write!(f, "{file}:{line}: {msg}"),
+            CircuitError::Io(path, e) => write!(f, "{}: {e}", path.display()),
+            CircuitError::Invalid(msg) => write!(f, "bad circuit: {msg}"),
+            CircuitError::Expr(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<ExprError> for CircuitError {
+    fn from(e: ExprError) -> Self {
+        CircuitError::Expr(e)
+    }
+}
+
+/// Вентиль: провод `output` получает значение выражения над другими проводами.
+/// Обычно выражение -- одна операция (`nand a b`), но можно записать и формулу.
+pub struct Gate<T> {
+    pub output: String,
+    pub expr: Expr<T>,
+    /// Путь экземпляра подсхемы, к которому относится вентиль (пустой для верхнего уровня)
+    pub instance: String,
+}
+
+/// Строка тела схемы
+enum Statement<T> {
+    Gate {
+        output: String,
+        expr: Expr<T>,
+    },
+    /// Экземпляр подсхемы: выходы подсхемы подключаются к `outputs`, входы -- к `args`
+    Instance {
+        outputs: Vec<String>,
+        circuit: String,
+        args: Vec<String>,
+    },
+}
+
+/// Описание схемы до разворачивания подсхем
+struct Definition<T> {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    body: Vec<Statement<T>>,
+}
+
+impl<T> Default for Definition<T> {
+    fn default() -> Self {
+        Self {
+            inputs: vec![],
+            outputs: vec![],
+            body: vec![],
+        }
+    }
+}
+
+/// Разбор netlist. Подсхемы нужно определить до их использования,
+/// поэтому рекурсивных схем не бывает.
+struct Netlist<'a, T> {
+    parser: &'a Parser<T>,
+    definitions: HashMap<String, Definition<T>>,
+    main: Definition<T>,
+    /// Уже подключённые файлы, чтобы include не зациклился
+    included: HashSet<PathBuf>,
+}
+
+impl<T: Word> Netlist<'_, T> {
+    /// Разобрать текст. Во включаемых файлах (`top == false`) допустимы только определения подсхем.
+    fn read(&mut self, text: &str, file: &str, dir: &Path, top: bool) -> Result<(), CircuitError> {
+        let mut current: Option<(String, Definition<T>)> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let error = |msg: String| CircuitError::Syntax(file.to_string(), number + 1, msg);
+
+            // Комментарии начинаются с #
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            let rest: Vec<String> = words.map(str::to_string).collect();
+
+            match keyword {
+                "include" => {
+                    let [path] = &rest[..] else {
+                        return Err(error("expected include FILE".to_string()));
+                    };
+                    self.include(&dir.join(path)).map_err(|e| match e {
+                        CircuitError::Io(path, e) => error(format!("{}: {e}", path.display())),
+                        e => e,
+                    })?;
+                }
+                "circuit" => {
+                    if current.is_some() {
+                        return Err(error("nested circuit definitions are not allowed".into()));
+                    }
+
+                    // circuit ИМЯ ВХОДЫ... -> ВЫХОДЫ...
+                    let arrow = rest.iter().position(|word| word == "->");
+                    let (Some(name), Some(arrow)) = (rest.first(), arrow) else {
+                        return Err(error(
+                            "expected circuit NAME INPUTS... -> OUTPUTS...".into(),
+                        ));
+                    };
+                    if self.definitions.contains_key(name) {
+                        return Err(error(format!("circuit '{name}' is already defined")));
+                    }
+
+                    let definition = Definition {
+                        inputs: wires(&rest[1..arrow]).map_err(error)?,
+                        outputs: wires(&rest[arrow + 1..]).map_err(error)?,
+                        body: vec![],
+                    };
+                    current = Some((name.clone(), definition));
+                }
+                "end" => {
+                    let Some((name, definition)) = current.take() else {
+                        return Err(error("'end' without 'circuit'".to_string()));
+                    };
+                    self.definitions.insert(name, definition);
+                }
+                "input" | "output" if current.is_none() && top => {
+                    let list = wires(&rest).map_err(error)?;
+                    if keyword == "input" {
+                        self.main.inputs.extend(list);
+                    } else {
+                        self.main.outputs.extend(list);
+                    }
+                }
+                _ => {
+                    let statement = self.statement(line).map_err(error)?;
+                    match current.as_mut() {
+                        Some((_, definition)) => definition.body.push(statement),
+                        None if top => self.main.body.push(statement),
+                        None => {
+                            return Err(error(
+                                "only circuit definitions are allowed in included files".into(),
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+
+        match current {
+            Some((name, _)) => Err(CircuitError::Syntax(
+                file.to_string(),
+                text.lines().count(),
+                format!("circuit '{name}' is not closed with 'end'"),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Подключить определения подсхем из другого файла
+    fn include(&mut self, path: &Path) -> Result<(), CircuitError> {
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| CircuitError::Io(path.to_path_buf(), e))?;
+        if !self.included.insert(canonical) {
+            return Ok(());
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| CircuitError::Io(path.to_path_buf(), e))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        self.read(&text, &path.display().to_string(), dir, false)
+    }
+
+    /// Разобрать строку `ВЫХОДЫ = ...`: экземпляр подсхемы, вентиль или формулу
+    fn statement(&self, line: &str) -> Result<Statement<T>, String> {
+        let Some((outputs, body)) = line.split_once('=') else {
+            return Err(format!("expected WIRE = GATE, got '{line}'"));
+        };
+        let outputs = wires(
+            &outputs
+                .split([',', ' '])
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>(),
+        )?;
+
+        let mut words = body.split_whitespace();
+        if let Some(circuit) = words
+            .next()
+            .filter(|name| self.definitions.contains_key(*name))
+        {
+            return Ok(Statement::Instance {
+                outputs,
+                circuit: circuit.to_string(),
+                args: words.map(str::to_string).collect(),
+            });
+        }
+
+        let [output] = &outputs[..] else {
+            return Err("a gate has exactly one output".to_string());
+        };
+
+        // Сначала пробуем префиксную запись вентиля (xor a b), затем обычную формулу (a ^ b)
+        let expr = match self.parser.parse_gate(body) {
+            Some(expr) => expr,
+            None => self.parser.parse(body).map_err(|e| e.to_string())?,
+        };
+
+        Ok(Statement::Gate {
+            output: output.clone(),
+            expr,
+        })
+    }
+}
+
+/// Проверить список имён проводов
+fn wires(names: &[String]) -> Result<Vec<String>, String> {
+    match names.iter().find(|name| !is_identifier(name)) {
+        Some(name) => Err(format!("bad wire name '{name}'")),
+        None => Ok(names.to_vec()),
+    }
+}
+
+/// Комбинационная схема из вентилей. Вентили хранятся в топологическом порядке:
+/// к моменту вычисления вентиля все его входы уже известны.
+pub struct Circuit<T> {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub gates: Vec<Gate<T>>,
+}
+
+impl<T: Word> Circuit<T> {
+    /// Прочитать схему из файла. Пути в include считаются от каталога файла.
+    pub fn from_file(path: &Path, parser: &Parser<T>) -> Result<Self, CircuitError> {
+        let text = fs::read_to_string(path).map_err(|e| CircuitError::Io(path.to_path_buf(), e))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&text, &path.display().to_string(), dir, parser)
+    }
+
+    /// Разобрать netlist вида
+    ///
+    /// ```text
+    /// circuit half_adder a b -> s c
+    ///     s = xor a b
+    ///     c = and a b
+    /// end
+    ///
+    /// input x y
+    /// output sum carry
+    /// sum, carry = half_adder x y
+    /// ```
+    pub fn parse(
+        text: &str,
+        file: &str,
+        dir: &Path,
+        parser: &Parser<T>,
+    ) -> Result<Self, CircuitError> {
+        let mut netlist = Netlist {
+            parser,
+            definitions: HashMap::new(),
+            main: Definition::default(),
+            included: HashSet::new(),
+        };
+        netlist.read(text, file, dir, true)?;
+
+        let main = &netlist.main;
+        let inputs: HashMap<String, Expr<T>> = main
+            .inputs
+            .iter()
+            .map(|name| (name.clone(), Expr::Var(name.clone())))
+            .collect();
+        let outputs: HashMap<String, String> = main
+            .outputs
+            .iter()
+            .map(|name| (name.clone(), name.clone()))
+            .collect();
+
+        let mut gates = vec![];
+        flatten(&netlist, main, "", &inputs, &outputs, &mut gates)?;
+
+        Self::new(main.inputs.clone(), main.outputs.clone(), gates)
+    }
+
+    /// Собрать схему из вентилей, проверив её и упорядочив вентили
+    pub fn new(
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+        gates: Vec<Gate<T>>,
+    ) -> Result<Self, CircuitError> {
+        let mut sources: HashSet<&str> = HashSet::new();
+        for name in &inputs {
+            if !sources.insert(name) {
+                return Err(CircuitError::Invalid(format!("input '{name}' is repeated")));
+            }
+        }
+        for gate in &gates {
+            if !sources.insert(&gate.output) {
+                return Err(CircuitError::Invalid(format!(
+                    "wire '{}' has more than one source",
+                    gate.output
+                )));
+            }
+        }
+
+        for gate in &gates {
+            if let Some(name) = gate
+                .expr
+                .variables()
+                .into_iter()
+                .find(|name| !sources.contains(name.as_str()))
+            {
+                return Err(CircuitError::Invalid(format!(
+                    "wire '{name}' used by '{}' has no source",
+                    gate.output
+                )));
+            }
+        }
+        if let Some(name) = outputs.iter().find(|name| !sources.contains(name.as_str())) {
+            return Err(CircuitError::Invalid(format!(
+                "output '{name}' has no source"
+            )));
+        }
+
+        let gates = topological_sort(&inputs, gates)?;
+        Ok(Self {
+            inputs,
+            outputs,
+            gates,
+        })
+    }
+
+    /// Схема на языке Graphviz DOT. Вентили подсхем группируются в рамки (cluster).
+    /// Посмотреть: `l3 circuit FILE --dot | dot -Tsvg > circuit.svg`
+    pub fn to_dot(&self) -> String {
+        let inputs: HashSet<&str> = self.inputs.iter().map(String::as_str).collect();
+        let node = |wire: &str| {
+            if inputs.contains(wire) {
+                format!("\"in:{wire}\"")
+            } else {
+                format!("\"gate:{wire}\"")
+            }
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph circuit {{");
+        let _ = writeln!(out, "    rankdir=LR;");
+        let _ = writeln!(out, "    node [fontname=\"Helvetica\"];");
+
+        for name in &self.inputs {
+            let _ = writeln!(out, "    {} [shape=circle, label=\"{name}\"];", node(name));
+        }
+
+        // Экземпляры подсхем вложены друг в друга: a.b лежит внутри a
+        let mut clusters: BTreeMap<&str, Vec<&Gate<T>>> = BTreeMap::new();
+        for gate in &self.gates {
+            let mut path = gate.instance.as_str();
+            clusters.entry(path).or_default().push(gate);
+            while let Some((parent, _)) = path.rsplit_once('.') {
+                clusters.entry(parent).or_default();
+                path = parent;
+            }
+            clusters.entry("").or_default();
+        }
+        write_cluster(&mut out, "", &clusters, &node, 1);
+
+        for gate in &self.gates {
+            for wire in gate.expr.variables() {
+                let _ = writeln!(
+                    out,
+                    "    {} -> {} [label=\"{}\"];",
+                    node(&wire),
+                    node(&gate.output),
+                    escape(short_name(&wire))
+                );
+            }
+        }
+
+        for name in &self.outputs {
+            let _ = writeln!(
+                out,
+                "    \"out:{name}\" [shape=doublecircle, label=\"{name}\"];"
+            );
+            let _ = writeln!(out, "    {} -> \"out:{name}\";", node(name));
+        }
+
+        let _ = writeln!(out, "}}");
+        out
+    }
+}
+
+impl Circuit<u8> {
+    /// Вычислить значения всех проводов схемы. Провода однобитные:
+    /// вентили вычисляются над u8, а значением считается младший бит, как в таблице истинности.
+    pub fn simulate(
+        &self,
+        inputs: &HashMap<String, bool>,
+    ) -> Result<HashMap<String, bool>, CircuitError> {
+        let mut values: HashMap<String, u8> = HashMap::new();
+        for name in &self.inputs {
+            let Some(&value) = inputs.get(name) else {
+                return Err(CircuitError::Invalid(format!(
+                    "no value for input '{name}'"
+                )));
+            };
+            values.insert(name.clone(), value as u8);
+        }
+
+        for gate in &self.gates {
+            let value = gate.expr.eval(&values, 8)?;
+            values.insert(gate.output.clone(), value & 1);
+        }
+
+        Ok(values
+            .into_iter()
+            .map(|(name, value)| (name, value == 1))
+            .collect())
+    }
+
+    /// Таблица истинности схемы: все наборы входов и значения выходов
+    pub fn table(&self) -> Result<String, CircuitError> {
+        let n = self.inputs.len();
+        if n > MAX_TABLE_VARS {
+            return Err(ExprError::TooManyVariables(n, MAX_TABLE_VARS).into());
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} | {}",
+            self.inputs.join(" "),
+            self.outputs.join(" ")
+        );
+        let _ = writeln!(
+            out,
+            "{}+{}",
+            self.inputs
+                .iter()
+                .map(|v| "-".repeat(v.chars().count() + 1))
+                .collect::<String>(),
+            self.outputs
+                .iter()
+                .map(|v| "-".repeat(v.chars().count() + 1))
+                .collect::<String>()
+        );
+
+        for row in 0..1usize << n {
+            let inputs: HashMap<String, bool> = self
+                .inputs
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), row >> (n - 1 - i) & 1 == 1))
+                .collect();
+            let values = self.simulate(&inputs)?;
+
+            for name in &self.inputs {
+                let _ = write!(
+                    out,
+                    "{:>width$} ",
+                    values[name] as u8,
+                    width = name.chars().count()
+                );
+            }
+            let _ = write!(out, "|");
+            for name in &self.outputs {
+                let _ = write!(
+                    out,
+                    " {:>width$}",
+                    values[name] as u8,
+                    width = name.chars().count()
+                );
+            }
+            let _ = writeln!(out);
+        }
+
+        Ok(out)
+    }
+}
+
+/// Развернуть подсхему в вентили. `inputs` -- чем заменить входы подсхемы,
+/// `outputs` -- как называются её выходы снаружи, `prefix` -- путь экземпляра.
+fn flatten<T: Word>(
+    netlist: &Netlist<T>,
+    definition: &Definition<T>,
+    prefix: &str,
+    inputs: &HashMap<String, Expr<T>>,
+    outputs: &HashMap<String, String>,
+    gates: &mut Vec<Gate<T>>,
+) -> Result<(), CircuitError> {
+    // Внутренние провода подсхемы получают имя с путём экземпляра, чтобы не совпасть с внешними
+    let local = |name: &str| -> String {
+        match outputs.get(name) {
+            Some(outer) => outer.clone(),
+            None if prefix.is_empty() => name.to_string(),
+            None => format!("{prefix}.{name}"),
+        }
+    };
+    let rename = |name: &str| -> Expr<T> {
+        match inputs.get(name) {
+            Some(expr) => expr.substitute(&|name| Expr::Var(name.to_string())),
+            None => Expr::Var(local(name)),
+        }
+    };
+
+    let mut counters: HashMap<&str, usize> = HashMap::new();
+
+    for statement in &definition.body {
+        match statement {
+            Statement::Gate { output, expr } => {
+                if inputs.contains_key(output) {
+                    return Err(CircuitError::Invalid(format!(
+                        "input '{output}' cannot be assigned"
+                    )));
+                }
+                gates.push(Gate {
+                    output: local(output),
+                    expr: expr.substitute(&rename),
+                    instance: prefix.to_string(),
+                });
+            }
+            Statement::Instance {
+                outputs: instance_outputs,
+                circuit,
+                args,
+            } => {
+                let inner = &netlist.definitions[circuit];
+                if args.len() != inner.inputs.len() || instance_outputs.len() != inner.outputs.len()
+                {
+                    return Err(CircuitError::Invalid(format!(
+                        "circuit '{circuit}' has {} inputs and {} outputs, got {} and {}",
+                        inner.inputs.len(),
+                        inner.outputs.len(),
+                        args.len(),
+                        instance_outputs.len()
+                    )));
+                }
+
+                // Входом экземпляра может быть провод или константа 0/1
+                let inner_inputs = inner
+                    .inputs
+                    .iter()
+                    .zip(args)
+                    .map(|(name, arg)| {
+                        let expr = match parse_literal(arg) {
+                            Some(value) => Expr::Const(value),
+                            None => rename(arg),
+                        };
+                        (name.clone(), expr)
+                    })
+                    .collect();
+                let inner_outputs = inner
+                    .outputs
+                    .iter()
+                    .zip(instance_outputs)
+                    .map(|(name, outer)| (name.clone(), local(outer)))
+                    .collect();
+
+                let counter = counters.entry(circuit.as_str()).or_default();
+                let name = format!("{circuit}{counter}");
+                *counter += 1;
+                let inner_prefix = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{prefix}.{name}")
+                };
+
+                flatten(
+                    netlist,
+                    inner,
+                    &inner_prefix,
+                    &inner_inputs,
+                    &inner_outputs,
+                    gates,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Упорядочить вентили так, чтобы каждый шёл после вентилей, от которых зависит
+/// (алгоритм Кана). Если упорядочить нельзя, в схеме есть цикл.
+fn topological_sort<T>(
+    inputs: &[String],
+    gates: Vec<Gate<T>>,
+) -> Result<Vec<Gate<T>>, CircuitError> {
+    let mut known: HashSet<String> = inputs.iter().cloned().collect();
+    let mut pending: Vec<(Gate<T>, BTreeSet<String>)> = gates
+        .into_iter()
+        .map(|gate| {
+            let deps = gate.expr.variables().into_iter().collect();
+            (gate, deps)
+        })
+        .collect();
+    let mut sorted = Vec::with_capacity(pending.len());
+
+    while !pending.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .partition(|(_, deps)| deps.iter().all(|dep| known.contains(dep)));
+
+        if ready.is_empty() {
+            let wires: Vec<&str> = rest.iter().map(|(gate, _)| gate.output.as_str()).collect();
+            return Err(CircuitError::Invalid(format!(
+                "combinational loop through {}",
+                wires.join(", ")
+            )));
+        }
+
+        for (gate, _) in ready {
+            known.insert(gate.output.clone());
+            sorted.push(gate);
+        }
+        pending = rest;
+    }
+
+    Ok(sorted)
+}
+
+/// Вывести рамку экземпляра `path` со всеми вложенными рамками
+fn write_cluster<T>(
+    out: &mut String,
+    path: &str,
+    clusters: &BTreeMap<&str, Vec<&Gate<T>>>,
+    node: &dyn Fn(&str) -> String,
+    depth: usize,
+) {
+    let indent = "    ".repeat(depth);
+
+    for gate in clusters.get(path).into_iter().flatten() {
+        let _ = writeln!(
+            out,
+            "{indent}{} [shape=box, label=\"{}\"];",
+            node(&gate.output),
+            escape(&gate_label(&gate.expr))
+        );
+    }
+
+    let children = clusters.keys().filter(|child| {
+        !child.is_empty()
+            && match child.rsplit_once('.') {
+                Some((parent, _)) => parent == path,
+                None => path.is_empty(),
+            }
+    });
+    for child in children {
+        let _ = writeln!(out, "{indent}subgraph \"cluster_{child}\" {{");
+        let _ = writeln!(out, "{indent}    label=\"{}\";", short_name(child));
+        write_cluster(out, child, clusters, node, depth + 1);
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+/// Подпись вентиля: имя операции, если это одна операция над проводами, иначе формула
+fn gate_label<T>(expr: &Expr<T>) -> String {
+    match expr {
+        Expr::Binary(op, a, b) if matches!(**a, Expr::Var(_)) && matches!(**b, Expr::Var(_)) => {
+            op.name().to_uppercase()
+        }
+        Expr::Unary(op, a) if matches!(**a, Expr::Var(_)) => op.name().to_uppercase(),
+        _ => {
+            // В формуле внутренние провода показываются без пути экземпляра
+            let expr = expr.substitute(&|name| Expr::Var(short_name(name).to_string()));
+            expr.to_string()
+        }
+    }
+}
+
+/// Имя провода без пути экземпляра подсхемы
+fn short_name(wire: &str) -> &str {
+    wire.rsplit_once('.').map_or(wire, |(_, name)| name)
+}
+
+/// Экранировать строку для подписи в DOT
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::Overflow;
+    use crate::operation::{operations, unary_operations};
+
+    fn parser() -> Parser<u8> {
+        Parser::new(
+            operations(Overflow::Wrapping),
+            unary_operations(Overflow::Wrapping),
+        )
+    }
+
+    fn parse(text: &str) -> Result<Circuit<u8>, CircuitError> {
+        Circuit::parse(text, "test.net", Path::new("."), &parser())
+    }
+
+    fn invalid(text: &str) -> String {
+        match parse(text) {
+            Err(CircuitError::Invalid(msg)) => msg,
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("circuit is accepted"),
+        }
+    }
+
+    fn simulate(circuit: &Circuit<u8>, inputs: &[(&str, bool)]) -> HashMap<String, bool> {
+        let inputs = inputs
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+        circuit.simulate(&inputs).unwrap()
+    }
+
+    #[test]
+    fn gates_are_sorted_topologically() {
+        // Вентили записаны в обратном порядке
+        let circuit = parse(
+            "input a b\n\
+             output y\n\
+             y = or p q\n\
+             q = and n b\n\
+             p = and a m\n\
+             m = not b\n\
+             n = not a\n",
+        )
+        .unwrap();
+
+        let order: Vec<&str> = circuit.gates.iter().map(|g| g.output.as_str()).collect();
+        let position = |wire| order.iter().position(|w| *w == wire).unwrap();
+        assert!(position("m") < position("p") && position("n") < position("q"));
+        assert_eq!(order.last(), Some(&"y"));
+
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert_eq!(simulate(&circuit, &[("a", a), ("b", b)])["y"], a ^ b);
+        }
+    }
+
+    #[test]
+    fn subcircuits_are_flattened() {
+        let circuit = parse(
+            "circuit half_adder a b -> s c\n\
+             \x20   s = xor a b\n\
+             \x20   c = and a b\n\
+             end\n\
+             input x y\n\
+             output sum carry\n\
+             sum, carry = half_adder x y\n",
+        )
+        .unwrap();
+
+        assert!(circuit.gates.iter().all(|g| g.instance == "half_adder0"));
+        let values = simulate(&circuit, &[("x", true), ("y", true)]);
+        assert_eq!((values["sum"], values["carry"]), (false, true));
+        assert!(circuit.table().unwrap().contains("1 1 |   0     1"));
+    }
+
+    #[test]
+    fn ripple_carry_adder_from_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("circuits/rca4.net");
+        let circuit = Circuit::from_file(&path, &parser()).unwrap();
+
+        // 5 + 9 + 1 = 15
+        let mut inputs = vec![("cin", true)];
+        let bits = ["a0", "a1", "a2", "a3", "b0", "b1", "b2", "b3"];
+        for (i, name) in bits.iter().enumerate() {
+            let value = if i < 4 { 5 } else { 9 };
+            inputs.push((name, value >> (i % 4) & 1 == 1));
+        }
+        let values = simulate(&circuit, &inputs);
+        let sum: u32 = ["s0", "s1", "s2", "s3", "cout"]
+            .iter()
+            .enumerate()
+            .map(|(i, wire)| (values[*wire] as u32) << i)
+            .sum();
+        assert_eq!(sum, 15);
+    }
+
+    #[test]
+    fn loops_are_rejected() {
+        let msg = invalid("input a\noutput x\nx = and a y\ny = not x\n");
+        assert_eq!(msg, "combinational loop through x, y");
+        let msg = invalid("input a\noutput x\nx = xor x a\n");
+        assert_eq!(msg, "combinational loop through x");
+    }
+
+    #[test]
+    fn undefined_and_conflicting_wires_are_rejected() {
+        assert_eq!(
+            invalid("input a\noutput x\nx = and a z\n"),
+            "wire 'z' used by 'x' has no source"
+        );
+        assert_eq!(
+            invalid("input a\noutput y\nx = not a\n"),
+            "output 'y' has no source"
+        );
+        assert_eq!(
+            invalid("input a\noutput x\nx = not a\nx = a\n"),
+            "wire 'x' has more than one source"
+        );
+        assert_eq!(
+            invalid("input a a\noutput x\nx = not a\n"),
+            "input 'a' is repeated"
+        );
+        assert_eq!(
+            invalid("input a\noutput a\na = not a\n"),
+            "input 'a' cannot be assigned"
+        );
+    }
+
+    #[test]
+    fn syntax_errors_have_line_numbers() {
+        let error = |text| match parse(text) {
+            Err(CircuitError::Syntax(file, line, msg)) => (file, line, msg),
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("circuit is accepted"),
+        };
+
+        assert_eq!(error("input a\nx and a\n").1, 2);
+        assert_eq!(error("input a\noutput 1x\n").2, "bad wire name '1x'");
+        let (file, line, msg) = error("circuit c a -> b\n b = not a\n");
+        assert_eq!((file.as_str(), line), ("test.net", 2));
+        assert_eq!(msg, "circuit 'c' is not closed with 'end'");
+        assert_eq!(error("end\n").2, "'end' without 'circuit'");
+    }
+
+    #[test]
+    fn missing_input_value() {
+        let circuit = parse("input a b\noutput x\nx = and a b\n").unwrap();
+        let inputs = HashMap::from([("a".to_string(), true)]);
+        assert!(matches!(
+            circuit.simulate(&inputs),
+            Err(CircuitError::Invalid(msg)) if msg == "no value for input 'b'"
+        ));
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::arith::Overflow;
+use crate::bdd::{Bdd, Node};
+use crate::bitvec::BitVec;
+use crate::expr::{Expr, ExprError};
+use crate::word::Word;
+
+/// Наибольшее число битов всех переменных вместе, при котором наборы перебираются полностью
+pub const MAX_EXHAUSTIVE_BITS: usize = 20;
+
+/// Способ проверки эквивалентности
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Method {
+    /// Перебор для маленьких слов, BDD для больших
+    #[default]
+    Auto,
+    /// Вычислить оба выражения на всех наборах значений переменных
+    Exhaustive,
+    /// Построить двоичные диаграммы решений для каждого бита результата и сравнить их
+    Bdd,
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Method::Auto),
+            "exhaustive" => Ok(Method::Exhaustive),
+            "bdd" => Ok(Method::Bdd),
+            _ => Err(format!(
+                "unknown method '{s}', expected auto, exhaustive or bdd"
+            )),
+        }
+    }
+}
+
+/// Ошибка проверки эквивалентности
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquivError {
+    Expr(ExprError),
+    /// Перебор слишком долгий: сколько битов, сколько можно
+    TooManyBits(usize, usize),
+    /// Операцию нельзя выразить через BDD
+    Unsupported(String),
+}
+
+impl Display for EquivError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquivError::Expr(e) => write!(f, "{e}"),
+            EquivError::TooManyBits(bits, max) => write!(
+                f,
+                "too many input bits for exhaustive check: {bits}, at most {max} supported"
+            ),
+            EquivError::Unsupported(msg) => write!(f, "BDD check is not possible: {msg}"),
+        }
+    }
+}
+
+impl From<ExprError> for EquivError {
+    fn from(e: ExprError) -> Self {
+        EquivError::Expr(e)
+    }
+}
+
+/// Результат проверки
+pub struct Report<T> {
+    /// Каким способом проверено (Exhaustive или Bdd)
+    pub method: Method,
+    /// Сколько наборов перебрано или сколько вершин в диаграмме
+    pub size: usize,
+    /// Набор значений переменных, на котором выражения различаются
+    pub counterexample: Option<Vec<(String, T)>>,
+}
+
+/// Проверить, что выражения `left` и `right` равны при любых значениях переменных.
+/// `overflow` -- политика арифметических операций в выражениях: BDD умеет только wrapping.
+pub fn check<T: Word>(
+    left: &Expr<T>,
+    right: &Expr<T>,
+    width: usize,
+    overflow: Overflow,
+    method: Method,
+) -> Result<Report<T>, EquivError> {
+    // Переменные обоих выражений, без повторов и по алфавиту
+    let mut vars = left.variables();
+    vars.extend(right.variables());
+    vars.sort();
+    vars.dedup();
+
+    let bits = vars.len() * width;
+    let method = match method {
+        Method::Auto if bits <= MAX_EXHAUSTIVE_BITS => Method::Exhaustive,
+        Method::Auto => Method::Bdd,
+        method => method,
+    };
+
+    match method {
+        Method::Exhaustive => exhaustive(left, right, &vars, width),
+        _ => bdd(left, right, &vars, width, overflow),
+    }
+}
+
+/// Полный перебор. Набор значений -- число, в котором переменной i отведены биты
+/// с i * width по (i + 1) * width - 1.
+fn exhaustive<T: Word>(
+    left: &Expr<T>,
+    right: &Expr<T>,
+    vars: &[String],
+    width: usize,
+) -> Result<Report<T>, EquivError> {
+    let bits = vars.len() * width;
+    if bits > MAX_EXHAUSTIVE_BITS {
+        return Err(EquivError::TooManyBits(bits, MAX_EXHAUSTIVE_BITS));
+    }
+
+    let mask = u128::MAX >> (u128::BITS as usize - width.min(u128::BITS as usize));
+    let mut values = HashMap::new();
+
+    for row in 0..1u128 << bits {
+        for (i, name) in vars.iter().enumerate() {
+            let value = row >> (i * width) & mask;
+            values.insert(name.clone(), T::from_u128(value, width));
+        }
+
+        // Ошибки (деление на ноль, переполнение) тоже сравниваются:
+        // эквивалентные выражения ошибаются на одних и тех же наборах
+        if left.eval(&values, width) != right.eval(&values, width) {
+            let counterexample = vars
+                .iter()
+                .map(|name| (name.clone(), values[name].clone()))
+                .collect();
+            return Ok(Report {
+                method: Method::Exhaustive,
+                size: row as usize + 1,
+                counterexample: Some(counterexample),
+            });
+        }
+    }
+
+    Ok(Report {
+        method: Method::Exhaustive,
+        size: 1 << bits,
+        counterexample: None,
+    })
+}
+
+/// Проверка через BDD: каждый бит результата -- булева функция от битов переменных.
+/// Биты разных переменных чередуются (a0 b0 a1 b1 ...), начиная с младших.
+/// При таком порядке диаграммы сумматоров растут линейно, а не экспоненциально.
+fn bdd<T: Word>(
+    left: &Expr<T>,
+    right: &Expr<T>,
+    vars: &[String],
+    width: usize,
+    overflow: Overflow,
+) -> Result<Report<T>, EquivError> {
+    let mut builder = Builder {
+        bdd: Bdd::new(),
+        vars,
+        width,
+        overflow,
+        signed: T::from_u128(0, width).is_signed(),
+    };
+
+    let left_bits = builder.bits(left)?;
+    let right_bits = builder.bits(right)?;
+    let bdd = &mut builder.bdd;
+
+    // Благодаря каноничности BDD равные функции -- это одна и та же вершина
+    let differs = left_bits
+        .iter()
+        .zip(&right_bits)
+        .find(|(l, r)| l != r)
+        .map(|(&l, &r)| bdd.xor(l, r));
+
+    let counterexample = differs
+        .and_then(|diff| bdd.satisfy(diff))
+        .map(|assignment| {
+            let mut values = vec![BitVec::zeros(width); vars.len()];
+            for (var, value) in assignment {
+                values[var % vars.len()].set_bit(var / vars.len(), value);
+            }
+
+            vars.iter()
+                .cloned()
+                .zip(values.iter().map(T::from_bitvec))
+                .collect()
+        });
+
+    Ok(Report {
+        method: Method::Bdd,
+        size: bdd.size(),
+        counterexample,
+    })
+}
+
+/// Построение BDD для битов выражения
+struct Builder<'a> {
+    bdd: Bdd,
+    vars: &'a [String],
+    width: usize,
+    overflow: Overflow,
+    signed: bool,
+}
+
+impl Builder<'_> {
+    /// BDD каждого бита значения выражения, начиная с младшего
+    fn bits<T: Word>(&mut self, expr: &Expr<T>) -> Result<Vec<Node>, EquivError> {
+        // Выражение без переменных просто вычисляется
+        if expr.variables().is_empty() {
+            let value = expr.eval(&HashMap::new(), self.width)?.to_bitvec();
+            return Ok((0..self.width)
+                .map(|i| Bdd::constant(value.bit(i)))
+                .collect());
+        }
+
+        match expr {
+            Expr::Const(_) => unreachable!("constants have no variables"),
+            Expr::Var(name) => {
+                let index = self.vars.iter().position(|var| var == name).unwrap();
+                Ok((0..self.width)
+                    .map(|bit| self.bdd.var(bit * self.vars.len() + index))
+                    .collect())
+            }
+            Expr::Unary(op, a) => {
+                let a = self.bits(a)?;
+
+                if let Some([zero, one]) = op.truth_table() {
+                    // Унарная таблица как бинарная, у которой второй операнд всегда ложен
+                    let table = [zero, zero, one, one];
+                    return Ok(a
+                        .into_iter()
+                        .map(|x| self.bdd.apply(table, x, Bdd::FALSE))
+                        .collect());
+                }
+
+                match op.name() {
+                    "neg" => {
+                        self.wrapping("neg")?;
+                        let zero = vec![Bdd::FALSE; self.width];
+                        Ok(self.sub(&zero, &a))
+                    }
+                    name => Err(EquivError::Unsupported(format!(
+                        "operation '{name}' is not bitwise"
+                    ))),
+                }
+            }
+            Expr::Binary(op, a, b) => {
+                if let Some(table) = op.truth_table() {
+                    let (a, b) = (self.bits(a)?, self.bits(b)?);
+                    return Ok(a
+                        .into_iter()
+                        .zip(b)
+                        .map(|(x, y)| self.bdd.apply(table, x, y))
+                        .collect());
+                }
+
+                let name = op.name();
+                match name {
+                    "add" | "sub" | "mul" => {
+                        self.wrapping(name)?;
+                        // Диаграмма произведения двух переменных растёт экспоненциально
+                        // с разрядностью, поэтому один множитель должен быть константой
+                        if name == "mul" && !a.variables().is_empty() && !b.variables().is_empty() {
+                            return Err(EquivError::Unsupported(
+                                "'mul' needs a constant operand".to_string(),
+                            ));
+                        }
+                        let (a, b) = (self.bits(a)?, self.bits(b)?);
+                        Ok(match name {
+                            "add" => self.add(&a, &b, Bdd::FALSE),
+                            "sub" => self.sub(&a, &b),
+                            _ => self.mul(&a, &b),
+                        })
+                    }
+                    "shl" | "shr" | "rotl" | "rotr" => {
+                        self.wrapping(name)?;
+                        // Сдвиг на константу -- просто перестановка битов
+                        if !b.variables().is_empty() {
+                            return Err(EquivError::Unsupported(format!(
+                                "'{name}' needs a constant shift amount"
+                            )));
+                        }
+                        let n = b.eval(&HashMap::new(), self.width)?.to_u128();
+                        let n = usize::try_from(n).unwrap_or(usize::MAX);
+                        let a = self.bits(a)?;
+                        Ok(self.shift(name, &a, n))
+                    }
+                    _ => Err(EquivError::Unsupported(format!(
+                        "operation '{name}' is not bitwise"
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Арифметика через BDD описывается только без обработки переполнения
+    fn wrapping(&self, name: &str) -> Result<(), EquivError> {
+        match self.overflow {
+            Overflow::Wrapping | Overflow::Widening => Ok(()),
+            _ => Err(EquivError::Unsupported(format!(
+                "'{name}' with overflow policy other than wrapping"
+            ))),
+        }
+    }
+
+    /// Сумматор с последовательным переносом
+    fn add(&mut self, a: &[Node], b: &[Node], carry: Node) -> Vec<Node> {
+        let bdd = &mut self.bdd;
+        let mut carry = carry;
+
+        a.iter()
+            .zip(b)
+            .map(|(&x, &y)| {
+                let half = bdd.xor(x, y);
+                let sum = bdd.xor(half, carry);
+                // Перенос: оба бита единицы или единица в одном из них и входной перенос
+                let both = bdd.and(x, y);
+                let propagated = bdd.and(half, carry);
+                carry = bdd.or(both, propagated);
+                sum
+            })
+            .collect()
+    }
+
+    /// a - b = a + ~b + 1
+    fn sub(&mut self, a: &[Node], b: &[Node]) -> Vec<Node> {
+        let not_b: Vec<Node> = b.iter().map(|&y| self.bdd.not(y)).collect();
+        self.add(a, &not_b, Bdd::TRUE)
+    }
+
+    /// Умножение сложением сдвинутых копий `a` для каждого бита `b`
+    fn mul(&mut self, a: &[Node], b: &[Node]) -> Vec<Node> {
+        let mut product = vec![Bdd::FALSE; self.width];
+
+        for (i, &y) in b.iter().enumerate() {
+            let partial: Vec<Node> = (0..self.width)
+                .map(|j| match j.checked_sub(i) {
+                    Some(k) => self.bdd.and(a[k], y),
+                    None => Bdd::FALSE,
+                })
+                .collect();
+            product = self.add(&product, &partial, Bdd::FALSE);
+        }
+
+        product
+    }
+
+    /// Сдвиги и циклические сдвиги на `n` разрядов
+    fn shift(&mut self, name: &str, a: &[Node], n: usize) -> Vec<Node> {
+        let width = self.width;
+        // Знаковый сдвиг вправо заполняет освободившиеся биты знаком
+        let fill = if self.signed {
+            a[width - 1]
+        } else {
+            Bdd::FALSE
+        };
+
+        (0..width)
+            .map(|i| match name {
+                "shl" => i.checked_sub(n).map_or(Bdd::FALSE, |j| a[j]),
+                "shr" => i
+                    .checked_add(n)
+                    .filter(|&j| j < width)
+                    .map_or(fill, |j| a[j]),
+                "rotl" => a[(i + width - n % width) % width],
+                _ => a[(i + n % width) % width],
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::expr::Parser;
+    use crate::operation::{operations, unary_operations};
+
+    fn parse<T: Word + 'static>(input: &str) -> Expr<T> {
+        Parser::new(
+            operations(Overflow::Wrapping),
+            unary_operations(Overflow::Wrapping),
+        )
+        .parse(input)
+        .unwrap()
+    }
+
+    /// Случайная формула над a, b и c из операций, которые понимает BDD
+    fn formula() -> impl Strategy<Value = String> {
+        let leaf = prop_oneof![
+            prop::sample::select(vec!["a", "b", "c"]).prop_map(str::to_string),
+            (0u8..16).prop_map(|n| n.to_string()),
+        ];
+        leaf.prop_recursive(4, 32, 2, |inner| {
+            let ops = vec!["&", "|", "^", "↑", "↓", "+", "-"];
+            let shifts = vec!["<<", ">>", "<<<", ">>>"];
+            prop_oneof![
+                (inner.clone(), prop::sample::select(ops), inner.clone())
+                    .prop_map(|(a, op, b)| format!("({a} {op} {b})")),
+                inner.clone().prop_map(|a| format!("~{a}")),
+                inner.clone().prop_map(|a| format!("-{a}")),
+                (inner.clone(), prop::sample::select(shifts), 0u8..6)
+                    .prop_map(|(a, op, n)| format!("({a} {op} {n})")),
+                (inner, 0u8..5).prop_map(|(a, n)| format!("({a} * {n})")),
+            ]
+        })
+    }
+
+    /// Оба способа должны дать один ответ, а контрпример BDD -- действительно различать выражения
+    fn agree<T: Word + 'static>(left: &str, right: &str, width: usize) {
+        let (left, right) = (parse::<T>(left), parse::<T>(right));
+        let check = |method| check(&left, &right, width, Overflow::Wrapping, method).unwrap();
+        let exhaustive = check(Method::Exhaustive);
+        let bdd = check(Method::Bdd);
+
+        assert_eq!(
+            exhaustive.counterexample.is_none(),
+            bdd.counterexample.is_none(),
+            "{left} vs {right}"
+        );
+        if let Some(counterexample) = bdd.counterexample {
+            let values: HashMap<String, T> = counterexample.into_iter().collect();
+            assert!(
+                left.eval(&values, width) != right.eval(&values, width),
+                "{left} vs {right}"
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn random_formulas(left in formula(), right in formula()) {
+            agree::<BitVec>(&left, &right, 4);
+        }
+
+        /// Случайные пары почти всегда различаются, поэтому отдельно проверяются
+        /// заведомо эквивалентные переписывания
+        #[test]
+        fn equivalent_rewrites(f in formula(), g in formula()) {
+            agree::<BitVec>(&f, &format!("~~{f}"), 4);
+            agree::<BitVec>(&f, &format!("({f} ^ {g}) ^ {g}"), 4);
+            agree::<BitVec>(&format!("{f} + {g}"), &format!("{g} - -{f}"), 4);
+            agree::<BitVec>(&format!("~({f} & {g})"), &format!("~{f} | ~{g}"), 4);
+        }
+
+        /// Знаковый тип: сдвиг вправо арифметический
+        #[test]
+        fn signed_formulas(left in formula(), right in formula()) {
+            let (left, right) = (left.replace('c', "a"), right.replace('c', "b"));
+            agree::<i8>(&left, &right, 8);
+        }
+    }
+
+    #[test]
+    fn known_identities() {
+        for (left, right) in [
+            ("a ↑ b", "~a | ~b"),
+            ("a ↓ b", "~a & ~b"),
+            ("a + b", "(a ^ b) + ((a & b) << 1)"),
+            ("a * 3", "a + a + a"),
+            ("-a", "~a + 1"),
+        ] {
+            for method in [Method::Exhaustive, Method::Bdd] {
+                let (l, r) = (parse::<u8>(left), parse::<u8>(right));
+                let report = check(&l, &r, 8, Overflow::Wrapping, method).unwrap();
+                assert!(report.counterexample.is_none(), "{left} vs {right}");
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_operations() {
+        let (left, right) = (parse::<u64>("a * b"), parse::<u64>("b * a"));
+        assert!(matches!(
+            check(&left, &right, 64, Overflow::Wrapping, Method::Bdd),
+            Err(EquivError::Unsupported(_))
+        ));
+        assert!(matches!(
+            check(&left, &right, 64, Overflow::Wrapping, Method::Exhaustive),
+            Err(EquivError::TooManyBits(128, MAX_EXHAUSTIVE_BITS))
+        ));
+
+        let (left, right) = (parse::<u64>("a + 1"), parse::<u64>("1 + a"));
+        assert!(matches!(
+            check(&left, &right, 64, Overflow::Checked, Method::Bdd),
+            Err(EquivError::Unsupported(_))
+        ));
+        let report = check(&left, &right, 64, Overflow::Wrapping, Method::Auto).unwrap();
+        assert_eq!(report.method, Method::Bdd);
+        assert!(report.counterexample.is_none());
+    }
+}
@@ -0,0 +1,605 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use crate::bitvec::BitVec;
+use crate::operation::{OpError, Operation, UnaryOperation};
+use crate::word::Word;
+
+/// ASCII и Unicode синонимы обозначений операций. Справа -- обозначение из `designation()`.
+const ALIASES: &[(&str, &str)] = &[
+    ("!&", "↑"),
+    ("~&", "↑"),
+    ("!|", "↓"),
+    ("~|", "↓"),
+    ("⊕", "^"),
+    ("∧", "&"),
+    ("∨", "|"),
+    ("!", "~"),
+    ("¬", "~"),
+];
+
+/// Приоритет бинарной операции: чем больше число, тем раньше она выполняется.
+/// Порядок такой же, как в C: умножение и деление, сложение и вычитание, сдвиги,
+/// затем AND, затем XOR, затем OR.
+fn precedence(name: &str) -> u8 {
+    match name {
+        "mul" | "div" | "rem" => 7,
+        "add" | "sub" => 6,
+        "shl" | "shr" | "rotl" | "rotr" => 5,
+        "and" | "nand" => 4,
+        "xor" => 3,
+        "or" | "nor" => 2,
+        _ => 1,
+    }
+}
+
+/// Ошибка разбора или вычисления выражения
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    /// Синтаксическая ошибка в позиции (номер символа)
+    Syntax(usize, String),
+    /// В выражении есть переменная без значения
+    UnknownVariable(String),
+    /// Переменных больше, чем можно перебрать (сколько есть, сколько можно)
+    TooManyVariables(usize, usize),
+    /// Ошибка вычисления операции
+    Operation(OpError),
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Syntax(pos, msg) => write!(f, "syntax error at {pos}: {msg}"),
+            ExprError::UnknownVariable(name) => write!(f, "unknown variable '{name}'"),
+            ExprError::TooManyVariables(count, max) => {
+                write!(f, "too many variables: {count}, at most {max} supported")
+            }
+            ExprError::Operation(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Синтаксическое дерево выражения. Узлы операций хранят сами реализации `Operation`.
+pub enum Expr<T> {
+    /// Константа. Разрядность вектора -- сколько заняли цифры литерала, при вычислении
+    /// он обрезается или дополняется нулями до разрядности слова.
+    Const(BitVec),
+    Var(String),
+    Unary(Rc<dyn UnaryOperation<T>>, Box<Expr<T>>),
+    Binary(Rc<dyn Operation<T>>, Box<Expr<T>>, Box<Expr<T>>),
+}
+
+impl<T> Expr<T> {
+    /// Имена всех переменных выражения в алфавитном порядке, без повторов
+    pub fn variables(&self) -> Vec<String> {
+        fn collect<T>(e: &Expr<T>, names: &mut BTreeSet<String>) {
+            match e {
+                Expr::Const(_) => {}
+                Expr::Var(name) => {
+                    names.insert(name.clone());
+                }
+                Expr::Unary(_, a) => collect(a, names),
+                Expr::Binary(_, a, b) => {
+                    collect(a, names);
+                    collect(b, names);
+                }
+            }
+        }
+
+        let mut names = BTreeSet::new();
+        collect(self, &mut names);
+        names.into_iter().collect()
+    }
+
+    /// Побитовое ли выражение: в нём только побитовые операции и нет констант.
+    /// Тогда каждый бит результата вычисляется по тем же битам переменных.
+    pub fn is_bitwise(&self) -> bool {
+        match self {
+            Expr::Const(_) => false,
+            Expr::Var(_) => true,
+            Expr::Unary(op, a) => op.truth_table().is_some() && a.is_bitwise(),
+            Expr::Binary(op, a, b) => {
+                op.truth_table().is_some() && a.is_bitwise() && b.is_bitwise()
+            }
+        }
+    }
+
+    /// Копия выражения, в которой каждая переменная заменена на результат `f`
+    pub fn substitute(&self, f: &dyn Fn(&str) -> Expr<T>) -> Expr<T> {
+        match self {
+            Expr::Const(value) => Expr::Const(value.clone()),
+            Expr::Var(name) => f(name),
+            Expr::Unary(op, a) => Expr::Unary(op.clone(), Box::new(a.substitute(f))),
+            Expr::Binary(op, a, b) => Expr::Binary(
+                op.clone(),
+                Box::new(a.substitute(f)),
+                Box::new(b.substitute(f)),
+            ),
+        }
+    }
+}
+
+impl<T: Word> Expr<T> {
+    /// Вычислить выражение. `width` нужна для констант, если T это битовый вектор.
+    pub fn eval(&self, vars: &HashMap<String, T>, width: usize) -> Result<T, ExprError> {
+        Ok(match self {
+            Expr::Const(value) => T::from_bitvec(&value.resize(width)),
+            Expr::Var(name) => vars
+                .get(name)
+                .cloned()
+                .ok_or_else(|| ExprError::UnknownVariable(name.clone()))?,
+            // Отрицательная константа, как и в Rust: -128 помещается в i8, хотя 128 -- нет.
+            // Поэтому минус перед числом не операция, а часть литерала.
+            Expr::Unary(op, a) if op.name() == "neg" && matches!(**a, Expr::Const(_)) => {
+                let Expr::Const(value) = &**a else {
+                    unreachable!()
+                };
+                let bits = value.resize(width.max(value.width()));
+                T::from_bitvec(&bits.wrapping_neg().resize(width))
+            }
+            Expr::Unary(op, a) => op
+                .try_calclate(a.eval(vars, width)?)
+                .map_err(ExprError::Operation)?,
+            Expr::Binary(op, a, b) => op
+                .try_calclate(a.eval(vars, width)?, b.eval(vars, width)?)
+                .map_err(ExprError::Operation)?,
+        })
+    }
+}
+
+// Вывод выражения с обозначениями операций. Вложенные бинарные операции берутся в скобки.
+impl<T> Display for Expr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn operand<T>(f: &mut fmt::Formatter<'_>, e: &Expr<T>) -> fmt::Result {
+            match e {
+                Expr::Binary(..) => write!(f, "({e})"),
+                _ => write!(f, "{e}"),
+            }
+        }
+
+        match self {
+            Expr::Const(value) => write!(f, "{value}"),
+            Expr::Var(name) => write!(f, "{name}"),
+            Expr::Unary(op, a) => {
+                // Словесное обозначение (например, у пользовательской операции) отделяется пробелом,
+                // как и вложенная унарная операция, чтобы - -5 не превратилось в --5
+                let designation = op.designation();
+                if designation.ends_with(|c: char| c.is_alphanumeric())
+                    || matches!(**a, Expr::Unary(..))
+                {
+                    write!(f, "{designation} ")?;
+                } else {
+                    write!(f, "{designation}")?;
+                }
+                operand(f, a)
+            }
+            Expr::Binary(op, a, b) => {
+                operand(f, a)?;
+                write!(f, " {} ", op.designation())?;
+                operand(f, b)
+            }
+        }
+    }
+}
+
+/// Лексема
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(BitVec),
+    Ident(String),
+    Symbol(String),
+    LParen,
+    RParen,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Num(value) => write!(f, "'{value}'"),
+            Token::Ident(s) | Token::Symbol(s) => write!(f, "'{s}'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+/// Разборщик выражений. Знает только те операции, которые ему передали.
+pub struct Parser<T> {
+    operations: Vec<Rc<dyn Operation<T>>>,
+    unary_operations: Vec<Rc<dyn UnaryOperation<T>>>,
+}
+
+impl<T: Word> Parser<T> {
+    pub fn new(
+        operations: Vec<Rc<dyn Operation<T>>>,
+        unary_operations: Vec<Rc<dyn UnaryOperation<T>>>,
+    ) -> Self {
+        Self {
+            operations,
+            unary_operations,
+        }
+    }
+
+    /// Разобрать строку в синтаксическое дерево
+    pub fn parse(&self, input: &str) -> Result<Expr<T>, ExprError> {
+        let tokens = self.tokenize(input)?;
+        let mut pos = 0;
+
+        let expr = self.parse_binary(&tokens, &mut pos, 0)?;
+
+        match tokens.get(pos) {
+            None => Ok(expr),
+            Some((at, token)) => Err(ExprError::Syntax(*at, format!("unexpected {token}"))),
+        }
+    }
+
+    /// Разобрать формулу. Одиночное имя или обозначение операции превращается
+    /// в её применение к переменным: `nand` -> `a ↑ b`, `not` -> `~a`.
+    pub fn parse_formula(&self, input: &str) -> Result<Expr<T>, ExprError> {
+        let input = input.trim();
+
+        if let Some(op) = self
+            .operations
+            .iter()
+            .find(|op| op.name() == input || op.designation() == input)
+        {
+            return Ok(Expr::Binary(
+                op.clone(),
+                Box::new(Expr::Var("a".to_string())),
+                Box::new(Expr::Var("b".to_string())),
+            ));
+        }
+
+        if let Some(op) = self
+            .unary_operations
+            .iter()
+            .find(|op| op.name() == input || op.designation() == input)
+        {
+            return Ok(Expr::Unary(
+                op.clone(),
+                Box::new(Expr::Var("a".to_string())),
+            ));
+        }
+
+        self.parse(input)
+    }
+
+    /// Разобрать вентиль в префиксной записи: имя операции и операнды через пробел,
+    /// например `nand a b` или `not a`. Операнды -- имена переменных или числа.
+    /// Если строка не похожа на вентиль, возвращается None.
+    pub fn parse_gate(&self, input: &str) -> Option<Expr<T>> {
+        let mut words = input.split_whitespace();
+        let name = Token::Ident(words.next()?.to_string());
+
+        let operands = words
+            .map(|word| match parse_literal(word) {
+                Some(value) => Some(Expr::Const(value)),
+                None if is_identifier(word) => Some(Expr::Var(word.to_string())),
+                None => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        match <[Expr<T>; 2]>::try_from(operands) {
+            Ok([a, b]) => Some(Expr::Binary(
+                self.binary_operation(&name)?,
+                Box::new(a),
+                Box::new(b),
+            )),
+            Err(operands) => match <[Expr<T>; 1]>::try_from(operands) {
+                Ok([a]) => Some(Expr::Unary(self.unary_operation(&name)?, Box::new(a))),
+                Err(_) => None,
+            },
+        }
+    }
+
+    /// Все символьные обозначения, которые понимает лексер.
+    /// Отсортированы по убыванию длины, чтобы `<<` не разобралось как два `<`.
+    fn symbols(&self) -> Vec<&str> {
+        let mut symbols: Vec<&str> = self
+            .operations
+            .iter()
+            .map(|op| op.designation())
+            .chain(self.unary_operations.iter().map(|op| op.designation()))
+            .chain(ALIASES.iter().map(|(alias, _)| *alias))
+            .collect();
+
+        symbols.sort_by_key(|s| std::cmp::Reverse(s.chars().count()));
+        symbols
+    }
+
+    /// Разбить строку на лексемы. Вместе с лексемой сохраняется её позиция для сообщений об ошибках.
+    fn tokenize(&self, input: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+        let symbols = self.symbols();
+        let chars: Vec<char> = input.chars().collect();
+        let mut tokens = vec![];
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            if c.is_whitespace() {
+                i += 1;
+            } else if c == '(' {
+                tokens.push((i, Token::LParen));
+                i += 1;
+            } else if c == ')' {
+                tokens.push((i, Token::RParen));
+                i += 1;
+            } else if c.is_ascii_digit() {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let value = parse_literal(&literal)
+                    .ok_or_else(|| ExprError::Syntax(start, format!("bad number '{literal}'")))?;
+                tokens.push((start, Token::Num(value)));
+            } else if c.is_alphabetic() || c == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push((start, Token::Ident(chars[start..i].iter().collect())));
+            } else {
+                let rest: String = chars[i..].iter().collect();
+                let symbol = symbols
+                    .iter()
+                    .find(|s| rest.starts_with(**s))
+                    .ok_or_else(|| ExprError::Syntax(i, format!("unexpected '{c}'")))?;
+
+                // Синонимы сразу заменяются на настоящие обозначения
+                let canonical = ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias == symbol)
+                    .map_or(*symbol, |(_, canonical)| canonical);
+
+                tokens.push((i, Token::Symbol(canonical.to_string())));
+                i += symbol.chars().count();
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// Найти бинарную операцию по обозначению или по имени (`a nand b`)
+    fn binary_operation(&self, token: &Token) -> Option<Rc<dyn Operation<T>>> {
+        self.operations
+            .iter()
+            .find(|op| match token {
+                Token::Symbol(s) => op.designation() == s,
+                Token::Ident(s) => op.name() == s,
+                _ => false,
+            })
+            .cloned()
+    }
+
+    /// Найти унарную операцию по обозначению или по имени (`not a`)
+    fn unary_operation(&self, token: &Token) -> Option<Rc<dyn UnaryOperation<T>>> {
+        self.unary_operations
+            .iter()
+            .find(|op| match token {
+                Token::Symbol(s) => op.designation() == s,
+                Token::Ident(s) => op.name() == s,
+                _ => false,
+            })
+            .cloned()
+    }
+
+    /// Разбор бинарных операций методом подъёма приоритетов (precedence climbing):
+    /// в цикле забираются только операции с приоритетом не ниже `min_precedence`.
+    fn parse_binary(
+        &self,
+        tokens: &[(usize, Token)],
+        pos: &mut usize,
+        min_precedence: u8,
+    ) -> Result<Expr<T>, ExprError> {
+        let mut lhs = self.parse_unary(tokens, pos)?;
+
+        while let Some(op) = tokens
+            .get(*pos)
+            .and_then(|(_, token)| self.binary_operation(token))
+        {
+            let precedence = precedence(op.name());
+            if precedence < min_precedence {
+                break;
+            }
+
+            *pos += 1;
+            // Все операции левоассоциативны, поэтому правая часть забирает только более приоритетные
+            let rhs = self.parse_binary(tokens, pos, precedence + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    /// Разбор операнда: числа, переменной, скобок или унарной операции
+    fn parse_unary(
+        &self,
+        tokens: &[(usize, Token)],
+        pos: &mut usize,
+    ) -> Result<Expr<T>, ExprError> {
+        let Some((at, token)) = tokens.get(*pos) else {
+            return Err(ExprError::Syntax(
+                tokens.last().map_or(0, |(at, _)| at + 1),
+                "unexpected end of expression".to_string(),
+            ));
+        };
+        *pos += 1;
+
+        if let Some(op) = self.unary_operation(token) {
+            return Ok(Expr::Unary(op, Box::new(self.parse_unary(tokens, pos)?)));
+        }
+
+        match token {
+            Token::Num(value) => Ok(Expr::Const(value.clone())),
+            Token::Ident(name) if self.binary_operation(token).is_none() => {
+                Ok(Expr::Var(name.clone()))
+            }
+            Token::LParen => {
+                let expr = self.parse_binary(tokens, pos, 0)?;
+                match tokens.get(*pos) {
+                    Some((_, Token::RParen)) => {
+                        *pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err(ExprError::Syntax(*at, "unclosed '('".to_string())),
+                }
+            }
+            _ => Err(ExprError::Syntax(*at, format!("unexpected {token}"))),
+        }
+    }
+}
+
+/// Является ли строка допустимым именем переменной
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Разобрать число в десятичной, шестнадцатеричной (0x), восьмеричной (0o) или двоичной (0b) записи
+pub fn parse_number(literal: &str) -> Option<u128> {
+    let (digits, radix) = split_radix(literal);
+    u128::from_str_radix(&digits, radix).ok()
+}
+
+/// Разобрать число так же, как parse_number, но без ограничения в 128 бит:
+/// константа в выражении над b<N> может быть любой длины
+pub fn parse_literal(literal: &str) -> Option<BitVec> {
+    let (digits, radix) = split_radix(literal);
+    BitVec::from_str_radix(&digits, radix)
+}
+
+/// Цифры литерала без префикса и подчёркиваний и основание системы счисления
+fn split_radix(literal: &str) -> (String, u32) {
+    let literal = literal.replace('_', "");
+    match literal.get(..2) {
+        Some("0x" | "0X") => (literal[2..].to_string(), 16),
+        Some("0o" | "0O") => (literal[2..].to_string(), 8),
+        Some("0b" | "0B") => (literal[2..].to_string(), 2),
+        _ => (literal, 10),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::Overflow;
+    use crate::operation::{operations, unary_operations};
+
+    fn parser() -> Parser<u8> {
+        Parser::new(
+            operations(Overflow::Wrapping),
+            unary_operations(Overflow::Wrapping),
+        )
+    }
+
+    /// Разобрать и вывести обратно: скобки показывают, как сгруппировалось выражение
+    fn grouped(input: &str) -> String {
+        parser().parse(input).unwrap().to_string()
+    }
+
+    fn eval(input: &str, a: u8, b: u8) -> Result<u8, ExprError> {
+        let vars = HashMap::from([("a".to_string(), a), ("b".to_string(), b)]);
+        parser().parse(input)?.eval(&vars, 8)
+    }
+
+    #[test]
+    fn precedence_like_c() {
+        assert_eq!(grouped("a | b & c"), "a | (b & c)");
+        assert_eq!(grouped("a & b | c"), "(a & b) | c");
+        assert_eq!(grouped("a | b ^ c & d"), "a | (b ^ (c & d))");
+        assert_eq!(grouped("a + b * c"), "a + (b * c)");
+        assert_eq!(grouped("a << 1 + b"), "a << (1 + b)");
+        assert_eq!(grouped("a & b + c"), "a & (b + c)");
+    }
+
+    #[test]
+    fn left_associativity_and_parentheses() {
+        assert_eq!(grouped("a - b - c"), "(a - b) - c");
+        assert_eq!(grouped("a - (b - c)"), "a - (b - c)");
+        assert_eq!(grouped("((a))"), "a");
+        assert_eq!(eval("a - b - 1", 10, 3), Ok(6));
+    }
+
+    #[test]
+    fn unary_operations_bind_tightest() {
+        assert_eq!(grouped("~a & b"), "~a & b");
+        assert_eq!(grouped("~(a & b)"), "~(a & b)");
+        assert_eq!(grouped("- -a"), "- -a");
+        assert_eq!(eval("~a & b", 0b1100, 0b1010), Ok(0b0000_0010));
+        assert_eq!(eval("not a", 0, 0), Ok(0xff));
+    }
+
+    #[test]
+    fn names_and_aliases() {
+        assert_eq!(grouped("a nand b"), "a ↑ b");
+        assert_eq!(grouped("a !& b"), "a ↑ b");
+        assert_eq!(grouped("a ∨ b ⊕ c"), "a | (b ^ c)");
+        assert_eq!(grouped("!a"), "~a");
+        assert_eq!(parser().parse_formula("nor").unwrap().to_string(), "a ↓ b");
+        assert_eq!(parser().parse_formula("~").unwrap().to_string(), "~a");
+        assert_eq!(parser().parse_gate("xor a 5").unwrap().to_string(), "a ^ 5");
+        assert!(parser().parse_gate("xor a b c").is_none());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("0x_ff"), Some(255));
+        assert_eq!(parse_number("0b1010"), Some(10));
+        assert_eq!(parse_number("0o17"), Some(15));
+        assert_eq!(parse_number("1_000"), Some(1000));
+        assert_eq!(parse_number("0xg"), None);
+        assert_eq!(eval("-128", 0, 0), Ok(0x80));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |input| parser().parse(input).err().unwrap();
+        assert_eq!(
+            error("a &"),
+            ExprError::Syntax(3, "unexpected end of expression".to_string())
+        );
+        assert_eq!(
+            error("(a | b"),
+            ExprError::Syntax(0, "unclosed '('".to_string())
+        );
+        assert_eq!(
+            error("a b"),
+            ExprError::Syntax(2, "unexpected 'b'".to_string())
+        );
+        assert_eq!(
+            error("a $ b"),
+            ExprError::Syntax(2, "unexpected '$'".to_string())
+        );
+        assert_eq!(
+            error("12ab"),
+            ExprError::Syntax(0, "bad number '12ab'".to_string())
+        );
+        assert_eq!(
+            error("a & )"),
+            ExprError::Syntax(4, "unexpected ')'".to_string())
+        );
+        assert_eq!(
+            error(""),
+            ExprError::Syntax(0, "unexpected end of expression".to_string())
+        );
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        assert_eq!(
+            eval("a & c", 1, 1),
+            Err(ExprError::UnknownVariable("c".to_string()))
+        );
+        assert!(matches!(eval("a / b", 1, 0), Err(ExprError::Operation(_))));
+    }
+
+    #[test]
+    fn variables_are_sorted_and_unique() {
+        let expr = parser().parse("c & a | c ^ b").unwrap();
+        assert_eq!(expr.variables(), ["a", "b", "c"]);
+    }
+}
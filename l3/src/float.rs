@@ -0,0 +1,430 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::expr::parse_number;
+use crate::operation::{OpError, Operation, UnaryOperation};
+use crate::word::Word;
+
+/// Формат числа с плавающей точкой IEEE-754
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Одинарная точность: 1 бит знака, 8 битов порядка, 23 бита мантиссы
+    #[default]
+    F32,
+    /// Двойная точность: 1 бит знака, 11 битов порядка, 52 бита мантиссы
+    F64,
+}
+
+impl Format {
+    /// Разрядность числа в битах
+    pub fn width(self) -> usize {
+        match self {
+            Format::F32 => 32,
+            Format::F64 => 64,
+        }
+    }
+
+    pub fn exponent_bits(self) -> usize {
+        match self {
+            Format::F32 => 8,
+            Format::F64 => 11,
+        }
+    }
+
+    pub fn mantissa_bits(self) -> usize {
+        self.width() - self.exponent_bits() - 1
+    }
+
+    /// Смещение порядка: в битах хранится порядок + bias, чтобы обойтись без знака
+    pub fn bias(self) -> i64 {
+        (1 << (self.exponent_bits() - 1)) - 1
+    }
+
+    fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.width())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "f32" => Ok(Format::F32),
+            "f64" => Ok(Format::F64),
+            _ => Err(format!("unknown format '{s}', expected f32 or f64")),
+        }
+    }
+}
+
+/// Класс числа, который определяется полями порядка и мантиссы
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    /// Порядок и мантисса нулевые (знак может быть любым: есть +0 и -0)
+    Zero,
+    /// Порядок нулевой, мантисса нет. Ведущая единица не подразумевается,
+    /// поэтому такие числа меньше наименьшего нормального и точность у них ниже.
+    Subnormal,
+    Normal,
+    /// Порядок из одних единиц, мантисса нулевая
+    Infinite,
+    /// Порядок из одних единиц, мантисса ненулевая. Старший бит мантиссы
+    /// отличает тихий NaN от сигнального, остальные биты -- полезная нагрузка.
+    Nan,
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Class::Zero => write!(f, "ноль"),
+            Class::Subnormal => write!(f, "субнормальное"),
+            Class::Normal => write!(f, "нормальное"),
+            Class::Infinite => write!(f, "бесконечность"),
+            Class::Nan => write!(f, "не число"),
+        }
+    }
+}
+
+/// Поле числа IEEE-754
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Sign,
+    Exponent,
+    Mantissa,
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Sign => write!(f, "знак"),
+            Field::Exponent => write!(f, "порядок"),
+            Field::Mantissa => write!(f, "мантисса"),
+        }
+    }
+}
+
+/// Ошибка разбора числа или применения операции к его битам
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FloatError {
+    /// Строка не число и не набор битов
+    Parse(String),
+    /// Набор битов не помещается в формат: сколько битов в формате
+    TooWide(String, usize),
+    Operation(OpError),
+}
+
+impl Display for FloatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloatError::Parse(s) => write!(f, "'{s}' is neither a number nor a bit pattern"),
+            FloatError::TooWide(s, width) => {
+                write!(f, "bit pattern {s} is wider than {width} bits")
+            }
+            FloatError::Operation(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<OpError> for FloatError {
+    fn from(e: OpError) -> Self {
+        FloatError::Operation(e)
+    }
+}
+
+/// Число с плавающей точкой, которое хранится как набор своих битов.
+/// Так к нему можно применять операции над словами: например, xor со знаковым
+/// битом меняет знак, а and с маской порядка оставляет только порядок.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Float {
+    pub format: Format,
+    pub bits: u64,
+}
+
+impl Float {
+    /// Лишние старшие биты отбрасываются
+    pub fn from_bits(bits: u64, format: Format) -> Self {
+        Self {
+            format,
+            bits: bits & format.mask(),
+        }
+    }
+
+    /// Разобрать число (1.5, -0, 1e-40, inf, nan) или набор битов с префиксом
+    /// 0x, 0o или 0b, например 0x80000000 -- знаковый бит f32
+    pub fn parse(input: &str, format: Format) -> Result<Self, FloatError> {
+        let input = input.trim();
+
+        if let Some("0x" | "0X" | "0o" | "0O" | "0b" | "0B") = input.get(..2) {
+            let bits = parse_number(input).ok_or_else(|| FloatError::Parse(input.to_string()))?;
+            if bits > format.mask() as u128 {
+                return Err(FloatError::TooWide(input.to_string(), format.width()));
+            }
+            return Ok(Self::from_bits(bits as u64, format));
+        }
+
+        let parsed = match format {
+            Format::F32 => input.parse::<f32>().map(|x| x.to_bits() as u64),
+            Format::F64 => input.parse::<f64>().map(f64::to_bits),
+        };
+        parsed
+            .map(|bits| Self::from_bits(bits, format))
+            .map_err(|_| FloatError::Parse(input.to_string()))
+    }
+
+    /// Значение числа. f32 представляется в f64 точно.
+    pub fn value(&self) -> f64 {
+        match self.format {
+            Format::F32 => f32::from_bits(self.bits as u32) as f64,
+            Format::F64 => f64::from_bits(self.bits),
+        }
+    }
+
+    /// Знаковый бит: true у отрицательных чисел (и у -0, и у NaN с единичным знаком)
+    pub fn sign(&self) -> bool {
+        self.bits >> (self.format.width() - 1) & 1 == 1
+    }
+
+    /// Поле порядка как есть, со смещением
+    pub fn exponent(&self) -> u64 {
+        self.bits >> self.format.mantissa_bits() & ((1 << self.format.exponent_bits()) - 1)
+    }
+
+    /// Поле мантиссы без подразумеваемой ведущей единицы
+    pub fn mantissa(&self) -> u64 {
+        self.bits & ((1 << self.format.mantissa_bits()) - 1)
+    }
+
+    pub fn class(&self) -> Class {
+        let max_exponent = (1 << self.format.exponent_bits()) - 1;
+        match (self.exponent(), self.mantissa()) {
+            (0, 0) => Class::Zero,
+            (0, _) => Class::Subnormal,
+            (e, 0) if e == max_exponent => Class::Infinite,
+            (e, _) if e == max_exponent => Class::Nan,
+            _ => Class::Normal,
+        }
+    }
+
+    /// Порядок без смещения, то есть степень двойки, на которую умножается мантисса.
+    /// У субнормальных чисел он такой же, как у наименьших нормальных.
+    /// У нуля, бесконечности и NaN порядка нет.
+    pub fn power(&self) -> Option<i64> {
+        match self.class() {
+            Class::Normal => Some(self.exponent() as i64 - self.format.bias()),
+            Class::Subnormal => Some(1 - self.format.bias()),
+            _ => None,
+        }
+    }
+
+    /// Тихий NaN: старший бит мантиссы единичный. Операции с тихим NaN дают NaN
+    /// без исключения, а сигнальный NaN вызывает исключение «недопустимая операция».
+    pub fn is_quiet_nan(&self) -> bool {
+        self.class() == Class::Nan && self.mantissa() >> (self.format.mantissa_bits() - 1) == 1
+    }
+
+    /// Поля, которые отличаются у двух чисел
+    pub fn changed_fields(&self, other: &Float) -> Vec<Field> {
+        let mut fields = vec![];
+        if self.sign() != other.sign() {
+            fields.push(Field::Sign);
+        }
+        if self.exponent() != other.exponent() {
+            fields.push(Field::Exponent);
+        }
+        if self.mantissa() != other.mantissa() {
+            fields.push(Field::Mantissa);
+        }
+        fields
+    }
+
+    /// Биты по полям: знак, порядок, мантисса через пробел
+    pub fn to_binary(&self) -> String {
+        format!(
+            "{} {:0ew$b} {:0mw$b}",
+            self.sign() as u8,
+            self.exponent(),
+            self.mantissa(),
+            ew = self.format.exponent_bits(),
+            mw = self.format.mantissa_bits(),
+        )
+    }
+
+    /// Применить операцию над словами к битам чисел. Тип слова T должен
+    /// быть той же разрядности, что и формат: u32 для f32, u64 для f64.
+    pub fn apply<T: Word>(
+        op: &dyn Operation<T>,
+        a: &Float,
+        b: &Float,
+    ) -> Result<Float, FloatError> {
+        let result = op.try_calclate(a.to_word(), b.to_word())?;
+        Ok(Self::from_bits(result.to_u128() as u64, a.format))
+    }
+
+    /// Применить унарную операцию над словами к битам числа
+    pub fn apply_unary<T: Word>(
+        op: &dyn UnaryOperation<T>,
+        a: &Float,
+    ) -> Result<Float, FloatError> {
+        let result = op.try_calclate(a.to_word())?;
+        Ok(Self::from_bits(result.to_u128() as u64, a.format))
+    }
+
+    fn to_word<T: Word>(self) -> T {
+        T::from_u128(self.bits as u128, self.format.width())
+    }
+}
+
+impl Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.value();
+        let magnitude = value.abs();
+
+        // Очень большие и очень маленькие числа в экспоненциальной записи
+        let scientific =
+            value.is_finite() && magnitude != 0.0 && !(1e-4..1e16).contains(&magnitude);
+        match (self.format, scientific) {
+            (Format::F32, false) => write!(f, "{}", value as f32),
+            (Format::F32, true) => write!(f, "{:e}", value as f32),
+            (Format::F64, false) => write!(f, "{value}"),
+            (Format::F64, true) => write!(f, "{value:e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operation::{And, Xor};
+
+    fn f32(input: &str) -> Float {
+        Float::parse(input, Format::F32).unwrap()
+    }
+
+    fn f64(input: &str) -> Float {
+        Float::parse(input, Format::F64).unwrap()
+    }
+
+    #[test]
+    fn signed_zeros() {
+        let (plus, minus) = (f32("0"), f32("-0"));
+        assert_eq!((plus.bits, minus.bits), (0, 0x8000_0000));
+        assert_eq!((plus.class(), minus.class()), (Class::Zero, Class::Zero));
+        assert!(!plus.sign() && minus.sign());
+        assert_eq!(plus.power(), None);
+        assert_eq!(plus.changed_fields(&minus), [Field::Sign]);
+        // Числа равны, хотя биты разные
+        assert_eq!(plus.value(), minus.value());
+        assert_eq!(minus.to_string(), "-0");
+        assert_eq!(f64("-0").bits, 1 << 63);
+    }
+
+    #[test]
+    fn subnormals() {
+        let smallest = Float::from_bits(1, Format::F32);
+        assert_eq!(smallest.class(), Class::Subnormal);
+        assert_eq!(smallest.value(), f32::from_bits(1) as f64);
+        assert_eq!(smallest.power(), Some(-126));
+        assert_eq!(smallest.to_string(), "1e-45");
+
+        let largest = Float::from_bits(0x007f_ffff, Format::F32);
+        assert_eq!(largest.class(), Class::Subnormal);
+        assert_eq!(
+            Float::from_bits(0x0080_0000, Format::F32).class(),
+            Class::Normal
+        );
+        assert_eq!(
+            Float::from_bits(0x0080_0000, Format::F32).power(),
+            largest.power()
+        );
+
+        // 1e-40 не помещается в нормальные f32, но в нормальные f64 -- да
+        assert_eq!(f32("1e-40").class(), Class::Subnormal);
+        assert_eq!(f64("1e-40").class(), Class::Normal);
+        assert_eq!(f64("5e-324").bits, 1);
+        assert_eq!(f64("5e-324").power(), Some(-1022));
+    }
+
+    #[test]
+    fn infinities_and_nans() {
+        let inf = f32("inf");
+        assert_eq!((inf.bits, inf.class()), (0x7f80_0000, Class::Infinite));
+        assert!(f32("-inf").sign());
+        assert_eq!(inf.power(), None);
+
+        let nan = f32("nan");
+        assert_eq!(nan.class(), Class::Nan);
+        assert!(nan.is_quiet_nan());
+        assert!(nan.value().is_nan());
+        assert_eq!(nan.to_string(), "NaN");
+
+        // Сигнальный NaN: старший бит мантиссы нулевой, но мантисса не нулевая
+        let signaling = f32("0x7f800001");
+        assert_eq!(signaling.class(), Class::Nan);
+        assert!(!signaling.is_quiet_nan());
+        assert!(!inf.is_quiet_nan());
+
+        // NaN с отрицательным знаком и полезной нагрузкой в f64
+        let payload = f64("0xfff8000000000042");
+        assert!(payload.sign() && payload.is_quiet_nan());
+        assert_eq!(payload.mantissa(), 0x8_0000_0000_0042);
+    }
+
+    #[test]
+    fn fields() {
+        let x = f32("-1.5");
+        assert_eq!(x.to_binary(), "1 01111111 10000000000000000000000");
+        assert_eq!(
+            (x.exponent(), x.mantissa(), x.power()),
+            (127, 0x40_0000, Some(0))
+        );
+        assert_eq!(f64("1").exponent(), 1023);
+    }
+
+    #[test]
+    fn bit_patterns() {
+        assert_eq!(f32("0b1").bits, 1);
+        assert_eq!(f32("0x3f800000").value(), 1.0);
+        assert_eq!(
+            Float::parse("0x100000000", Format::F32),
+            Err(FloatError::TooWide("0x100000000".to_string(), 32))
+        );
+        assert_eq!(
+            Float::parse("abc", Format::F64),
+            Err(FloatError::Parse("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn operations_on_bits() {
+        // xor со знаковым битом меняет знак, в том числе у нуля и NaN
+        let sign = f32("-0");
+        assert_eq!(
+            Float::apply::<u32>(&Xor, &f32("2.5"), &sign)
+                .unwrap()
+                .value(),
+            -2.5
+        );
+        assert_eq!(Float::apply::<u32>(&Xor, &f32("0"), &sign).unwrap(), sign);
+        let negated_nan = Float::apply::<u32>(&Xor, &f32("nan"), &sign).unwrap();
+        assert!(negated_nan.sign() && negated_nan.is_quiet_nan());
+
+        // and с маской порядка обнуляет мантиссу: 1.5 -> 1, субнормальное -> 0
+        let exponent_mask = f64("0x7ff0000000000000");
+        assert_eq!(
+            Float::apply::<u64>(&And, &f64("1.5"), &exponent_mask)
+                .unwrap()
+                .value(),
+            1.0
+        );
+        assert_eq!(
+            Float::apply::<u64>(&And, &f64("5e-324"), &exponent_mask)
+                .unwrap()
+                .class(),
+            Class::Zero
+        );
+        assert_eq!(
+            f64("1.5").changed_fields(&f64("-2.5")),
+            [Field::Sign, Field::Exponent, Field::Mantissa]
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! Побитовые и арифметические операции над словами любой разрядности.
+//! Библиотека отделена от приложения (main.rs), чтобы её можно было подключить
+//! в бенчмарках и других программах.
+
+pub mod arith;
+pub mod bdd;
+pub mod bitvec;
+pub mod circuit;
+pub mod equiv;
+pub mod expr;
+pub mod float;
+pub mod operation;
+pub mod properties;
+pub mod qmc;
+pub mod registry;
+pub mod repl;
+pub mod truth;
+pub mod word;
@@ -1,120 +1,646 @@
-trait Operation<T> {
-    fn name(&self) -> &'static str;
-    fn designation(&self) -> &'static str;
-    fn calclate(&self, a: T, b: T) -> T;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser as _, Subcommand};
+
+use l3::arith::{arithmetic_operations, Overflow};
+use l3::circuit::Circuit;
+use l3::equiv::{self, Method};
+use l3::expr::{self, ExprError};
+use l3::float::{Class, Field, Float, Format};
+use l3::operation::operations;
+use l3::registry::{self, Registry, RegistryError};
+use l3::repl::Repl;
+use l3::truth::TruthTable;
+use l3::with_word;
+use l3::word::{Word, WordType};
+
+#[derive(clap::Parser, Debug)]
+#[command(about = "Побитовые операции над целыми числами любой разрядности.", long_about = None)]
+struct Args {
+    /// Тип слова: u8..u128, i8..i128 или битовый вектор b<N> (например, b256)
+    #[arg(
+        short,
+        long = "type",
+        value_name = "TYPE",
+        default_value = "u8",
+        global = true
+    )]
+    ty: WordType,
+
+    /// Файл с пользовательскими операциями (по умолчанию ~/.config/l3/operations.txt)
+    #[arg(long, value_name = "FILE", global = true)]
+    ops: Option<PathBuf>,
+
+    /// Поведение арифметики при переполнении: wrapping, saturating, checked или widening
+    #[arg(long, value_name = "POLICY", default_value = "wrapping", global = true)]
+    overflow: Overflow,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Default)]
-struct Xor;
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Вычислить выражение, например "(a ^ b) ↑ ~c | 0xF0"
+    Eval {
+        /// Выражение
+        #[arg(allow_hyphen_values = true)]
+        expr: String,
+
+        /// Значение переменной, может быть выражением без переменных
+        #[arg(short, long = "var", value_name = "NAME=VALUE")]
+        vars: Vec<String>,
+    },
+    /// Применить все арифметические операции к двум числам с учётом политики переполнения
+    Arith {
+        /// Первый операнд (выражение без переменных, например -100 или 0xFF)
+        #[arg(allow_hyphen_values = true)]
+        a: String,
+
+        /// Второй операнд
+        #[arg(allow_hyphen_values = true)]
+        b: String,
+    },
+    /// Интерактивный калькулятор с переменными и историей
+    Repl,
+    /// Определить операцию и сохранить её в файл пользовательских операций,
+    /// например "xnor = ~(a ^ b)", "xnor ⊙ = ~(a ^ b)" или "imp = table 1101"
+    Define {
+        /// Определение вида ИМЯ [ОБОЗНАЧЕНИЕ] = ТЕЛО
+        definition: String,
+    },
+    /// Таблица истинности, минимальные ДНФ и КНФ и карта Карно.
+    /// Принимает выражение или имя операции (например, nand)
+    Table {
+        /// Выражение или имя операции
+        expr: String,
+    },
+    /// Проверить, что два выражения равны при любых значениях переменных.
+    /// Для маленьких слов -- перебором, для больших -- с помощью BDD.
+    /// Если выражения различаются, код завершения ненулевой
+    Equiv {
+        /// Первое выражение
+        #[arg(allow_hyphen_values = true)]
+        left: String,
+
+        /// Второе выражение
+        #[arg(allow_hyphen_values = true)]
+        right: String,
+
+        /// Способ проверки: auto, exhaustive или bdd
+        #[arg(long, default_value = "auto")]
+        method: Method,
+    },
+    /// Смоделировать логическую схему, описанную в netlist-файле
+    Circuit {
+        /// Файл со схемой
+        file: PathBuf,
+
+        /// Значение входа NAME=VALUE. Для шины a0, a1, ... можно задать сразу всё число: a=5
+        #[arg(short, long = "input", value_name = "NAME=VALUE")]
+        inputs: Vec<String>,
 
-impl Operation<u8> for Xor {
-    fn name(&self) -> &'static str {
-        "xor"
+        /// Вывести таблицу истинности схемы
+        #[arg(long)]
+        table: bool,
+
+        /// Вывести схему в формате Graphviz DOT
+        #[arg(long)]
+        dot: bool,
+    },
+    /// Алгебраические свойства операций для выбранного типа и политики переполнения
+    Props,
+    /// Разобрать число с плавающей точкой IEEE-754 на знак, порядок и мантиссу.
+    /// С операцией применяет её к битам числа, например "-1.5 xor 0x80000000" меняет знак
+    Float {
+        /// Число (1.5, 1e-40, inf, nan) или набор битов (0x7FC00000)
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+
+        /// Операция: имя или обозначение, бинарная или унарная
+        op: Option<String>,
+
+        /// Второй операнд: число или набор битов
+        #[arg(allow_hyphen_values = true)]
+        operand: Option<String>,
+
+        /// Формат: f32 или f64
+        #[arg(short, long, default_value = "f32")]
+        format: Format,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let path = args.ops.clone().or_else(registry::default_path);
+    let path = path.as_deref();
+
+    // Тип выбирается во время выполнения, а функции обобщённые,
+    // поэтому макрос подставляет нужный тип вместо T
+    match args.command {
+        None => {
+            with_word!(args.ty, demo(args.overflow, args.ty.width()));
+            ExitCode::SUCCESS
+        }
+        Some(Command::Eval { expr, vars }) => report(with_word!(
+            args.ty,
+            eval(path, args.overflow, &expr, &vars, args.ty.width())
+        )),
+        Some(Command::Arith { a, b }) => report(with_word!(
+            args.ty,
+            arith(&a, &b, args.overflow, args.ty.width())
+        )),
+        Some(Command::Repl) => report(with_word!(
+            args.ty,
+            repl(path, args.overflow, args.ty.width())
+        )),
+        Some(Command::Define { definition }) => report(define(path, &definition)),
+        Some(Command::Table { expr }) => report(table(path, args.overflow, &expr)),
+        Some(Command::Equiv {
+            left,
+            right,
+            method,
+        }) => {
+            let result = with_word!(
+                args.ty,
+                equiv(path, args.overflow, &left, &right, method, args.ty.width())
+            );
+            // Как у diff и cmp: различие выражений -- не ошибка, но и не успех
+            match result {
+                Ok(true) => ExitCode::SUCCESS,
+                Ok(false) => ExitCode::FAILURE,
+                Err(e) => report(Err(e)),
+            }
+        }
+        Some(Command::Circuit {
+            file,
+            inputs,
+            table,
+            dot,
+        }) => report(circuit(path, args.overflow, &file, &inputs, table, dot)),
+        Some(Command::Props) => report(with_word!(args.ty, props(path, args.overflow))),
+        Some(Command::Float {
+            value,
+            op,
+            operand,
+            format,
+        }) => {
+            let (op, operand) = (op.as_deref(), operand.as_deref());
+            // Биты f32 обрабатываются как u32, биты f64 -- как u64
+            report(match format {
+                Format::F32 => float::<u32>(path, args.overflow, format, &value, op, operand),
+                Format::F64 => float::<u64>(path, args.overflow, format, &value, op, operand),
+            })
+        }
     }
+}
 
-    fn designation(&self) -> &'static str {
-        "^"
+/// Вывести ошибку команды, если она есть, и вернуть код завершения
+fn report(result: Result<(), impl Display>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Ошибка: {e}");
+            ExitCode::FAILURE
+        }
     }
+}
+
+/// Вывести объявленные свойства всех операций, включая пользовательские
+fn props<T: Word + 'static>(path: Option<&Path>, overflow: Overflow) -> Result<(), RegistryError> {
+    let registry = Registry::<T>::with_file(path, overflow)?;
+    let list = |names: Vec<String>| {
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
 
-    fn calclate(&self, a: u8, b: u8) -> u8 {
-        a ^ b
+    for op in registry.operations() {
+        let properties = list(op.properties().names());
+        println!("{:>4}  {:<8} {}", op.designation(), op.name(), properties);
     }
+    for op in registry.unary_operations() {
+        let properties = list(op.properties().names());
+        println!(
+            "{:>4}  {:<8} {} (унарная)",
+            op.designation(),
+            op.name(),
+            properties
+        );
+    }
+
+    Ok(())
 }
 
-#[derive(Default)]
-struct Or;
+/// Вывести поля числа IEEE-754, а если задана операция -- её операнды, результат
+/// и то, какие поля она изменила
+fn float<T: Word + 'static>(
+    path: Option<&Path>,
+    overflow: Overflow,
+    format: Format,
+    value: &str,
+    op: Option<&str>,
+    operand: Option<&str>,
+) -> Result<(), String> {
+    let registry = Registry::<T>::with_file(path, overflow).map_err(|e| e.to_string())?;
+    let a = Float::parse(value, format).map_err(|e| e.to_string())?;
+    let matches = |name: &str, designation: &str| Some(name) == op || Some(designation) == op;
 
-impl Operation<u8> for Or {
-    fn name(&self) -> &'static str {
-        "or"
+    let mut rows = vec![("a".to_string(), a)];
+    match (op, operand) {
+        (None, _) => {}
+        (Some(name), None) => {
+            let Some(op) = registry
+                .unary_operations()
+                .into_iter()
+                .find(|op| matches(op.name(), op.designation()))
+            else {
+                return Err(format!("unknown unary operation '{name}'"));
+            };
+            let result = Float::apply_unary(op.as_ref(), &a).map_err(|e| e.to_string())?;
+            rows.push((format!("{}a", op.designation()), result));
+        }
+        (Some(name), Some(operand)) => {
+            let Some(op) = registry
+                .operations()
+                .into_iter()
+                .find(|op| matches(op.name(), op.designation()))
+            else {
+                return Err(format!("unknown operation '{name}'"));
+            };
+            let b = Float::parse(operand, format).map_err(|e| e.to_string())?;
+            let result = Float::apply(op.as_ref(), &a, &b).map_err(|e| e.to_string())?;
+            rows.push(("b".to_string(), b));
+            rows.push((format!("a {} b", op.designation()), result));
+        }
     }
 
-    fn designation(&self) -> &'static str {
-        "|"
+    let label = rows
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let exponent = format.exponent_bits() + 12;
+    println!("{:label$}  знак {:exponent$} мантисса", "", "порядок");
+    for (name, x) in &rows {
+        let power = match x.power() {
+            Some(power) => format!(
+                "{:0w$b} (2^{power})",
+                x.exponent(),
+                w = format.exponent_bits()
+            ),
+            None => format!("{:0w$b}", x.exponent(), w = format.exponent_bits()),
+        };
+        println!(
+            "{name:label$}  {}    {power:exponent$} {:0w$b}  {x} ({})",
+            x.sign() as u8,
+            x.mantissa(),
+            x.class(),
+            w = format.mantissa_bits(),
+        );
     }
 
-    fn calclate(&self, a: u8, b: u8) -> u8 {
-        a | b
+    // Последняя строка -- результат операции, его сравнение с первым операндом
+    let Some((_, result)) = rows.get(1..).and_then(|rest| rest.last()) else {
+        return Ok(());
+    };
+
+    println!();
+    let changed = a.changed_fields(result);
+    if changed.is_empty() {
+        println!("Операция не изменила ни одного поля");
     }
+    for field in changed {
+        let (before, after) = match field {
+            Field::Sign => (a.sign() as u64, result.sign() as u64),
+            Field::Exponent => (a.exponent(), result.exponent()),
+            Field::Mantissa => (a.mantissa(), result.mantissa()),
+        };
+        let flipped = (before ^ after).count_ones();
+        println!("Изменено поле «{field}»: {before:#x} -> {after:#x}, битов: {flipped}");
+    }
+
+    match result.class() {
+        Class::Nan => {
+            let kind = if result.is_quiet_nan() {
+                "тихий"
+            } else {
+                "сигнальный"
+            };
+            println!(
+                "Внимание: результат -- {kind} NaN, мантисса {:#x}",
+                result.mantissa()
+            );
+        }
+        Class::Subnormal => println!(
+            "Внимание: результат субнормальный, ведущая единица мантиссы не подразумевается \
+             и точность ниже, чем у нормальных чисел"
+        ),
+        _ => {}
+    }
+
+    Ok(())
 }
 
-#[derive(Default)]
-struct And;
+/// Применить все операции к двум константам и вывести результат
+fn demo<T: Word>(overflow: Overflow, width: usize) {
+    let a = T::from_u128(0b1100, width);
+    let b = T::from_u128(0b1010, width);
+
+    for op in operations::<T>(overflow) {
+        let result = match op.try_calclate(a.clone(), b.clone()) {
+            Ok(result) => result,
+            Err(e) => {
+                println!("{} {} {}: {} <-- {}", a, op.designation(), b, e, op.name());
+                continue;
+            }
+        };
 
-impl Operation<u8> for And {
-    fn name(&self) -> &'static str {
-        "and"
+        println!(
+            "{} {} {} = {} ({} {} {} = {}) <-- {}",
+            a,
+            op.designation(),
+            b,
+            result,
+            a.to_binary(),
+            op.designation(),
+            b.to_binary(),
+            result.to_binary(),
+            op.name(),
+        );
     }
+}
+
+/// Разобрать и вычислить выражение с переменными вида NAME=VALUE
+fn eval<T: Word + 'static>(
+    path: Option<&Path>,
+    overflow: Overflow,
+    input: &str,
+    vars: &[String],
+    width: usize,
+) -> Result<(), RegistryError> {
+    let parser = Registry::<T>::with_file(path, overflow)?.parser();
 
-    fn designation(&self) -> &'static str {
-        "&"
+    let mut values = HashMap::new();
+    for var in vars {
+        let Some((name, value)) = var.split_once('=') else {
+            return Err(ExprError::Syntax(0, format!("expected NAME=VALUE, got '{var}'")).into());
+        };
+        let value = parser.parse(value)?.eval(&HashMap::new(), width)?;
+        values.insert(name.trim().to_string(), value);
     }
 
-    fn calclate(&self, a: u8, b: u8) -> u8 {
-        a & b
+    let expr = parser.parse(input)?;
+    let result = expr.eval(&values, width)?;
+
+    println!("{} = {} ({})", expr, result, result.to_binary());
+    Ok(())
+}
+
+/// Применить арифметические операции к двум числам. При политике widening
+/// выводится результат двойной разрядности: старшая и младшая половины.
+fn arith<T: Word + 'static>(
+    a: &str,
+    b: &str,
+    overflow: Overflow,
+    width: usize,
+) -> Result<(), ExprError> {
+    let parser = Registry::<T>::new(overflow).parser();
+    let vars = HashMap::new();
+    let a = parser.parse(a)?.eval(&vars, width)?;
+    let b = parser.parse(b)?.eval(&vars, width)?;
+
+    for op in arithmetic_operations::<T>(overflow) {
+        let result = if overflow == Overflow::Widening {
+            op.calclate_wide(a.clone(), b.clone())
+                .map(|(high, low)| format!("{}:{}", high.to_binary(), low.to_binary()))
+        } else {
+            op.try_calclate(a.clone(), b.clone())
+                .map(|result| format!("{} ({})", result, result.to_binary()))
+        };
+
+        match result {
+            Ok(result) => println!("{} {} {} = {}", a, op.designation(), b, result),
+            Err(e) => println!("{} {} {}: {}", a, op.designation(), b, e),
+        }
     }
+
+    Ok(())
 }
 
-#[derive(Default)]
-struct Nand;
+/// Запустить интерактивный калькулятор
+fn repl<T: Word + 'static>(
+    path: Option<&Path>,
+    overflow: Overflow,
+    width: usize,
+) -> Result<(), String> {
+    let registry = Registry::<T>::with_file(path, overflow).map_err(|e| e.to_string())?;
+    Repl::new(registry, path.map(Path::to_path_buf), width)
+        .run()
+        .map_err(|e| e.to_string())
+}
+
+/// Добавить операцию в файл пользовательских операций
+fn define(path: Option<&Path>, definition: &str) -> Result<(), RegistryError> {
+    let Some(path) = path else {
+        return Err(RegistryError::Definition("no operations file".to_string()));
+    };
+
+    // Тип слова не важен: сохраняется только текст определения
+    let mut registry = Registry::<u8>::with_file(Some(path), Overflow::default())?;
+    registry.define(definition)?;
+    registry.save(path)?;
 
-impl Operation<u8> for Nand {
-    fn name(&self) -> &'static str {
-        "nand"
+    println!("Операция сохранена в {}", path.display());
+    Ok(())
+}
+
+/// Вывести таблицу истинности формулы, её минимальные формы и карту Карно
+fn table(path: Option<&Path>, overflow: Overflow, input: &str) -> Result<(), RegistryError> {
+    let parser = Registry::<u8>::with_file(path, overflow)?.parser();
+    let expr = parser.parse_formula(input)?;
+    let table = TruthTable::from_expr(&expr)?;
+
+    println!("f = {expr}\n");
+    print!("{}", table.render());
+
+    match (table.sop(), table.pos()) {
+        (Some(sop), Some(pos)) => {
+            println!("\nДНФ: {}", sop.text);
+            println!("КНФ: {}", pos.text);
+            if !sop.minimal || !pos.minimal {
+                println!(
+                    "Импликант слишком много для точного перебора: \
+                     формы найдены жадно и могут быть не минимальными."
+                );
+            }
+        }
+        _ => println!("\nСлишком много переменных для минимизации."),
     }
 
-    fn designation(&self) -> &'static str {
-        "↑"
+    if let Some(kmap) = table.kmap() {
+        println!("\nКарта Карно:\n{kmap}");
     }
 
-    fn calclate(&self, a: u8, b: u8) -> u8 {
-        !(a & b)
+    Ok(())
+}
+
+/// Проверить эквивалентность двух выражений и вывести контрпример, если они различаются.
+/// Возвращает, эквивалентны ли выражения.
+fn equiv<T: Word + 'static>(
+    path: Option<&Path>,
+    overflow: Overflow,
+    left: &str,
+    right: &str,
+    method: Method,
+    width: usize,
+) -> Result<bool, String> {
+    let parser = Registry::<T>::with_file(path, overflow)
+        .map_err(|e| e.to_string())?
+        .parser();
+    let left = parser.parse_formula(left).map_err(|e| e.to_string())?;
+    let right = parser.parse_formula(right).map_err(|e| e.to_string())?;
+
+    let report = equiv::check(&left, &right, width, overflow, method).map_err(|e| e.to_string())?;
+    let how = match report.method {
+        Method::Exhaustive => format!("перебор, наборов: {}", report.size),
+        _ => format!("BDD, вершин: {}", report.size),
+    };
+
+    let Some(counterexample) = report.counterexample else {
+        println!("{left} ≡ {right}");
+        println!("Выражения эквивалентны ({how})");
+        return Ok(true);
+    };
+
+    println!("{left} ≢ {right}");
+    println!("Выражения различаются ({how}). Контрпример:");
+
+    let values: HashMap<String, T> = counterexample.iter().cloned().collect();
+    for (name, value) in &counterexample {
+        println!("  {name} = {value} ({})", value.to_binary());
     }
+    for expr in [&left, &right] {
+        match expr.eval(&values, width) {
+            Ok(value) => println!("  {expr} = {value} ({})", value.to_binary()),
+            Err(e) => println!("  {expr}: {e}"),
+        }
+    }
+
+    Ok(false)
 }
 
-#[derive(Default)]
-struct Nor;
+/// Смоделировать схему: вывести её в DOT, таблицу истинности или значения выходов
+fn circuit(
+    path: Option<&Path>,
+    overflow: Overflow,
+    file: &Path,
+    inputs: &[String],
+    table: bool,
+    dot: bool,
+) -> Result<(), String> {
+    let parser = Registry::<u8>::with_file(path, overflow)
+        .map_err(|e| e.to_string())?
+        .parser();
+    let circuit = Circuit::from_file(file, &parser).map_err(|e| e.to_string())?;
 
-impl Operation<u8> for Nor {
-    fn name(&self) -> &'static str {
-        "nor"
+    if dot {
+        print!("{}", circuit.to_dot());
+        return Ok(());
     }
 
-    fn designation(&self) -> &'static str {
-        "↓"
+    if table {
+        print!("{}", circuit.table().map_err(|e| e.to_string())?);
+        return Ok(());
     }
 
-    fn calclate(&self, a: u8, b: u8) -> u8 {
-        !(a | b)
+    // Выходные шины печатаются ещё и числом. Провода одной шины не обязательно идут
+    // подряд, поэтому повторы убирает множество. Число -- u128, и шины шире не влезут.
+    let prefixes: BTreeSet<&str> = circuit
+        .outputs
+        .iter()
+        .map(|name| name.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .filter(|prefix| bus(&circuit.outputs, prefix).len() > 1)
+        .collect();
+    for prefix in &prefixes {
+        if let Some((_, wire)) = bus(&circuit.outputs, prefix)
+            .into_iter()
+            .find(|(bit, _)| *bit >= u128::BITS as usize)
+        {
+            return Err(format!(
+                "output bus '{prefix}' is limited to {} bits, got {wire}",
+                u128::BITS
+            ));
+        }
     }
-}
 
-fn main() {
-    // Box это ссылка на память в куче. В Rust нельзя создавать объекты, реализующие типаж (trait)
-    // на стеке, потому что размер таких объектов неизвестен на этапе компиляции.
-    let operations: &[Box<dyn Operation<u8>>] = &[
-        Box::new(Xor::default()),
-        Box::new(And::default()),
-        Box::new(Or::default()),
-        Box::new(Nand::default()),
-        Box::new(Nor::default()),
-    ];
-
-    let a = 0b1100;
-    let b = 0b1010;
-
-    for op in operations {
-        println!(
-            "{} {} {} = {} ({:08b} {} {:08b} = {:08b}) <-- {}",
-            a,
-            op.designation(),
-            b,
-            op.calclate(a, b),
-            a,
-            op.designation(),
-            b,
-            op.calclate(a, b),
-            op.name(),
-        );
+    let mut values = HashMap::new();
+    for input in inputs {
+        let (name, value) = input
+            .split_once('=')
+            .ok_or_else(|| format!("expected NAME=VALUE, got '{input}'"))?;
+        let value =
+            expr::parse_number(value).ok_or_else(|| format!("bad value '{value}' for {name}"))?;
+
+        if circuit.inputs.iter().any(|input| input == name) {
+            if value > 1 {
+                return Err(format!("input '{name}' is a single bit, got {value}"));
+            }
+            values.insert(name.to_string(), value == 1);
+            continue;
+        }
+
+        // Шина: входы name0, name1, ... получают биты числа, name0 -- младший
+        let bus = bus(&circuit.inputs, name);
+        if bus.is_empty() {
+            return Err(format!("unknown input '{name}'"));
+        }
+        if bus.len() < u128::BITS as usize && value >> bus.len() != 0 {
+            return Err(format!(
+                "value {value} does not fit in {} bits of {name}",
+                bus.len()
+            ));
+        }
+        for (bit, wire) in bus {
+            values.insert(
+                wire.to_string(),
+                bit < u128::BITS as usize && value >> bit & 1 == 1,
+            );
+        }
+    }
+
+    let result = circuit.simulate(&values).map_err(|e| e.to_string())?;
+    for name in &circuit.outputs {
+        println!("{name} = {}", result[name] as u8);
     }
+
+    // Значения выходных шин целиком
+    for prefix in prefixes {
+        let value = bus(&circuit.outputs, prefix)
+            .into_iter()
+            .filter(|(_, wire)| result[*wire])
+            .fold(0u128, |acc, (bit, _)| acc | 1 << bit);
+        println!("{prefix} = {value}");
+    }
+
+    Ok(())
+}
+
+/// Провода шины `prefix`: имена вида prefix0, prefix1, ... с номерами битов
+fn bus<'a>(wires: &'a [String], prefix: &str) -> Vec<(usize, &'a str)> {
+    wires
+        .iter()
+        .filter_map(|wire| {
+            let bit = wire.strip_prefix(prefix)?;
+            if bit.is_empty() || !bit.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            Some((bit.parse().ok()?, wire.as_str()))
+        })
+        .collect()
 }
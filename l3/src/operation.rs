@@ -0,0 +1,486 @@
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use crate::arith::{arithmetic_operations, Neg, Overflow};
+use crate::properties::{Element, Properties, UnaryProperties};
+use crate::word::Word;
+
+/// Ошибка вычисления операции
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpError {
+    DivisionByZero,
+    /// Переполнение при вычислении операции с указанным именем
+    Overflow(String),
+}
+
+impl Display for OpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpError::DivisionByZero => write!(f, "division by zero"),
+            OpError::Overflow(name) => write!(f, "overflow in {name}"),
+        }
+    }
+}
+
+pub trait Operation<T> {
+    fn name(&self) -> &str;
+    fn designation(&self) -> &str;
+    fn calclate(&self, a: T, b: T) -> T;
+
+    /// Вычисление, которое может закончиться ошибкой (деление на ноль, переполнение).
+    /// Побитовые операции ошибок не дают, поэтому по умолчанию просто вызывается calclate.
+    fn try_calclate(&self, a: T, b: T) -> Result<T, OpError> {
+        Ok(self.calclate(a, b))
+    }
+
+    /// Таблица истинности, если операция побитовая, то есть каждый бит результата зависит
+    /// только от тех же битов операндов. Строка с номером i соответствует a = i >> 1, b = i & 1.
+    /// У арифметических операций биты связаны переносами, для них None.
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        None
+    }
+
+    /// Алгебраические свойства операции. По умолчанию у побитовой операции они выводятся
+    /// из таблицы истинности, а у остальных не объявлено ни одного.
+    fn properties(&self) -> Properties {
+        self.truth_table()
+            .map(Properties::from_truth_table)
+            .unwrap_or_default()
+    }
+
+    /// Пакетное вычисление: `out[i] = a[i] op b[i]`. Срезы должны быть одной длины.
+    /// Один вызов через dyn Operation обрабатывает весь буфер, поэтому цикл внутри
+    /// компилируется для конкретного типа и операции. Ошибки приводят к панике, как в calclate.
+    fn calclate_into(&self, a: &[T], b: &[T], out: &mut [T])
+    where
+        T: Clone,
+    {
+        zip_into(a, b, out, |x, y| self.calclate(x, y));
+    }
+
+    /// Пакетное вычисление со скаляром: `out[i] = a[i] op b`, например наложение маски на буфер
+    fn calclate_scalar_into(&self, a: &[T], b: &T, out: &mut [T])
+    where
+        T: Clone,
+    {
+        map_into(a, out, |x| self.calclate(x, b.clone()));
+    }
+
+    /// То же, что calclate_into, но результат возвращается в новом векторе
+    fn calclate_slice(&self, a: &[T], b: &[T]) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut out = a.to_vec();
+        self.calclate_into(a, b, &mut out);
+        out
+    }
+
+    /// То же, что calclate_scalar_into, но результат возвращается в новом векторе
+    fn calclate_slice_scalar(&self, a: &[T], b: &T) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut out = a.to_vec();
+        self.calclate_scalar_into(a, b, &mut out);
+        out
+    }
+}
+
+/// Операция с одним операндом, например NOT
+pub trait UnaryOperation<T> {
+    fn name(&self) -> &str;
+    fn designation(&self) -> &str;
+    fn calclate(&self, a: T) -> T;
+
+    fn try_calclate(&self, a: T) -> Result<T, OpError> {
+        Ok(self.calclate(a))
+    }
+
+    /// Таблица истинности для a = 0 и a = 1, если операция побитовая
+    fn truth_table(&self) -> Option<[bool; 2]> {
+        None
+    }
+
+    fn properties(&self) -> UnaryProperties {
+        self.truth_table()
+            .map(UnaryProperties::from_truth_table)
+            .unwrap_or_default()
+    }
+
+    /// Пакетное вычисление: `out[i] = op a[i]`
+    fn calclate_into(&self, a: &[T], out: &mut [T])
+    where
+        T: Clone,
+    {
+        map_into(a, out, |x| self.calclate(x));
+    }
+}
+
+/// Сколько элементов обрабатывается за один шаг пакетного цикла. Блок фиксированного
+/// размера без проверок границ внутри компилятор превращает в SIMD-инструкции
+/// (автовекторизация), так что std::simd, доступный только в nightly, не нужен.
+const LANES: usize = 16;
+
+/// Применить `f` к парам элементов `a` и `b` блоками по LANES, записав результат в `out`
+fn zip_into<T: Clone>(a: &[T], b: &[T], out: &mut [T], f: impl Fn(T, T) -> T) {
+    assert_eq!(a.len(), b.len(), "operands must have the same length");
+    assert_eq!(
+        a.len(),
+        out.len(),
+        "output must have the same length as operands"
+    );
+
+    let mut a = a.chunks_exact(LANES);
+    let mut b = b.chunks_exact(LANES);
+    let mut out = out.chunks_exact_mut(LANES);
+
+    for ((a, b), out) in (&mut a).zip(&mut b).zip(&mut out) {
+        // Массивы вместо срезов: длина известна при компиляции, и проверки границ исчезают
+        let (a, b): (&[T; LANES], &[T; LANES]) = (a.try_into().unwrap(), b.try_into().unwrap());
+        let out: &mut [T; LANES] = out.try_into().unwrap();
+        for ((out, a), b) in out.iter_mut().zip(a).zip(b) {
+            *out = f(a.clone(), b.clone());
+        }
+    }
+
+    // Хвост, не поместившийся в целый блок
+    let tail = a.remainder().iter().zip(b.remainder());
+    for ((a, b), out) in tail.zip(out.into_remainder()) {
+        *out = f(a.clone(), b.clone());
+    }
+}
+
+/// Применить `f` к каждому элементу `a` блоками по LANES, записав результат в `out`
+fn map_into<T: Clone>(a: &[T], out: &mut [T], f: impl Fn(T) -> T) {
+    assert_eq!(
+        a.len(),
+        out.len(),
+        "output must have the same length as operand"
+    );
+
+    let mut a = a.chunks_exact(LANES);
+    let mut out = out.chunks_exact_mut(LANES);
+
+    for (a, out) in (&mut a).zip(&mut out) {
+        let a: &[T; LANES] = a.try_into().unwrap();
+        let out: &mut [T; LANES] = out.try_into().unwrap();
+        for (out, a) in out.iter_mut().zip(a) {
+            *out = f(a.clone());
+        }
+    }
+
+    for (a, out) in a.remainder().iter().zip(out.into_remainder()) {
+        *out = f(a.clone());
+    }
+}
+
+#[derive(Default)]
+pub struct Xor;
+
+// Операция реализована сразу для всех типов, которые реализуют типаж Word:
+// u8..u128, i8..i128 и BitVec
+impl<T: Word> Operation<T> for Xor {
+    fn name(&self) -> &str {
+        "xor"
+    }
+
+    fn designation(&self) -> &str {
+        "^"
+    }
+
+    fn calclate(&self, a: T, b: T) -> T {
+        a ^ b
+    }
+
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        Some([false, true, true, false])
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            commutative: true,
+            associative: true,
+            identity: Some(Element::Zero),
+            self_inverse: true,
+            ..Properties::default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Or;
+
+impl<T: Word> Operation<T> for Or {
+    fn name(&self) -> &str {
+        "or"
+    }
+
+    fn designation(&self) -> &str {
+        "|"
+    }
+
+    fn calclate(&self, a: T, b: T) -> T {
+        a | b
+    }
+
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        Some([false, true, true, true])
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            commutative: true,
+            associative: true,
+            identity: Some(Element::Zero),
+            absorbing: Some(Element::Ones),
+            idempotent: true,
+            ..Properties::default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct And;
+
+impl<T: Word> Operation<T> for And {
+    fn name(&self) -> &str {
+        "and"
+    }
+
+    fn designation(&self) -> &str {
+        "&"
+    }
+
+    fn calclate(&self, a: T, b: T) -> T {
+        a & b
+    }
+
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        Some([false, false, false, true])
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            commutative: true,
+            associative: true,
+            identity: Some(Element::Ones),
+            absorbing: Some(Element::Zero),
+            idempotent: true,
+            ..Properties::default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Nand;
+
+impl<T: Word> Operation<T> for Nand {
+    fn name(&self) -> &str {
+        "nand"
+    }
+
+    fn designation(&self) -> &str {
+        "↑"
+    }
+
+    fn calclate(&self, a: T, b: T) -> T {
+        !(a & b)
+    }
+
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        Some([true, true, true, false])
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            commutative: true,
+            ..Properties::default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Nor;
+
+impl<T: Word> Operation<T> for Nor {
+    fn name(&self) -> &str {
+        "nor"
+    }
+
+    fn designation(&self) -> &str {
+        "↓"
+    }
+
+    fn calclate(&self, a: T, b: T) -> T {
+        !(a | b)
+    }
+
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        Some([true, false, false, false])
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            commutative: true,
+            ..Properties::default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Not;
+
+impl<T: Word> UnaryOperation<T> for Not {
+    fn name(&self) -> &str {
+        "not"
+    }
+
+    fn designation(&self) -> &str {
+        "~"
+    }
+
+    fn calclate(&self, a: T) -> T {
+        !a
+    }
+
+    fn truth_table(&self) -> Option<[bool; 2]> {
+        Some([true, false])
+    }
+
+    fn properties(&self) -> UnaryProperties {
+        UnaryProperties {
+            idempotent: false,
+            self_inverse: true,
+        }
+    }
+}
+
+/// Все встроенные бинарные операции для слова типа T.
+/// Арифметические операции обрабатывают переполнение согласно `overflow`.
+pub fn operations<T: Word>(overflow: Overflow) -> Vec<Rc<dyn Operation<T>>> {
+    // Rc это счётчик ссылок на память в куче. В Rust нельзя создавать объекты, реализующие типаж (trait)
+    // на стеке, потому что размер таких объектов неизвестен на этапе компиляции.
+    // Счётчик ссылок (а не Box) нужен, чтобы одну операцию можно было положить в несколько узлов выражения.
+    let bitwise: Vec<Rc<dyn Operation<T>>> = vec![
+        Rc::new(Xor),
+        Rc::new(And),
+        Rc::new(Or),
+        Rc::new(Nand),
+        Rc::new(Nor),
+    ];
+
+    // Арифметическая операция -- тоже Operation, поэтому Rc<dyn Arithmetic> приводится к Rc<dyn Operation>
+    let arithmetic = arithmetic_operations(overflow)
+        .into_iter()
+        .map(|op| op as Rc<dyn Operation<T>>);
+
+    bitwise.into_iter().chain(arithmetic).collect()
+}
+
+/// Все встроенные унарные операции для слова типа T
+pub fn unary_operations<T: Word>(overflow: Overflow) -> Vec<Rc<dyn UnaryOperation<T>>> {
+    vec![Rc::new(Not), Rc::new(Neg { overflow })]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::Div;
+
+    /// Длины вокруг границ блоков: пусто, только хвост, ровно блок, блок с хвостом и т. д.
+    const LENGTHS: [usize; 8] = [
+        0,
+        1,
+        LANES - 1,
+        LANES,
+        LANES + 1,
+        2 * LANES + 1,
+        3 * LANES - 1,
+        100,
+    ];
+
+    /// Неповторяющиеся ненулевые данные, чтобы деление не падало
+    fn data(len: usize, seed: u16) -> Vec<u16> {
+        (0..len as u16)
+            .map(|i| i.wrapping_mul(40503).wrapping_add(seed) | 1)
+            .collect()
+    }
+
+    fn all_operations() -> Vec<Rc<dyn Operation<u16>>> {
+        operations(Overflow::Wrapping)
+    }
+
+    #[test]
+    fn batch_matches_scalar() {
+        for op in all_operations() {
+            for len in LENGTHS {
+                let (a, b) = (data(len, 7), data(len, 12345));
+                let expected: Vec<u16> =
+                    a.iter().zip(&b).map(|(&x, &y)| op.calclate(x, y)).collect();
+
+                let mut out = vec![0; len];
+                op.calclate_into(&a, &b, &mut out);
+                assert_eq!(out, expected, "{} on {len} elements", op.name());
+                assert_eq!(op.calclate_slice(&a, &b), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn batch_with_scalar_matches_scalar() {
+        for op in all_operations() {
+            for len in LENGTHS {
+                let a = data(len, 99);
+                let b = 0x0f0f;
+                let expected: Vec<u16> = a.iter().map(|&x| op.calclate(x, b)).collect();
+
+                let mut out = vec![0; len];
+                op.calclate_scalar_into(&a, &b, &mut out);
+                assert_eq!(out, expected, "{} on {len} elements", op.name());
+                assert_eq!(op.calclate_slice_scalar(&a, &b), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn unary_batch_matches_scalar() {
+        for op in unary_operations::<u16>(Overflow::Wrapping) {
+            for len in LENGTHS {
+                let a = data(len, 3);
+                let expected: Vec<u16> = a.iter().map(|&x| op.calclate(x)).collect();
+
+                let mut out = vec![0; len];
+                op.calclate_into(&a, &mut out);
+                assert_eq!(out, expected, "{} on {len} elements", op.name());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "operands must have the same length")]
+    fn mismatched_operands() {
+        Xor.calclate_into(&data(LANES, 0), &data(LANES + 1, 0), &mut [0; LANES]);
+    }
+
+    #[test]
+    #[should_panic(expected = "output must have the same length as operands")]
+    fn mismatched_output() {
+        Xor.calclate_into(&data(LANES + 1, 0), &data(LANES + 1, 0), &mut [0; LANES]);
+    }
+
+    #[test]
+    #[should_panic(expected = "output must have the same length as operand")]
+    fn mismatched_unary_output() {
+        Not.calclate_into(&data(3, 0), &mut [0; 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn batch_errors_panic() {
+        let div = Div {
+            overflow: Overflow::Wrapping,
+        };
+        div.calclate_slice(&[1u16, 2], &[1, 0]);
+    }
+}
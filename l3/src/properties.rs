@@ -0,0 +1,251 @@
+use std::fmt::{self, Display};
+
+use crate::operation::{Operation, UnaryOperation};
+use crate::word::Word;
+
+/// Особое значение слова, которое не зависит от разрядности
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Element {
+    /// Все биты нули
+    Zero,
+    /// Единица
+    One,
+    /// Все биты единицы (для знаковых типов это -1)
+    Ones,
+}
+
+impl Element {
+    /// Значение элемента для слова разрядности `width`
+    pub fn value<T: Word>(self, width: usize) -> T {
+        let zero = T::from_u128(0, width);
+        match self {
+            Element::Zero => zero,
+            Element::One => T::from_u128(1, width),
+            Element::Ones => !zero,
+        }
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Element::Zero => write!(f, "0"),
+            Element::One => write!(f, "1"),
+            Element::Ones => write!(f, "~0"),
+        }
+    }
+}
+
+/// Алгебраические свойства бинарной операции `op`. Объявляются только те свойства,
+/// которые выполняются для всех типов слов: и знаковых, и беззнаковых, любой разрядности.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Properties {
+    /// a op b = b op a
+    pub commutative: bool,
+    /// (a op b) op c = a op (b op c)
+    pub associative: bool,
+    /// Нейтральный элемент e: a op e = e op a = a
+    pub identity: Option<Element>,
+    /// Поглощающий элемент z: a op z = z op a = z
+    pub absorbing: Option<Element>,
+    /// a op a = a
+    pub idempotent: bool,
+    /// Каждый элемент обратен сам себе: a op a = e, где e -- нейтральный элемент
+    pub self_inverse: bool,
+}
+
+impl Properties {
+    /// Свойства побитовой операции по её таблице истинности (строка i -- a = i >> 1, b = i & 1).
+    /// Побитовая операция работает с каждым битом отдельно, поэтому свойство выполняется
+    /// для слов, если оно выполняется для одного бита. Нейтральный и поглощающий элементы
+    /// при этом должны состоять из одинаковых битов, то есть быть 0 или ~0.
+    pub fn from_truth_table(table: [bool; 4]) -> Self {
+        let op = |a: bool, b: bool| table[(a as usize) << 1 | b as usize];
+        let bits = [false, true];
+        let element = |bit: bool| if bit { Element::Ones } else { Element::Zero };
+
+        let identity = bits
+            .into_iter()
+            .find(|&e| bits.iter().all(|&a| op(a, e) == a && op(e, a) == a));
+        let absorbing = bits
+            .into_iter()
+            .find(|&z| bits.iter().all(|&a| op(a, z) == z && op(z, a) == z));
+
+        Self {
+            commutative: bits
+                .iter()
+                .all(|&a| bits.iter().all(|&b| op(a, b) == op(b, a))),
+            associative: bits.iter().all(|&a| {
+                bits.iter()
+                    .all(|&b| bits.iter().all(|&c| op(op(a, b), c) == op(a, op(b, c))))
+            }),
+            identity: identity.map(element),
+            absorbing: absorbing.map(element),
+            idempotent: bits.iter().all(|&a| op(a, a) == a),
+            self_inverse: identity.is_some_and(|e| bits.iter().all(|&a| op(a, a) == e)),
+        }
+    }
+
+    /// Названия объявленных свойств
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![];
+        if self.commutative {
+            names.push("commutative".to_string());
+        }
+        if self.associative {
+            names.push("associative".to_string());
+        }
+        if let Some(e) = self.identity {
+            names.push(format!("identity {e}"));
+        }
+        if let Some(z) = self.absorbing {
+            names.push(format!("absorbing {z}"));
+        }
+        if self.idempotent {
+            names.push("idempotent".to_string());
+        }
+        if self.self_inverse {
+            names.push("self-inverse".to_string());
+        }
+        names
+    }
+}
+
+/// Алгебраические свойства унарной операции `f`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnaryProperties {
+    /// f(f(a)) = f(a)
+    pub idempotent: bool,
+    /// Операция обратна сама себе (инволюция): f(f(a)) = a
+    pub self_inverse: bool,
+}
+
+impl UnaryProperties {
+    /// Свойства побитовой унарной операции по её таблице истинности
+    pub fn from_truth_table(table: [bool; 2]) -> Self {
+        let f = |a: bool| table[a as usize];
+        let bits = [false, true];
+
+        Self {
+            idempotent: bits.iter().all(|&a| f(f(a)) == f(a)),
+            self_inverse: bits.iter().all(|&a| f(f(a)) == a),
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![];
+        if self.idempotent {
+            names.push("idempotent".to_string());
+        }
+        if self.self_inverse {
+            names.push("self-inverse".to_string());
+        }
+        names
+    }
+}
+
+/// Нарушение объявленного свойства: какое свойство и на каких операндах
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation<T> {
+    pub law: &'static str,
+    pub operands: Vec<T>,
+}
+
+impl<T: Display> Display for Violation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operands: Vec<String> = self.operands.iter().map(|x| x.to_string()).collect();
+        write!(f, "{} is violated for {}", self.law, operands.join(", "))
+    }
+}
+
+/// Проверить объявленные свойства бинарной операции, кроме ассоциативности, на операндах `a` и `b`.
+/// Результаты сравниваются вместе с ошибками: например, при политике checked
+/// коммутативность означает, что a + b и b + a переполняются одновременно.
+pub fn check<T: Word>(op: &dyn Operation<T>, a: &T, b: &T) -> Result<(), Violation<T>> {
+    let properties = op.properties();
+    let width = a.width();
+    let calc = |x: &T, y: &T| op.try_calclate(x.clone(), y.clone());
+    let violation = |law, operands: &[&T]| Violation {
+        law,
+        operands: operands.iter().map(|&x| x.clone()).collect(),
+    };
+
+    if properties.commutative && calc(a, b) != calc(b, a) {
+        return Err(violation("commutativity", &[a, b]));
+    }
+
+    if let Some(e) = properties.identity {
+        let e = e.value::<T>(width);
+        if calc(a, &e) != Ok(a.clone()) || calc(&e, a) != Ok(a.clone()) {
+            return Err(violation("identity", &[a]));
+        }
+        if properties.self_inverse && calc(a, a) != Ok(e) {
+            return Err(violation("self-inverse", &[a]));
+        }
+    } else if properties.self_inverse {
+        return Err(violation("self-inverse without identity", &[]));
+    }
+
+    if let Some(z) = properties.absorbing {
+        let z = z.value::<T>(width);
+        if calc(a, &z) != Ok(z.clone()) || calc(&z, a) != Ok(z) {
+            return Err(violation("absorbing element", &[a]));
+        }
+    }
+
+    if properties.idempotent && calc(a, a) != Ok(a.clone()) {
+        return Err(violation("idempotence", &[a]));
+    }
+
+    Ok(())
+}
+
+/// Проверить ассоциативность, если она объявлена. Отдельно от остальных свойств,
+/// потому что ей нужны три операнда, и при полном переборе троек в разы больше, чем пар.
+pub fn check_associative<T: Word>(
+    op: &dyn Operation<T>,
+    a: &T,
+    b: &T,
+    c: &T,
+) -> Result<(), Violation<T>> {
+    if !op.properties().associative {
+        return Ok(());
+    }
+
+    let calc = |x: &T, y: &T| op.try_calclate(x.clone(), y.clone());
+    let left = calc(a, b).and_then(|ab| calc(&ab, c));
+    let right = calc(b, c).and_then(|bc| calc(a, &bc));
+
+    if left != right {
+        return Err(Violation {
+            law: "associativity",
+            operands: vec![a.clone(), b.clone(), c.clone()],
+        });
+    }
+
+    Ok(())
+}
+
+/// Проверить все объявленные свойства унарной операции на операнде `a`
+pub fn check_unary<T: Word>(op: &dyn UnaryOperation<T>, a: &T) -> Result<(), Violation<T>> {
+    let properties = op.properties();
+    let twice = op
+        .try_calclate(a.clone())
+        .and_then(|fa| op.try_calclate(fa));
+
+    if properties.idempotent && twice != op.try_calclate(a.clone()) {
+        return Err(Violation {
+            law: "idempotence",
+            operands: vec![a.clone()],
+        });
+    }
+
+    if properties.self_inverse && twice != Ok(a.clone()) {
+        return Err(Violation {
+            law: "self-inverse",
+            operands: vec![a.clone()],
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use std::collections::{BTreeSet, HashSet};
+
+/// Импликанта -- конъюнкция литералов. Биты `mask` не участвуют в ней (склеенные переменные),
+/// остальные биты должны совпадать с `value`. Переменная с номером 0 соответствует старшему биту.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Implicant {
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl Implicant {
+    /// Покрывает ли импликанта набор `minterm`
+    pub fn covers(&self, minterm: u32) -> bool {
+        minterm & !self.mask == self.value
+    }
+
+    /// Количество литералов в импликанте для `n` переменных
+    pub fn literals(&self, n: usize) -> usize {
+        n - self.mask.count_ones() as usize
+    }
+
+    /// Литералы импликанты: номер переменной и её значение (false -- с отрицанием)
+    pub fn terms(&self, n: usize) -> Vec<(usize, bool)> {
+        (0..n)
+            .filter_map(|var| {
+                let bit = 1 << (n - 1 - var);
+                (self.mask & bit == 0).then_some((var, self.value & bit != 0))
+            })
+            .collect()
+    }
+}
+
+/// Найти все простые импликанты методом Квайна -- Мак-Класки.
+/// На каждом шаге склеиваются пары импликант, отличающиеся ровно в одном бите.
+/// Импликанты, которые ни с чем не склеились, простые.
+pub fn prime_implicants(minterms: &[u32]) -> Vec<Implicant> {
+    let mut current: BTreeSet<Implicant> = minterms
+        .iter()
+        .map(|&value| Implicant { value, mask: 0 })
+        .collect();
+    let mut primes = BTreeSet::new();
+
+    while !current.is_empty() {
+        let list: Vec<Implicant> = current.iter().copied().collect();
+        let mut merged = HashSet::new();
+        let mut next = BTreeSet::new();
+
+        for (i, a) in list.iter().enumerate() {
+            for b in &list[i + 1..] {
+                let diff = a.value ^ b.value;
+                if a.mask == b.mask && diff.count_ones() == 1 {
+                    next.insert(Implicant {
+                        value: a.value & !diff,
+                        mask: a.mask | diff,
+                    });
+                    merged.insert(*a);
+                    merged.insert(*b);
+                }
+            }
+        }
+
+        primes.extend(list.into_iter().filter(|imp| !merged.contains(imp)));
+        current = next;
+    }
+
+    primes.into_iter().collect()
+}
+
+/// Покрытие наборов простыми импликантами
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cover {
+    pub implicants: Vec<Implicant>,
+    /// Доказано ли, что покрытие минимальное. Если метод Петрика не справился,
+    /// покрытие жадное и может содержать лишние импликанты.
+    pub minimal: bool,
+}
+
+/// Сколько произведений может получиться при раскрытии скобок в методе Петрика,
+/// прежде чем он уступит место жадному покрытию
+const MAX_PRODUCTS: usize = 1 << 12;
+
+/// Выбрать минимальный набор простых импликант, покрывающий все `minterms`.
+/// Сначала берутся существенные импликанты, остаток покрывается методом Петрика.
+pub fn minimal_cover(primes: &[Implicant], minterms: &[u32], n: usize) -> Cover {
+    let mut chosen: BTreeSet<Implicant> = BTreeSet::new();
+
+    // Существенная импликанта -- единственная, которая покрывает какой-то минтерм
+    for &m in minterms {
+        let covering: Vec<&Implicant> = primes.iter().filter(|imp| imp.covers(m)).collect();
+        if let [only] = covering[..] {
+            chosen.insert(*only);
+        }
+    }
+
+    let remaining: Vec<u32> = minterms
+        .iter()
+        .copied()
+        .filter(|&m| !chosen.iter().any(|imp| imp.covers(m)))
+        .collect();
+
+    let candidates: Vec<Implicant> = primes
+        .iter()
+        .copied()
+        .filter(|imp| !chosen.contains(imp) && remaining.iter().any(|&m| imp.covers(m)))
+        .collect();
+
+    let mut minimal = true;
+    if !remaining.is_empty() {
+        let extra = petrick(&candidates, &remaining, n).unwrap_or_else(|| {
+            minimal = false;
+            greedy(&candidates, &remaining)
+        });
+        chosen.extend(extra);
+    }
+
+    Cover {
+        implicants: chosen.into_iter().collect(),
+        minimal,
+    }
+}
+
+/// Произведение импликант в методе Петрика: битовое множество их номеров
+/// по 64 номера в слове, так что число импликант не ограничено
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Product(Vec<u64>);
+
+impl Product {
+    fn empty(len: usize) -> Self {
+        Product(vec![0; len.div_ceil(64)])
+    }
+
+    fn with(&self, i: usize) -> Self {
+        let mut product = self.clone();
+        product.0[i / 64] |= 1 << (i % 64);
+        product
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.0[i / 64] & 1 << (i % 64) != 0
+    }
+
+    fn len(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /// Все импликанты `self` есть в `other`
+    fn is_subset(&self, other: &Product) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & !b == 0)
+    }
+}
+
+/// Метод Петрика: условие покрытия записывается как произведение сумм
+/// (для каждого минтерма -- сумма покрывающих его импликант), раскрывается в сумму
+/// произведений, и из неё выбирается самое короткое произведение.
+/// None, если произведений стало больше MAX_PRODUCTS.
+fn petrick(candidates: &[Implicant], minterms: &[u32], n: usize) -> Option<Vec<Implicant>> {
+    let mut products = vec![Product::empty(candidates.len())];
+
+    for &m in minterms {
+        let sum: Vec<usize> = (0..candidates.len())
+            .filter(|&i| candidates[i].covers(m))
+            .collect();
+
+        let mut expanded: Vec<Product> = products
+            .iter()
+            .flat_map(|p| sum.iter().map(move |&i| p.with(i)))
+            .collect();
+
+        // Поглощение: X + XY = X. Оставляем только произведения, не содержащие другие.
+        expanded.sort();
+        expanded.dedup();
+        expanded.sort_by_key(Product::len);
+        products = vec![];
+        for p in expanded {
+            if !products.iter().any(|q: &Product| q.is_subset(&p)) {
+                products.push(p);
+            }
+        }
+        if products.len() > MAX_PRODUCTS {
+            return None;
+        }
+    }
+
+    let literals = |p: &Product| -> usize {
+        (0..candidates.len())
+            .filter(|&i| p.contains(i))
+            .map(|i| candidates[i].literals(n))
+            .sum()
+    };
+
+    let best = products
+        .into_iter()
+        .min_by_key(|p| (p.len(), literals(p)))?;
+
+    Some(
+        (0..candidates.len())
+            .filter(|&i| best.contains(i))
+            .map(|i| candidates[i])
+            .collect(),
+    )
+}
+
+/// Жадное покрытие на случай, когда метод Петрика раскрывает слишком много скобок:
+/// каждый раз берётся импликанта, покрывающая больше всего оставшихся минтермов.
+fn greedy(candidates: &[Implicant], minterms: &[u32]) -> Vec<Implicant> {
+    let mut remaining: Vec<u32> = minterms.to_vec();
+    let mut chosen = vec![];
+
+    while !remaining.is_empty() {
+        let Some(best) = candidates
+            .iter()
+            .max_by_key(|imp| remaining.iter().filter(|&&m| imp.covers(m)).count())
+        else {
+            break;
+        };
+
+        remaining.retain(|&m| !best.covers(m));
+        chosen.push(*best);
+    }
+
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn imp(value: u32, mask: u32) -> Implicant {
+        Implicant { value, mask }
+    }
+
+    /// Все наборы, покрытые импликантами
+    fn covered(cover: &[Implicant], n: usize) -> Vec<u32> {
+        (0..1 << n)
+            .filter(|&m| cover.iter().any(|imp| imp.covers(m)))
+            .collect()
+    }
+
+    #[test]
+    fn implicant_terms() {
+        // a & ~c при трёх переменных: b склеена
+        let imp = imp(0b100, 0b010);
+        assert!(imp.covers(0b100) && imp.covers(0b110));
+        assert!(!imp.covers(0b101) && !imp.covers(0b000));
+        assert_eq!(imp.literals(3), 2);
+        assert_eq!(imp.terms(3), [(0, true), (2, false)]);
+    }
+
+    #[test]
+    fn prime_implicants_of_known_functions() {
+        assert_eq!(prime_implicants(&[]), []);
+        assert_eq!(prime_implicants(&[0, 1, 2, 3]), [imp(0, 0b11)]);
+        // a ^ b не склеивается
+        assert_eq!(prime_implicants(&[1, 2]), [imp(1, 0), imp(2, 0)]);
+        // f(a, b, c) = Σ(0, 1, 2, 5, 6, 7): шесть простых импликант по две переменные
+        assert_eq!(
+            prime_implicants(&[0, 1, 2, 5, 6, 7]),
+            [
+                imp(0b000, 0b001),
+                imp(0b000, 0b010),
+                imp(0b001, 0b100),
+                imp(0b010, 0b100),
+                imp(0b101, 0b010),
+                imp(0b110, 0b001),
+            ]
+        );
+    }
+
+    #[test]
+    fn essential_implicants_only() {
+        // a | b: обе импликанты существенные
+        let minterms = [1, 2, 3];
+        let cover = minimal_cover(&prime_implicants(&minterms), &minterms, 2);
+        assert!(cover.minimal);
+        assert_eq!(cover.implicants, [imp(0b01, 0b10), imp(0b10, 0b01)]);
+    }
+
+    #[test]
+    fn cyclic_cover_needs_petrick() {
+        // У циклической функции нет существенных импликант, а минимальное покрытие -- из трёх
+        let minterms = [0, 1, 2, 5, 6, 7];
+        let cover = minimal_cover(&prime_implicants(&minterms), &minterms, 3);
+        assert!(cover.minimal);
+        assert_eq!(cover.implicants.len(), 3);
+        assert_eq!(covered(&cover.implicants, 3), minterms);
+    }
+
+    #[test]
+    fn petrick_prefers_fewer_literals() {
+        // Оба варианта из одной импликанты, но у первого меньше литералов
+        let candidates = [imp(0b10, 0b01), imp(0b10, 0)];
+        assert_eq!(
+            petrick(&candidates, &[0b10], 2),
+            Some(vec![imp(0b10, 0b01)])
+        );
+    }
+
+    #[test]
+    fn petrick_is_not_limited_to_64_implicants() {
+        let candidates: Vec<Implicant> = (0..100).map(|m| imp(m, 0)).collect();
+        let minterms: Vec<u32> = (0..100).collect();
+        assert_eq!(petrick(&candidates, &minterms, 7), Some(candidates));
+    }
+
+    #[test]
+    fn petrick_gives_up_on_too_many_products() {
+        // Каждый минтерм покрывается двумя одинаковыми импликантами под разными номерами,
+        // поэтому произведений получается 2^13 > MAX_PRODUCTS
+        let candidates: Vec<Implicant> = (0..26).map(|i| imp(i / 2, 0)).collect();
+        let minterms: Vec<u32> = (0..13).collect();
+        assert_eq!(petrick(&candidates, &minterms, 5), None);
+
+        let cover = greedy(&candidates, &minterms);
+        assert_eq!(covered(&cover, 5), covered(&candidates, 5));
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::{env, fs, io};
+
+use crate::arith::Overflow;
+use crate::expr::{is_identifier, Expr, ExprError, Parser};
+use crate::operation::{operations, unary_operations, OpError, Operation, UnaryOperation};
+use crate::word::Word;
+
+/// Ошибка работы с реестром операций
+#[derive(Debug)]
+pub enum RegistryError {
+    Expr(ExprError),
+    Io(io::Error),
+    /// Некорректное определение операции
+    Definition(String),
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Expr(e) => write!(f, "{e}"),
+            RegistryError::Io(e) => write!(f, "{e}"),
+            RegistryError::Definition(msg) => write!(f, "bad definition: {msg}"),
+        }
+    }
+}
+
+// Благодаря From работает оператор ? для ошибок разбора и ввода-вывода
+impl From<ExprError> for RegistryError {
+    fn from(e: ExprError) -> Self {
+        RegistryError::Expr(e)
+    }
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(e: io::Error) -> Self {
+        RegistryError::Io(e)
+    }
+}
+
+/// Тело пользовательской операции
+enum Body<T> {
+    /// Выражение над переменными a и b
+    Expr(Expr<T>),
+    /// Таблица истинности, применяемая к каждому биту отдельно.
+    /// Строка с номером i соответствует a = i >> 1, b = i & 1 (для унарной -- a = i).
+    Table(Vec<bool>),
+}
+
+/// Операция, определённая пользователем во время выполнения
+pub struct UserOperation<T> {
+    name: String,
+    designation: String,
+    body: Body<T>,
+}
+
+impl<T: Word> UserOperation<T> {
+    /// Вычислить операцию над операндами (один для унарной, два для бинарной)
+    fn apply(&self, operands: &[T]) -> Result<T, OpError> {
+        Ok(match &self.body {
+            Body::Expr(expr) => {
+                let vars: HashMap<String, T> = ["a", "b"]
+                    .iter()
+                    .map(|name| name.to_string())
+                    .zip(operands.iter().cloned())
+                    .collect();
+
+                // Переменные проверяются при определении, поэтому остаются только ошибки операций
+                match expr.eval(&vars, operands[0].width()) {
+                    Ok(value) => value,
+                    Err(ExprError::Operation(e)) => return Err(e),
+                    Err(e) => unreachable!("variables are checked on definition: {e}"),
+                }
+            }
+            Body::Table(table) => {
+                // Каждая единица таблицы даёт конъюнкцию операндов (или их отрицаний),
+                // а результат -- дизъюнкция этих конъюнкций. Так таблица работает сразу на всех битах.
+                let zero = operands[0].clone() ^ operands[0].clone();
+                let n = operands.len();
+
+                table
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &value)| value)
+                    .map(|(row, _)| {
+                        operands
+                            .iter()
+                            .enumerate()
+                            .fold(!zero.clone(), |acc, (i, x)| {
+                                if row >> (n - 1 - i) & 1 == 1 {
+                                    acc & x.clone()
+                                } else {
+                                    acc & !x.clone()
+                                }
+                            })
+                    })
+                    .fold(zero.clone(), |acc, term| acc | term)
+            }
+        })
+    }
+}
+
+impl<T: Word> UserOperation<T> {
+    /// Таблица истинности, если операция побитовая: табличная операция
+    /// или выражение только из побитовых операций без констант
+    fn table(&self, arity: usize) -> Option<Vec<bool>> {
+        match &self.body {
+            Body::Table(table) => Some(table.clone()),
+            Body::Expr(expr) if expr.is_bitwise() => (0..1u128 << arity)
+                .map(|row| {
+                    let operands: Vec<T> = (0..arity)
+                        .map(|i| T::from_u128(row >> (arity - 1 - i) & 1, 1))
+                        .collect();
+                    Some(self.apply(&operands).ok()?.to_u128() & 1 == 1)
+                })
+                .collect(),
+            Body::Expr(_) => None,
+        }
+    }
+}
+
+impl<T: Word> Operation<T> for UserOperation<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn designation(&self) -> &str {
+        &self.designation
+    }
+
+    fn calclate(&self, a: T, b: T) -> T {
+        Operation::try_calclate(self, a, b).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_calclate(&self, a: T, b: T) -> Result<T, OpError> {
+        self.apply(&[a, b])
+    }
+
+    fn truth_table(&self) -> Option<[bool; 4]> {
+        self.table(2)?.try_into().ok()
+    }
+}
+
+impl<T: Word> UnaryOperation<T> for UserOperation<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn designation(&self) -> &str {
+        &self.designation
+    }
+
+    fn calclate(&self, a: T) -> T {
+        UnaryOperation::try_calclate(self, a).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_calclate(&self, a: T) -> Result<T, OpError> {
+        self.apply(&[a])
+    }
+
+    fn truth_table(&self) -> Option<[bool; 2]> {
+        self.table(1)?.try_into().ok()
+    }
+}
+
+/// Реестр операций по имени. Содержит встроенные операции и операции,
+/// определённые пользователем. Пользовательские операции можно сохранить в файл.
+pub struct Registry<T> {
+    operations: BTreeMap<String, Rc<dyn Operation<T>>>,
+    unary_operations: BTreeMap<String, Rc<dyn UnaryOperation<T>>>,
+    /// Исходные строки пользовательских определений в порядке добавления
+    definitions: Vec<(String, String)>,
+    overflow: Overflow,
+}
+
+impl<T: Word + 'static> Registry<T> {
+    /// Реестр со встроенными операциями
+    pub fn new(overflow: Overflow) -> Self {
+        Self {
+            operations: operations(overflow)
+                .into_iter()
+                .map(|op| (op.name().to_string(), op))
+                .collect(),
+            unary_operations: unary_operations(overflow)
+                .into_iter()
+                .map(|op| (op.name().to_string(), op))
+                .collect(),
+            definitions: vec![],
+            overflow,
+        }
+    }
+
+    /// Реестр со встроенными операциями и операциями из файла, если он есть
+    pub fn with_file(path: Option<&Path>, overflow: Overflow) -> Result<Self, RegistryError> {
+        let mut registry = Self::new(overflow);
+        if let Some(path) = path.filter(|path| path.exists()) {
+            registry.load(path)?;
+        }
+        Ok(registry)
+    }
+
+    pub fn operations(&self) -> Vec<Rc<dyn Operation<T>>> {
+        self.operations.values().cloned().collect()
+    }
+
+    pub fn unary_operations(&self) -> Vec<Rc<dyn UnaryOperation<T>>> {
+        self.unary_operations.values().cloned().collect()
+    }
+
+    /// Разборщик выражений, знающий все операции реестра
+    pub fn parser(&self) -> Parser<T> {
+        Parser::new(self.operations(), self.unary_operations())
+    }
+
+    /// Является ли операция пользовательской
+    pub fn is_user_defined(&self, name: &str) -> bool {
+        self.definitions.iter().any(|(defined, _)| defined == name)
+    }
+
+    /// Определить операцию строкой вида `ИМЯ [ОБОЗНАЧЕНИЕ] = ТЕЛО`, где тело --
+    /// выражение над a и b (`xnor = ~(a ^ b)`) или таблица истинности (`imp = table 1101`).
+    /// Если переменная b не используется, операция получается унарной.
+    ///
+    /// Переопределение сохраняет место операции в списке определений, а операции,
+    /// которые её используют, определяются заново уже с новым телом. Если какая-то
+    /// из них после этого не разбирается (например, изменилась арность), переопределение
+    /// отклоняется: иначе сохранённый файл нельзя было бы загрузить обратно.
+    pub fn define(&mut self, definition: &str) -> Result<(), RegistryError> {
+        let definition = definition.trim();
+        let (name, operation, arity) = self.compile(definition)?;
+
+        if !self.is_user_defined(&name) {
+            self.insert(name, operation, arity, definition);
+            return Ok(());
+        }
+
+        let mut definitions = self.definitions.clone();
+        for (defined, text) in &mut definitions {
+            if *defined == name {
+                *text = definition.to_string();
+            }
+        }
+        self.rebuild(definitions)
+    }
+
+    /// Разобрать определение и проверить его по текущему реестру
+    fn compile(
+        &self,
+        definition: &str,
+    ) -> Result<(String, Rc<UserOperation<T>>, usize), RegistryError> {
+        let Some((head, body)) = definition.split_once('=') else {
+            return Err(RegistryError::Definition(
+                "expected NAME = BODY".to_string(),
+            ));
+        };
+
+        let mut head = head.split_whitespace();
+        let (Some(name), designation, None) = (head.next(), head.next(), head.next()) else {
+            return Err(RegistryError::Definition(
+                "expected NAME [SYMBOL] = BODY".to_string(),
+            ));
+        };
+        let designation = designation.unwrap_or(name);
+
+        self.check_names(name, designation)?;
+
+        let (body, arity) = match body.trim().strip_prefix("table") {
+            Some(bits) => {
+                let table: Vec<bool> = bits
+                    .trim()
+                    .chars()
+                    .map(|c| match c {
+                        '0' => Ok(false),
+                        '1' => Ok(true),
+                        _ => Err(RegistryError::Definition(format!("bad table digit '{c}'"))),
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                let arity = match table.len() {
+                    2 => 1,
+                    4 => 2,
+                    _ => {
+                        return Err(RegistryError::Definition(
+                            "table must have 2 (unary) or 4 (binary) digits".to_string(),
+                        ))
+                    }
+                };
+                (Body::Table(table), arity)
+            }
+            None => {
+                let expr = self.parser().parse(body)?;
+                let vars = expr.variables();
+
+                if let Some(var) = vars.iter().find(|var| *var != "a" && *var != "b") {
+                    return Err(RegistryError::Definition(format!(
+                        "only variables a and b are allowed, got '{var}'"
+                    )));
+                }
+
+                let arity = if vars.iter().any(|var| var == "b") {
+                    2
+                } else {
+                    1
+                };
+                (Body::Expr(expr), arity)
+            }
+        };
+
+        let operation = Rc::new(UserOperation {
+            name: name.to_string(),
+            designation: designation.to_string(),
+            body,
+        });
+        Ok((name.to_string(), operation, arity))
+    }
+
+    /// Добавить разобранную операцию в конец списка определений
+    fn insert(
+        &mut self,
+        name: String,
+        operation: Rc<UserOperation<T>>,
+        arity: usize,
+        definition: &str,
+    ) {
+        if arity == 2 {
+            self.operations.insert(name.clone(), operation);
+        } else {
+            self.unary_operations.insert(name.clone(), operation);
+        }
+        self.definitions.push((name, definition.to_string()));
+    }
+
+    /// Заново определить все пользовательские операции по порядку, так же, как при
+    /// загрузке из файла. Если какое-то определение не разбирается, реестр не меняется.
+    fn rebuild(&mut self, definitions: Vec<(String, String)>) -> Result<(), RegistryError> {
+        let mut registry = Self::new(self.overflow);
+        for (name, definition) in &definitions {
+            let (name, operation, arity) = registry
+                .compile(definition)
+                .map_err(|e| RegistryError::Definition(format!("'{name}' would break: {e}")))?;
+            registry.insert(name, operation, arity, definition);
+        }
+
+        *self = registry;
+        Ok(())
+    }
+
+    /// Проверить, что имя и обозначение допустимы и не заняты встроенными операциями
+    fn check_names(&self, name: &str, designation: &str) -> Result<(), RegistryError> {
+        if !is_identifier(name) {
+            return Err(RegistryError::Definition(format!("bad name '{name}'")));
+        }
+
+        if designation != name
+            && designation
+                .chars()
+                .any(|c| c.is_alphanumeric() || c == '_' || c == '(' || c == ')')
+        {
+            return Err(RegistryError::Definition(format!(
+                "bad symbol '{designation}': it must consist of punctuation only"
+            )));
+        }
+
+        // Занятые имена и обозначения чужих операций, кроме переопределяемой пользовательской
+        let taken = self
+            .operations
+            .values()
+            .map(|op| (op.name(), op.designation()))
+            .chain(
+                self.unary_operations
+                    .values()
+                    .map(|op| (op.name(), op.designation())),
+            )
+            .filter(|(other, _)| *other != name || !self.is_user_defined(name));
+
+        for (other, other_designation) in taken {
+            if other == name || other_designation == name {
+                return Err(RegistryError::Definition(format!(
+                    "name '{name}' is already used by '{other}'"
+                )));
+            }
+            if other == designation || other_designation == designation {
+                return Err(RegistryError::Definition(format!(
+                    "symbol '{designation}' is already used by '{other}'"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Удалить пользовательскую операцию. Встроенные операции удалить нельзя,
+    /// как и операцию, которую используют другие пользовательские операции.
+    pub fn remove(&mut self, name: &str) -> Result<(), RegistryError> {
+        if !self.is_user_defined(name) {
+            return Err(RegistryError::Definition(format!(
+                "'{name}' is not a user-defined operation"
+            )));
+        }
+
+        let mut definitions = self.definitions.clone();
+        definitions.retain(|(defined, _)| defined != name);
+        self.rebuild(definitions)
+    }
+
+    /// Сохранить пользовательские операции в файл, по одному определению на строку
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut text = String::from("# Пользовательские операции l3: ИМЯ [ОБОЗНАЧЕНИЕ] = ТЕЛО\n");
+        for (_, definition) in &self.definitions {
+            text.push_str(definition);
+            text.push('\n');
+        }
+
+        fs::write(path, text)
+    }
+
+    /// Загрузить операции из файла. Пустые строки и строки с # пропускаются.
+    pub fn load(&mut self, path: &Path) -> Result<(), RegistryError> {
+        let text = fs::read_to_string(path)?;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            self.define(line).map_err(|e| {
+                RegistryError::Definition(format!("{}:{}: {e}", path.display(), number + 1))
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Файл с пользовательскими операциями по умолчанию: $XDG_CONFIG_HOME/l3/operations.txt
+/// или ~/.config/l3/operations.txt
+pub fn default_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("l3").join("operations.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Значение выражения при a = 0b1100, b = 0b1010
+    fn eval(registry: &Registry<u8>, input: &str) -> u8 {
+        let vars = HashMap::from([("a".to_string(), 0b1100), ("b".to_string(), 0b1010)]);
+        registry
+            .parser()
+            .parse(input)
+            .unwrap()
+            .eval(&vars, 8)
+            .unwrap()
+    }
+
+    /// Сохранить реестр во временный файл и загрузить его в новый реестр
+    fn round_trip(registry: &Registry<u8>, file: &str) -> Registry<u8> {
+        let path = env::temp_dir().join(format!("l3-{}-{file}", std::process::id()));
+        registry.save(&path).unwrap();
+        let loaded = Registry::with_file(Some(&path), Overflow::Wrapping);
+        fs::remove_file(&path).unwrap();
+        loaded.unwrap()
+    }
+
+    fn names(registry: &Registry<u8>) -> Vec<&str> {
+        registry
+            .definitions
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn define_expression_and_table() {
+        let mut registry = Registry::<u8>::new(Overflow::Wrapping);
+        registry.define("xnor = ~(a ^ b)").unwrap();
+        registry.define("imp = table 1101").unwrap();
+        registry.define("inv = ~a").unwrap();
+
+        assert_eq!(eval(&registry, "a xnor b"), !(0b1100 ^ 0b1010));
+        assert_eq!(eval(&registry, "a imp b"), !0b1100 | 0b1010);
+        assert_eq!(eval(&registry, "inv a"), !0b1100);
+        assert!(registry.is_user_defined("imp"));
+        assert!(!registry.is_user_defined("and"));
+    }
+
+    #[test]
+    fn bad_definitions_are_rejected() {
+        let mut registry = Registry::<u8>::new(Overflow::Wrapping);
+        for definition in ["xnor", "x y z = a", "f = a + c", "f = table 101", "and = a"] {
+            assert!(registry.define(definition).is_err(), "{definition}");
+        }
+        assert!(registry.definitions.is_empty());
+    }
+
+    #[test]
+    fn redefinition_keeps_position_and_updates_dependents() {
+        let mut registry = Registry::<u8>::new(Overflow::Wrapping);
+        registry.define("xnor = ~(a ^ b)").unwrap();
+        registry.define("f = a xnor b & a").unwrap();
+        registry.define("xnor = a ^ b").unwrap();
+
+        assert_eq!(names(&registry), ["xnor", "f"]);
+        assert_eq!(eval(&registry, "a f b"), (0b1100 ^ 0b1010) & 0b1100);
+
+        let loaded = round_trip(&registry, "redefine.txt");
+        assert_eq!(names(&loaded), ["xnor", "f"]);
+        assert_eq!(eval(&loaded, "a f b"), eval(&registry, "a f b"));
+    }
+
+    #[test]
+    fn redefinition_that_breaks_dependents_is_rejected() {
+        let mut registry = Registry::<u8>::new(Overflow::Wrapping);
+        registry.define("xnor = ~(a ^ b)").unwrap();
+        registry.define("f = a xnor b").unwrap();
+        registry.define("g = f a").unwrap_err();
+
+        // Смена арности и ссылка на операцию, определённую позже
+        assert!(registry.define("xnor = ~a").is_err());
+        assert!(registry.define("xnor = a f b").is_err());
+
+        assert_eq!(eval(&registry, "a f b"), !(0b1100 ^ 0b1010));
+        let loaded = round_trip(&registry, "reject.txt");
+        assert_eq!(names(&loaded), ["xnor", "f"]);
+    }
+
+    #[test]
+    fn removal() {
+        let mut registry = Registry::<u8>::new(Overflow::Wrapping);
+        registry.define("xnor = ~(a ^ b)").unwrap();
+        registry.define("f = a xnor b").unwrap();
+        registry.define("g = a & ~b").unwrap();
+
+        assert!(registry.remove("and").is_err());
+        assert!(registry.remove("missing").is_err());
+        assert!(registry.remove("xnor").is_err());
+
+        registry.remove("g").unwrap();
+        registry.remove("f").unwrap();
+        registry.remove("xnor").unwrap();
+        assert!(registry.parser().parse("a xnor b").is_err());
+
+        registry.define("g = a & ~b").unwrap();
+        let loaded = round_trip(&registry, "remove.txt");
+        assert_eq!(names(&loaded), ["g"]);
+        assert_eq!(eval(&loaded, "a g b"), 0b1100 & !0b1010);
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::expr::{is_identifier, ExprError};
+use crate::registry::Registry;
+use crate::word::{Base, Word};
+
+const HELP: &str = "\
+Выражение         вычислить, например (a ^ b) ↑ ~c | 0xF0
+имя = выражение   присвоить значение переменной
+_                 результат последнего вычисления
+:ops              список операций
+:def ОПРЕДЕЛЕНИЕ  определить операцию: xnor = ~(a ^ b), xnor ⊙ = ~(a ^ b) или imp = table 1101
+:undef ИМЯ        удалить пользовательскую операцию
+:save [ФАЙЛ]      сохранить пользовательские операции
+:load ФАЙЛ        загрузить операции из файла
+:vars             список переменных
+:base [СИСТЕМА]   показать или сменить систему счисления: bin, oct, dec, hex
+:help             эта справка
+:quit             выход";
+
+/// Что делать со строкой, которую ввёл пользователь
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reply {
+    /// Напечатать ответ
+    Print(String),
+    /// Пустая строка: ничего не делать
+    Skip,
+    /// Завершить работу
+    Quit,
+}
+
+/// Интерактивный калькулятор
+pub struct Repl<T> {
+    registry: Registry<T>,
+    /// Файл, в который сохраняются пользовательские операции
+    path: Option<PathBuf>,
+    vars: HashMap<String, T>,
+    base: Base,
+    width: usize,
+}
+
+impl<T: Word + 'static> Repl<T> {
+    pub fn new(registry: Registry<T>, path: Option<PathBuf>, width: usize) -> Self {
+        Self {
+            registry,
+            path,
+            vars: HashMap::new(),
+            base: Base::Hex,
+            width,
+        }
+    }
+
+    /// Цикл чтения команд. rustyline даёт редактирование строки и историю (стрелки вверх/вниз).
+    pub fn run(&mut self) -> rustyline::Result<()> {
+        let mut editor = DefaultEditor::new()?;
+
+        println!("Калькулятор на {} бит. Справка: :help", self.width);
+
+        loop {
+            let line = match editor.readline("> ") {
+                Ok(line) => line,
+                // Ctrl+C сбрасывает строку, Ctrl+D завершает работу
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+
+            if !line.trim().is_empty() {
+                editor.add_history_entry(line.trim())?;
+            }
+            match self.handle(&line) {
+                Reply::Print(output) => println!("{output}"),
+                Reply::Skip => {}
+                Reply::Quit => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Обработать одну введённую строку. Отделено от rustyline, чтобы калькулятор
+    /// можно было проверить без терминала.
+    pub fn handle(&mut self, line: &str) -> Reply {
+        let line = line.trim();
+        match line {
+            "" => Reply::Skip,
+            ":quit" | ":q" => Reply::Quit,
+            _ => Reply::Print(match self.execute(line) {
+                Ok(output) => output,
+                Err(e) => format!("Ошибка: {e}"),
+            }),
+        }
+    }
+
+    /// Выполнить одну команду и вернуть текст для вывода
+    fn execute(&mut self, line: &str) -> Result<String, ExprError> {
+        if let Some(command) = line.strip_prefix(':') {
+            return Ok(self.command(command));
+        }
+
+        // Присваивание: слева должно быть имя переменной
+        if let Some((name, expr)) = line.split_once('=') {
+            let name = name.trim();
+            if !is_identifier(name) {
+                return Err(ExprError::Syntax(0, format!("bad variable name '{name}'")));
+            }
+
+            let value = self
+                .registry
+                .parser()
+                .parse(expr)?
+                .eval(&self.vars, self.width)?;
+            let output = format!("{name} = {}", value.to_base(self.base));
+            self.vars.insert(name.to_string(), value);
+            return Ok(output);
+        }
+
+        let value = self
+            .registry
+            .parser()
+            .parse(line)?
+            .eval(&self.vars, self.width)?;
+        let output = format!("= {}", value.to_base(self.base));
+        self.vars.insert("_".to_string(), value);
+        Ok(output)
+    }
+
+    /// Служебные команды, начинающиеся с двоеточия
+    fn command(&mut self, command: &str) -> String {
+        let (command, rest) = command.split_once(' ').unwrap_or((command, ""));
+        let rest = rest.trim();
+        let mut args = rest.split_whitespace();
+
+        match (command, args.next()) {
+            ("ops", _) => {
+                let user = |name: &str| {
+                    if self.registry.is_user_defined(name) {
+                        ", пользовательская"
+                    } else {
+                        ""
+                    }
+                };
+                let binary =
+                    self.registry.operations().into_iter().map(|op| {
+                        format!("{:>4}  {}{}", op.designation(), op.name(), user(op.name()))
+                    });
+                let unary = self.registry.unary_operations().into_iter().map(|op| {
+                    let user = user(op.name());
+                    format!("{:>4}  {} (унарная{user})", op.designation(), op.name())
+                });
+                binary.chain(unary).collect::<Vec<_>>().join("\n")
+            }
+            ("def", _) => match self.registry.define(rest) {
+                Ok(()) => "Операция определена".to_string(),
+                Err(e) => format!("Ошибка: {e}"),
+            },
+            ("undef", Some(name)) => match self.registry.remove(name) {
+                Ok(()) => format!("Операция {name} удалена"),
+                Err(e) => format!("Ошибка: {e}"),
+            },
+            ("save", path) => match path.map(PathBuf::from).or_else(|| self.path.clone()) {
+                Some(path) => match self.registry.save(&path) {
+                    Ok(()) => format!("Сохранено в {}", path.display()),
+                    Err(e) => format!("Ошибка: {e}"),
+                },
+                None => "Ошибка: не указан файл".to_string(),
+            },
+            ("load", Some(path)) => match self.registry.load(path.as_ref()) {
+                Ok(()) => format!("Загружено из {path}"),
+                Err(e) => format!("Ошибка: {e}"),
+            },
+            ("vars", _) => {
+                let mut names: Vec<&String> = self.vars.keys().collect();
+                names.sort();
+                names
+                    .into_iter()
+                    .map(|name| format!("{name} = {}", self.vars[name].to_base(self.base)))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            ("base", None) => format!("{:?}", self.base).to_lowercase(),
+            ("base", Some(base)) => match base.parse() {
+                Ok(base) => {
+                    self.base = base;
+                    format!("{:?}", self.base).to_lowercase()
+                }
+                Err(e) => format!("Ошибка: {e}"),
+            },
+            ("help", _) => HELP.to_string(),
+            _ => format!("Неизвестная команда :{command}. Справка: :help"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith::Overflow;
+
+    fn repl() -> Repl<u8> {
+        Repl::new(Registry::new(Overflow::Wrapping), None, 8)
+    }
+
+    /// Ввести строки по очереди и собрать ответы
+    fn session(lines: &[&str]) -> Vec<String> {
+        let mut repl = repl();
+        lines
+            .iter()
+            .map(|line| match repl.handle(line) {
+                Reply::Print(output) => output,
+                reply => format!("{reply:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expressions_and_variables() {
+        assert_eq!(
+            session(&["1 + 2", "a = 5", "  a * 3 ", "_ + 1", "b = _ ^ a", ":vars"]),
+            [
+                "= 03",
+                "a = 05",
+                "= 0f",
+                "= 10",
+                "b = 15",
+                "_ = 10\na = 05\nb = 15"
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_lines_and_quit() {
+        let mut repl = repl();
+        assert_eq!(repl.handle(""), Reply::Skip);
+        assert_eq!(repl.handle("   "), Reply::Skip);
+        assert_eq!(repl.handle(":quit"), Reply::Quit);
+        assert_eq!(repl.handle(" :q "), Reply::Quit);
+    }
+
+    #[test]
+    fn base_command() {
+        assert_eq!(
+            session(&[
+                ":base",
+                ":base dec",
+                "200 + 100",
+                ":base bin",
+                "5",
+                ":base 7",
+                ":base"
+            ]),
+            [
+                "hex",
+                "dec",
+                "= 44",
+                "bin",
+                "= 00000101",
+                "Ошибка: unknown base '7', expected bin, oct, dec or hex",
+                "bin"
+            ]
+        );
+    }
+
+    #[test]
+    fn define_and_remove_operations() {
+        assert_eq!(
+            session(&[
+                ":def xnor ⊙ = ~(a ^ b)",
+                "0x0f ⊙ 0x05",
+                ":undef xnor",
+                "0x0f ⊙ 0x05",
+                ":undef xnor",
+            ]),
+            [
+                "Операция определена",
+                "= f5",
+                "Операция xnor удалена",
+                "Ошибка: syntax error at 5: unexpected '⊙'",
+                "Ошибка: bad definition: 'xnor' is not a user-defined operation",
+            ]
+        );
+        let output = session(&[":def bad = a +"]);
+        assert!(output[0].starts_with("Ошибка: "), "{}", output[0]);
+    }
+
+    #[test]
+    fn bad_input_is_reported() {
+        assert_eq!(
+            session(&["2x = 1", "q + 1", "1 / 0", ":nope", ":save"]),
+            [
+                "Ошибка: syntax error at 0: bad variable name '2x'",
+                "Ошибка: unknown variable 'q'",
+                "Ошибка: division by zero",
+                "Неизвестная команда :nope. Справка: :help",
+                "Ошибка: не указан файл",
+            ]
+        );
+        // Неудачное присваивание не меняет переменные
+        assert_eq!(session(&["a = 1 +", ":vars"])[1], "");
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::expr::{Expr, ExprError};
+use crate::qmc::{minimal_cover, prime_implicants, Cover, Implicant};
+
+/// Максимальное число переменных в таблице истинности
+pub const MAX_TABLE_VARS: usize = 16;
+
+/// Максимальное число переменных, для которых строится минимальная форма
+pub const MAX_MINIMIZE_VARS: usize = 10;
+
+/// Максимальное число переменных в карте Карно
+pub const MAX_KMAP_VARS: usize = 6;
+
+/// Нормальная форма функции (ДНФ или КНФ)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Form {
+    pub text: String,
+    /// Минимальна ли форма. Если метод Петрика не справился, форма строится
+    /// по жадному покрытию и может быть длиннее минимальной.
+    pub minimal: bool,
+}
+
+/// Таблица истинности булевой функции.
+/// Строка с номером `i` соответствует набору, где переменная 0 -- старший бит `i`.
+pub struct TruthTable {
+    pub vars: Vec<String>,
+    pub values: Vec<bool>,
+}
+
+impl TruthTable {
+    /// Построить таблицу, вычислив выражение на всех наборах из нулей и единиц.
+    /// Выражение вычисляется над u8, а результатом считается младший бит.
+    pub fn from_expr(expr: &Expr<u8>) -> Result<Self, ExprError> {
+        let vars = expr.variables();
+        if vars.len() > MAX_TABLE_VARS {
+            return Err(ExprError::TooManyVariables(vars.len(), MAX_TABLE_VARS));
+        }
+
+        let n = vars.len();
+        let mut values = Vec::with_capacity(1 << n);
+
+        for row in 0..1u32 << n {
+            let inputs: HashMap<String, u8> = vars
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), (row >> (n - 1 - i) & 1) as u8))
+                .collect();
+
+            values.push(expr.eval(&inputs, 8)? & 1 == 1);
+        }
+
+        Ok(Self { vars, values })
+    }
+
+    /// Номера наборов, на которых функция принимает значение `value`
+    fn rows_with(&self, value: bool) -> Vec<u32> {
+        (0..self.values.len() as u32)
+            .filter(|&row| self.values[row as usize] == value)
+            .collect()
+    }
+
+    /// Таблица истинности в текстовом виде
+    pub fn render(&self) -> String {
+        let n = self.vars.len();
+        let mut out = String::new();
+
+        let _ = writeln!(out, "{} | f", self.vars.join(" "));
+        let _ = writeln!(
+            out,
+            "{}+--",
+            self.vars
+                .iter()
+                .map(|v| "-".repeat(v.chars().count() + 1))
+                .collect::<String>()
+        );
+
+        for (row, &value) in self.values.iter().enumerate() {
+            for (i, name) in self.vars.iter().enumerate() {
+                let bit = row >> (n - 1 - i) & 1;
+                let _ = write!(out, "{bit:>width$} ", width = name.chars().count());
+            }
+            let _ = writeln!(out, "| {}", value as u8);
+        }
+
+        out
+    }
+
+    /// Минимальная дизъюнктивная нормальная форма (сумма произведений).
+    /// Для слишком большого числа переменных не строится.
+    pub fn sop(&self) -> Option<Form> {
+        let Cover {
+            implicants: cover,
+            minimal,
+        } = self.minimize(true)?;
+
+        if cover.is_empty() {
+            return Some(Form {
+                text: "0".to_string(),
+                minimal,
+            });
+        }
+
+        let text = cover
+            .iter()
+            .map(|imp| {
+                let literals = self.literals(imp, false);
+                match literals.len() {
+                    0 => "1".to_string(),
+                    1 => literals[0].clone(),
+                    _ if cover.len() == 1 => literals.join(" & "),
+                    _ => format!("({})", literals.join(" & ")),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        Some(Form { text, minimal })
+    }
+
+    /// Минимальная конъюнктивная нормальная форма (произведение сумм).
+    /// Строится из минимальной ДНФ отрицания функции по закону де Моргана.
+    pub fn pos(&self) -> Option<Form> {
+        let Cover {
+            implicants: cover,
+            minimal,
+        } = self.minimize(false)?;
+
+        if cover.is_empty() {
+            return Some(Form {
+                text: "1".to_string(),
+                minimal,
+            });
+        }
+
+        let text = cover
+            .iter()
+            .map(|imp| {
+                let literals = self.literals(imp, true);
+                match literals.len() {
+                    0 => "0".to_string(),
+                    1 => literals[0].clone(),
+                    _ if cover.len() == 1 => literals.join(" | "),
+                    _ => format!("({})", literals.join(" | ")),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" & ");
+
+        Some(Form { text, minimal })
+    }
+
+    /// Минимальное покрытие наборов, на которых функция равна `value`
+    fn minimize(&self, value: bool) -> Option<Cover> {
+        let n = self.vars.len();
+        if n > MAX_MINIMIZE_VARS {
+            return None;
+        }
+
+        let minterms = self.rows_with(value);
+        Some(minimal_cover(&prime_implicants(&minterms), &minterms, n))
+    }
+
+    /// Литералы импликанты. Если `invert`, то все литералы берутся с отрицанием.
+    fn literals(&self, imp: &Implicant, invert: bool) -> Vec<String> {
+        imp.terms(self.vars.len())
+            .into_iter()
+            .map(|(var, value)| {
+                if value != invert {
+                    self.vars[var].clone()
+                } else {
+                    format!("~{}", self.vars[var])
+                }
+            })
+            .collect()
+    }
+
+    /// Карта Карно. Для 5 и 6 переменных рисуется несколько карт 4x4,
+    /// по одной на каждое значение первых переменных.
+    pub fn kmap(&self) -> Option<String> {
+        let n = self.vars.len();
+        if !(2..=MAX_KMAP_VARS).contains(&n) {
+            return None;
+        }
+
+        let outer = n.saturating_sub(4);
+        let inner = n - outer;
+        let row_vars = inner / 2;
+        let col_vars = inner - row_vars;
+
+        let mut out = String::new();
+
+        for map in 0..1usize << outer {
+            if outer > 0 {
+                let _ = writeln!(out, "{} = {:0outer$b}:", self.vars[..outer].join(""), map,);
+            }
+
+            let rows_name = self.vars[outer..outer + row_vars].join("");
+            let cols_name = self.vars[outer + row_vars..].join("");
+            let label = rows_name.chars().count().max(row_vars);
+
+            let _ = writeln!(out, "{rows_name:>label$}\\{cols_name}");
+            let _ = write!(out, "{:label$}  ", "");
+            for col in gray_codes(col_vars) {
+                let _ = write!(out, " {col:0col_vars$b}");
+            }
+            let _ = writeln!(out);
+
+            for row in gray_codes(row_vars) {
+                let _ = write!(out, "{:>label$} |", format!("{row:0row_vars$b}"));
+                for col in gray_codes(col_vars) {
+                    let index = (map << inner) | (row << col_vars) | col;
+                    let _ = write!(out, " {:>col_vars$}", self.values[index] as u8);
+                }
+                let _ = writeln!(out);
+            }
+        }
+
+        Some(out)
+    }
+}
+
+/// Код Грея на `bits` битах: соседние значения отличаются в одном бите,
+/// поэтому соседние клетки карты Карно можно склеивать
+fn gray_codes(bits: usize) -> impl Iterator<Item = usize> {
+    (0..1usize << bits).map(|i| i ^ (i >> 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Таблица функции `f` от переменных с именами из `vars`
+    fn truth_table(vars: &str, f: impl Fn(&[bool]) -> bool) -> TruthTable {
+        let vars: Vec<String> = vars.chars().map(String::from).collect();
+        let n = vars.len();
+        let values = (0..1usize << n)
+            .map(|row| {
+                let bits: Vec<bool> = (0..n).map(|i| row >> (n - 1 - i) & 1 == 1).collect();
+                f(&bits)
+            })
+            .collect();
+        TruthTable { vars, values }
+    }
+
+    #[test]
+    fn minimal_forms() {
+        let table = truth_table("abc", |x| x[0] && !x[1] || x[2]);
+        assert_eq!(table.sop().unwrap().text, "c | (a & ~b)");
+        assert_eq!(table.pos().unwrap().text, "(a | c) & (~b | c)");
+
+        let constant = truth_table("ab", |_| false);
+        assert_eq!(constant.sop().unwrap().text, "0");
+        assert_eq!(constant.pos().unwrap().text, "0");
+    }
+
+    #[test]
+    fn greedy_fallback_is_not_minimal() {
+        // (a & ~b) | (b & ~c) | ... | (j & ~a): у функции 90 простых импликант,
+        // и метод Петрика на ней не справляется
+        let table = truth_table("abcdefghij", |x| (0..10).any(|i| x[i] && !x[(i + 1) % 10]));
+        let sop = table.sop().unwrap();
+        assert!(!sop.minimal);
+        assert!(sop.text.matches('|').count() >= 9, "{}", sop.text);
+    }
+
+    #[test]
+    fn kmap_layout() {
+        let table = truth_table("abc", |x| x[0] && !x[1] || x[2]);
+        assert_eq!(
+            table.kmap().unwrap(),
+            "a\\bc\n    00 01 11 10\n0 |  0  1  1  0\n1 |  1  1  1  0\n"
+        );
+
+        // Соседние клетки отличаются в одной переменной: строки и столбцы в коде Грея
+        let table = truth_table("abcd", |x| x[0] && x[1] && !x[2] && x[3]);
+        assert_eq!(
+            table.kmap().unwrap(),
+            "ab\\cd\n     00 01 11 10\n00 |  0  0  0  0\n01 |  0  0  0  0\n\
+             11 |  0  1  0  0\n10 |  0  0  0  0\n"
+        );
+    }
+
+    #[test]
+    fn kmap_splits_five_variables() {
+        let kmap = truth_table("abcde", |x| x[0] && x[4]).kmap().unwrap();
+        assert!(kmap.starts_with("a = 0:\nbc\\de\n"));
+        assert!(kmap.contains("a = 1:\nbc\\de\n"));
+        assert_eq!(kmap.matches("00 |  0  0  0  0").count(), 1);
+        assert_eq!(kmap.matches("00 |  0  1  1  0").count(), 1);
+    }
+
+    #[test]
+    fn kmap_size_limits() {
+        assert!(truth_table("a", |x| x[0]).kmap().is_none());
+        assert!(truth_table("abcdefg", |x| x[0]).kmap().is_none());
+        assert!(truth_table("abcdef", |x| x[0]).kmap().is_some());
+    }
+}
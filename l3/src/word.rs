@@ -0,0 +1,232 @@
+use std::fmt::{Binary, Display, LowerHex, Octal};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+use std::str::FromStr;
+
+use crate::bitvec::BitVec;
+
+/// Машинное слово, над которым можно выполнять побитовые операции.
+/// Реализован для всех примитивных целых чисел и для битового вектора произвольной длины.
+pub trait Word:
+    Clone
+    + PartialEq
+    + Display
+    + Binary
+    + Octal
+    + LowerHex
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+{
+    /// Разрядность значения в битах
+    fn width(&self) -> usize;
+
+    /// Создать значение заданной разрядности из числа. Лишние старшие биты отбрасываются.
+    /// У примитивных типов разрядность фиксирована, поэтому `width` для них игнорируется.
+    fn from_u128(value: u128, width: usize) -> Self;
+
+    /// Младшие 128 бит значения как беззнаковое число
+    fn to_u128(&self) -> u128;
+
+    /// Знаковый ли тип (старший бит -- знак в дополнительном коде)
+    fn is_signed(&self) -> bool;
+
+    /// Биты значения в виде битового вектора той же разрядности
+    fn to_bitvec(&self) -> BitVec;
+
+    /// Значение из битового вектора той же разрядности
+    fn from_bitvec(bits: &BitVec) -> Self;
+
+    /// Расширить значение до `width` бит: знаковые типы дополняются знаком, остальные -- нулями
+    fn extend(&self, width: usize) -> BitVec {
+        let bits = self.to_bitvec();
+        let mut result = bits.resize(width);
+
+        if self.is_signed() && bits.bit(self.width() - 1) {
+            for i in self.width()..width {
+                result.set_bit(i, true);
+            }
+        }
+
+        result
+    }
+
+    /// Сдвиг влево. Сдвиг на разрядность и больше даёт ноль.
+    fn shift_left(self, n: usize) -> Self;
+
+    /// Сдвиг вправо: логический для беззнаковых типов и арифметический для знаковых
+    fn shift_right(self, n: usize) -> Self;
+
+    /// Двоичное представление, дополненное нулями до разрядности
+    fn to_binary(&self) -> String {
+        self.to_base(Base::Bin)
+    }
+
+    /// Представление в заданной системе счисления. Двоичная, восьмеричная и
+    /// шестнадцатеричная записи дополняются нулями до разрядности.
+    fn to_base(&self, base: Base) -> String {
+        let width = self.width();
+        match base {
+            Base::Bin => format!("{:0width$b}", self),
+            Base::Oct => format!("{:0width$o}", self, width = width.div_ceil(3)),
+            Base::Dec => format!("{}", self),
+            Base::Hex => format!("{:0width$x}", self, width = width.div_ceil(4)),
+        }
+    }
+}
+
+/// Система счисления для вывода
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base {
+    Bin,
+    Oct,
+    Dec,
+    Hex,
+}
+
+impl FromStr for Base {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bin" | "2" => Ok(Base::Bin),
+            "oct" | "8" => Ok(Base::Oct),
+            "dec" | "10" => Ok(Base::Dec),
+            "hex" | "16" => Ok(Base::Hex),
+            _ => Err(format!("unknown base '{s}', expected bin, oct, dec or hex")),
+        }
+    }
+}
+
+// Макрос избавляет от копипасты одинаковой реализации для десяти типов
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                fn width(&self) -> usize {
+                    <$t>::BITS as usize
+                }
+
+                fn from_u128(value: u128, _: usize) -> Self {
+                    // `as` для целых чисел просто обрезает старшие биты
+                    value as $t
+                }
+
+                fn to_u128(&self) -> u128 {
+                    *self as u128
+                }
+
+                fn is_signed(&self) -> bool {
+                    <$t>::MIN != 0
+                }
+
+                fn to_bitvec(&self) -> BitVec {
+                    BitVec::from_u128(self.to_u128(), <$t>::BITS as usize)
+                }
+
+                fn from_bitvec(bits: &BitVec) -> Self {
+                    bits.to_u128() as $t
+                }
+
+                fn shift_left(self, n: usize) -> Self {
+                    // Обычный << паникует при сдвиге на разрядность и больше
+                    u32::try_from(n)
+                        .ok()
+                        .and_then(|n| self.checked_shl(n))
+                        .unwrap_or(0)
+                }
+
+                fn shift_right(self, n: usize) -> Self {
+                    // При слишком большом сдвиге остаётся только знак: 0 или -1
+                    u32::try_from(n)
+                        .ok()
+                        .and_then(|n| self.checked_shr(n))
+                        .unwrap_or(self >> (<$t>::BITS - 1) >> 1)
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// Тип слова, который выбирает пользователь
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    /// Битовый вектор заданной длины
+    Bits(usize),
+}
+
+impl WordType {
+    /// Разрядность слова в битах
+    pub fn width(&self) -> usize {
+        match self {
+            WordType::U8 | WordType::I8 => 8,
+            WordType::U16 | WordType::I16 => 16,
+            WordType::U32 | WordType::I32 => 32,
+            WordType::U64 | WordType::I64 => 64,
+            WordType::U128 | WordType::I128 => 128,
+            WordType::Bits(width) => *width,
+        }
+    }
+}
+
+impl FromStr for WordType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "u8" => WordType::U8,
+            "u16" => WordType::U16,
+            "u32" => WordType::U32,
+            "u64" => WordType::U64,
+            "u128" => WordType::U128,
+            "i8" => WordType::I8,
+            "i16" => WordType::I16,
+            "i32" => WordType::I32,
+            "i64" => WordType::I64,
+            "i128" => WordType::I128,
+            // Битовый вектор задаётся как b<длина>, например b256
+            _ => match s.strip_prefix('b').map(str::parse::<usize>) {
+                Some(Ok(width)) if width > 0 => WordType::Bits(width),
+                _ => {
+                    return Err(format!(
+                        "unknown type '{s}', expected u8..u128, i8..i128 or b<N>"
+                    ))
+                }
+            },
+        })
+    }
+}
+
+/// Вызывает обобщённую функцию с типом слова, выбранным во время выполнения.
+/// Обобщения в Rust раскрываются на этапе компиляции, поэтому приходится
+/// перечислять все варианты вручную.
+#[macro_export]
+macro_rules! with_word {
+    ($ty:expr, $func:ident ( $($arg:expr),* )) => {
+        match $ty {
+            $crate::word::WordType::U8 => $func::<u8>($($arg),*),
+            $crate::word::WordType::U16 => $func::<u16>($($arg),*),
+            $crate::word::WordType::U32 => $func::<u32>($($arg),*),
+            $crate::word::WordType::U64 => $func::<u64>($($arg),*),
+            $crate::word::WordType::U128 => $func::<u128>($($arg),*),
+            $crate::word::WordType::I8 => $func::<i8>($($arg),*),
+            $crate::word::WordType::I16 => $func::<i16>($($arg),*),
+            $crate::word::WordType::I32 => $func::<i32>($($arg),*),
+            $crate::word::WordType::I64 => $func::<i64>($($arg),*),
+            $crate::word::WordType::I128 => $func::<i128>($($arg),*),
+            $crate::word::WordType::Bits(_) => $func::<$crate::bitvec::BitVec>($($arg),*),
+        }
+    };
+}
@@ -0,0 +1,179 @@
+//! Проверка алгебраических свойств, которые объявляют операции.
+//! Для u8 и i8 перебираются все пары и тройки значений, для более широких типов
+//! свойства проверяются на случайных значениях (property testing).
+
+use proptest::prelude::*;
+
+use l3::arith::Overflow;
+use l3::bitvec::BitVec;
+use l3::operation::{operations, unary_operations};
+use l3::properties::{self, Properties, UnaryProperties};
+use l3::word::Word;
+
+const POLICIES: [Overflow; 4] = [
+    Overflow::Wrapping,
+    Overflow::Saturating,
+    Overflow::Checked,
+    Overflow::Widening,
+];
+
+/// Проверить все свойства операции `name` на всех значениях типа T шириной 8 бит
+fn exhaustive<T: Word + 'static>(name: &str) {
+    let values: Vec<T> = (0..=u8::MAX as u128).map(|x| T::from_u128(x, 8)).collect();
+
+    for overflow in POLICIES {
+        let operations = operations::<T>(overflow);
+        let op = operations.iter().find(|op| op.name() == name).unwrap();
+
+        for a in &values {
+            for b in &values {
+                if let Err(violation) = properties::check(op.as_ref(), a, b) {
+                    panic!("{name} ({overflow:?}): {violation}");
+                }
+            }
+        }
+
+        if !op.properties().associative {
+            continue;
+        }
+        for a in &values {
+            for b in &values {
+                for c in &values {
+                    if let Err(violation) = properties::check_associative(op.as_ref(), a, b, c) {
+                        panic!("{name} ({overflow:?}): {violation}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Генератор тестов: по тесту на каждую операцию, чтобы было видно, какая из них нарушает
+// свойство, а тесты могли выполняться параллельно
+macro_rules! exhaustive_tests {
+    ($($name:ident),* $(,)?) => {
+        mod exhaustive {
+            $(
+                #[test]
+                fn $name() {
+                    super::exhaustive::<u8>(stringify!($name));
+                    super::exhaustive::<i8>(stringify!($name));
+                }
+            )*
+        }
+    };
+}
+
+exhaustive_tests!(xor, and, or, nand, nor, add, sub, mul, div, rem, shl, shr, rotl, rotr);
+
+#[test]
+fn exhaustive_unary() {
+    for overflow in POLICIES {
+        for op in unary_operations::<u8>(overflow) {
+            for a in 0..=u8::MAX {
+                properties::check_unary(op.as_ref(), &a).unwrap();
+            }
+        }
+        for op in unary_operations::<i8>(overflow) {
+            for a in i8::MIN..=i8::MAX {
+                properties::check_unary(op.as_ref(), &a).unwrap();
+            }
+        }
+    }
+}
+
+/// У побитовых операций свойства выводятся из таблицы истинности точно,
+/// поэтому объявления должны с ними совпадать: ни одно свойство не забыто
+#[test]
+fn bitwise_declarations_are_complete() {
+    for op in operations::<u8>(Overflow::Wrapping) {
+        if let Some(table) = op.truth_table() {
+            assert_eq!(
+                op.properties(),
+                Properties::from_truth_table(table),
+                "{}",
+                op.name()
+            );
+        }
+    }
+    for op in unary_operations::<u8>(Overflow::Wrapping) {
+        if let Some(table) = op.truth_table() {
+            let derived = UnaryProperties::from_truth_table(table);
+            assert_eq!(op.properties(), derived, "{}", op.name());
+        }
+    }
+}
+
+/// Случайные значения вперемешку с граничными: 0, 1, ~0 и старший бит
+fn value() -> impl Strategy<Value = u128> {
+    prop_oneof![
+        Just(0),
+        Just(1),
+        Just(u128::MAX),
+        (0..128u32).prop_map(|bit| 1 << bit),
+        any::<u128>(),
+        any::<u128>(),
+    ]
+}
+
+/// Проверить свойства всех операций при всех политиках на операндах a, b, c
+fn check_all<T: Word + 'static>(a: &T, b: &T, c: &T) -> Result<(), TestCaseError> {
+    for overflow in POLICIES {
+        for op in operations::<T>(overflow) {
+            let result = properties::check(op.as_ref(), a, b)
+                .and_then(|()| properties::check_associative(op.as_ref(), a, b, c));
+            if let Err(violation) = result {
+                return Err(TestCaseError::fail(format!(
+                    "{} ({overflow:?}): {violation}",
+                    op.name()
+                )));
+            }
+        }
+        for op in unary_operations::<T>(overflow) {
+            if let Err(violation) = properties::check_unary(op.as_ref(), a) {
+                return Err(TestCaseError::fail(format!(
+                    "{} ({overflow:?}): {violation}",
+                    op.name()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+macro_rules! property_tests {
+    ($($name:ident: $ty:ty),* $(,)?) => {
+        proptest! {
+            $(
+                #[test]
+                fn $name(a in value(), b in value(), c in value()) {
+                    let width = <$ty>::BITS as usize;
+                    let [a, b, c] = [a, b, c].map(|x| <$ty as Word>::from_u128(x, width));
+                    check_all(&a, &b, &c)?;
+                }
+            )*
+        }
+    };
+}
+
+property_tests!(
+    random_u16: u16,
+    random_i16: i16,
+    random_u32: u32,
+    random_i32: i32,
+    random_u64: u64,
+    random_i64: i64,
+    random_u128: u128,
+    random_i128: i128,
+);
+
+proptest! {
+    /// Битовый вектор на 200 бит: старшие биты берутся из второго числа
+    #[test]
+    fn random_bitvec(a in (value(), value()), b in (value(), value()), c in (value(), value())) {
+        let bits = |(high, low): (u128, u128)| {
+            BitVec::from_u128(high, 200).shift_left(128) | BitVec::from_u128(low, 200)
+        };
+        check_all(&bits(a), &bits(b), &bits(c))?;
+    }
+}
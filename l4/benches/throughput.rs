@@ -0,0 +1,37 @@
+//! Скорость каждого алгоритма в МБ/с. Запускается через `cargo bench`
+//! (в отладочной сборке числа ничего не говорят).
+
+use std::env;
+use std::time::Duration;
+
+use l4::algorythm::HashAlgorythm;
+use l4::bench::{self, DATA_SIZE};
+use l4::stream::{format_size, Progress};
+
+fn main() {
+    // cargo bench передаёт --bench, а фильтр по имени алгоритма -- отдельным аргументом
+    let filter = env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .map(|arg| arg.to_lowercase());
+
+    let algos: Vec<HashAlgorythm> = HashAlgorythm::ALL
+        .into_iter()
+        .filter(|algo| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| algo.name().to_lowercase().contains(filter))
+        })
+        .collect();
+
+    let data = bench::sample_data(DATA_SIZE);
+    println!("Данные: {} кусками по 1 МиБ", format_size(DATA_SIZE as u64));
+    let progress = Progress::default();
+    bench::measure_all(&algos, &data, Duration::from_secs(1), &progress, |result| {
+        println!(
+            "{:<12} {:>10.1} МБ/с",
+            result.algo.name(),
+            result.mb_per_sec()
+        );
+    });
+}
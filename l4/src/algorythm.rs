@@ -0,0 +1,307 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use blake2::{Blake2b512, Blake2bMac512, Blake2s256, Blake2sMac256};
+use hmac::Hmac;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use sha3::digest::{DynDigest, ExtendableOutputReset, InvalidBufferSize, KeyInit, Reset, Update};
+use sha3::{Keccak256, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256};
+
+use crate::key::KeyError;
+
+/// Хэшер, который можно передать в другой поток (например, для хэширования файла в фоне)
+pub type BoxDigest = Box<dyn DynDigest + Send>;
+
+/// Хэш-алгоритм
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorythm {
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    /// Keccak в том виде, в каком он был до стандартизации SHA3 (используется в Ethereum)
+    Keccak256,
+    /// SHAKE -- функции с выводом произвольной длины (XOF) из семейства SHA3
+    Shake128,
+    Shake256,
+    Blake2b,
+    Blake2s,
+    Blake3,
+}
+
+impl HashAlgorythm {
+    /// Реестр алгоритмов. По нему строится drop-down: номер варианта в списке --
+    /// это индекс в массиве, поэтому новый алгоритм достаточно добавить сюда.
+    pub const ALL: [HashAlgorythm; 14] = [
+        HashAlgorythm::Sha224,
+        HashAlgorythm::Sha256,
+        HashAlgorythm::Sha384,
+        HashAlgorythm::Sha512,
+        HashAlgorythm::Sha3_224,
+        HashAlgorythm::Sha3_256,
+        HashAlgorythm::Sha3_384,
+        HashAlgorythm::Sha3_512,
+        HashAlgorythm::Keccak256,
+        HashAlgorythm::Shake128,
+        HashAlgorythm::Shake256,
+        HashAlgorythm::Blake2b,
+        HashAlgorythm::Blake2s,
+        HashAlgorythm::Blake3,
+    ];
+
+    /// Название для интерфейса
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorythm::Sha224 => "SHA-224",
+            HashAlgorythm::Sha256 => "SHA-256",
+            HashAlgorythm::Sha384 => "SHA-384",
+            HashAlgorythm::Sha512 => "SHA-512",
+            HashAlgorythm::Sha3_224 => "SHA3-224",
+            HashAlgorythm::Sha3_256 => "SHA3-256",
+            HashAlgorythm::Sha3_384 => "SHA3-384",
+            HashAlgorythm::Sha3_512 => "SHA3-512",
+            HashAlgorythm::Keccak256 => "Keccak-256",
+            HashAlgorythm::Shake128 => "SHAKE128",
+            HashAlgorythm::Shake256 => "SHAKE256",
+            HashAlgorythm::Blake2b => "BLAKE2b",
+            HashAlgorythm::Blake2s => "BLAKE2s",
+            HashAlgorythm::Blake3 => "BLAKE3",
+        }
+    }
+
+    /// Номер алгоритма в реестре
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|&algo| algo == self).unwrap()
+    }
+
+    /// Имена всех алгоритмов реестра для drop-down
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|algo| algo.name()).collect()
+    }
+
+    /// Можно ли выбрать длину хэша
+    pub fn is_xof(self) -> bool {
+        matches!(self, HashAlgorythm::Shake128 | HashAlgorythm::Shake256)
+    }
+
+    /// Длина хэша в байтах по умолчанию. Для SHAKE берётся длина,
+    /// при которой стойкость равна заявленной (128 или 256 битов).
+    pub fn default_output_len(self) -> usize {
+        match self {
+            HashAlgorythm::Shake128 => 32,
+            HashAlgorythm::Shake256 => 64,
+            _ => self.digest(0).output_size(),
+        }
+    }
+
+    /// Новый хэшер, в который данные можно подавать по частям. Box<dyn ...> нужен,
+    /// потому что у каждого алгоритма свой тип, а выбирается он во время работы программы.
+    /// Длина `output_len` в байтах учитывается только у алгоритмов с выводом произвольной длины.
+    pub fn digest(self, output_len: usize) -> BoxDigest {
+        match self {
+            HashAlgorythm::Sha224 => Box::new(Sha224::default()),
+            HashAlgorythm::Sha256 => Box::new(Sha256::default()),
+            HashAlgorythm::Sha384 => Box::new(Sha384::default()),
+            HashAlgorythm::Sha512 => Box::new(Sha512::default()),
+            HashAlgorythm::Sha3_224 => Box::new(Sha3_224::default()),
+            HashAlgorythm::Sha3_256 => Box::new(Sha3_256::default()),
+            HashAlgorythm::Sha3_384 => Box::new(Sha3_384::default()),
+            HashAlgorythm::Sha3_512 => Box::new(Sha3_512::default()),
+            HashAlgorythm::Keccak256 => Box::new(Keccak256::default()),
+            HashAlgorythm::Shake128 => Box::new(Xof::<Shake128>::new(output_len)),
+            HashAlgorythm::Shake256 => Box::new(Xof::<Shake256>::new(output_len)),
+            HashAlgorythm::Blake2b => Box::new(Blake2b512::default()),
+            HashAlgorythm::Blake2s => Box::new(Blake2s256::default()),
+            HashAlgorythm::Blake3 => Box::new(Blake3(blake3::Hasher::new())),
+        }
+    }
+
+    /// Код алгоритма в таблице multihash (https://github.com/multiformats/multicodec)
+    pub fn multihash_code(self) -> Option<u64> {
+        match self {
+            HashAlgorythm::Sha224 => Some(0x1013),
+            HashAlgorythm::Sha256 => Some(0x12),
+            HashAlgorythm::Sha384 => Some(0x20),
+            HashAlgorythm::Sha512 => Some(0x13),
+            HashAlgorythm::Sha3_224 => Some(0x17),
+            HashAlgorythm::Sha3_256 => Some(0x16),
+            HashAlgorythm::Sha3_384 => Some(0x15),
+            HashAlgorythm::Sha3_512 => Some(0x14),
+            HashAlgorythm::Keccak256 => Some(0x1b),
+            HashAlgorythm::Shake128 => Some(0x18),
+            HashAlgorythm::Shake256 => Some(0x19),
+            HashAlgorythm::Blake2b => Some(0xb240),
+            HashAlgorythm::Blake2s => Some(0xb260),
+            HashAlgorythm::Blake3 => Some(0x1e),
+        }
+    }
+
+    /// Название функции с ключом: HMAC-SHA-256 или keyed BLAKE3
+    pub fn keyed_name(self) -> String {
+        match self {
+            HashAlgorythm::Blake2b | HashAlgorythm::Blake2s | HashAlgorythm::Blake3 => {
+                format!("keyed {}", self.name())
+            }
+            _ => format!("HMAC-{}", self.name()),
+        }
+    }
+
+    /// Хэшер с секретным ключом. У BLAKE2 и BLAKE3 режим с ключом встроен в сам алгоритм,
+    /// для остальных используется HMAC: H((K ^ opad) || H((K ^ ipad) || m)).
+    /// У SHAKE режима с ключом нет (для него есть отдельный стандарт KMAC).
+    pub fn keyed_digest(self, key: &[u8]) -> Result<BoxDigest, KeyError> {
+        // HMAC принимает ключ любой длины: длинный ключ сначала хэшируется
+        fn hmac<H: KeyInit + DynDigest + Send + 'static>(
+            key: &[u8],
+        ) -> Result<BoxDigest, KeyError> {
+            Ok(Box::new(<H as KeyInit>::new_from_slice(key).unwrap()))
+        }
+
+        let length =
+            |expected: &str| KeyError::Length(self.name(), key.len(), expected.to_string());
+
+        match self {
+            HashAlgorythm::Sha224 => hmac::<Hmac<Sha224>>(key),
+            HashAlgorythm::Sha256 => hmac::<Hmac<Sha256>>(key),
+            HashAlgorythm::Sha384 => hmac::<Hmac<Sha384>>(key),
+            HashAlgorythm::Sha512 => hmac::<Hmac<Sha512>>(key),
+            HashAlgorythm::Sha3_224 => hmac::<Hmac<Sha3_224>>(key),
+            HashAlgorythm::Sha3_256 => hmac::<Hmac<Sha3_256>>(key),
+            HashAlgorythm::Sha3_384 => hmac::<Hmac<Sha3_384>>(key),
+            HashAlgorythm::Sha3_512 => hmac::<Hmac<Sha3_512>>(key),
+            HashAlgorythm::Keccak256 => hmac::<Hmac<Keccak256>>(key),
+            HashAlgorythm::Shake128 | HashAlgorythm::Shake256 => {
+                Err(KeyError::Unsupported(self.name()))
+            }
+            HashAlgorythm::Blake2b => <Blake2bMac512 as KeyInit>::new_from_slice(key)
+                .map(|mac| Box::new(mac) as BoxDigest)
+                .map_err(|_| length("at most 64")),
+            HashAlgorythm::Blake2s => <Blake2sMac256 as KeyInit>::new_from_slice(key)
+                .map(|mac| Box::new(mac) as BoxDigest)
+                .map_err(|_| length("at most 32")),
+            HashAlgorythm::Blake3 => {
+                let key: &[u8; blake3::KEY_LEN] =
+                    key.try_into().map_err(|_| length("exactly 32"))?;
+                Ok(Box::new(Blake3(blake3::Hasher::new_keyed(key))))
+            }
+        }
+    }
+}
+
+impl Display for HashAlgorythm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for HashAlgorythm {
+    type Err = String;
+
+    /// Алгоритм по названию без учёта регистра, дефисов и подчёркиваний:
+    /// sha3-384, SHA256 (так пишут coreutils), blake2b
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|algo| normalize(algo.name()) == normalize(s))
+            .ok_or_else(|| format!("unknown algorithm '{s}'"))
+    }
+}
+
+/// Название алгоритма без различий в записи: "SHA3-256" -> "sha3256"
+pub fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Функция с выводом произвольной длины (XOF) выдаёт сколько угодно байтов,
+/// а DynDigest рассчитан на хэш фиксированной длины. Обёртка запоминает нужную длину.
+#[derive(Clone, Default)]
+struct Xof<T> {
+    inner: T,
+    output_len: usize,
+}
+
+impl<T: Default> Xof<T> {
+    fn new(output_len: usize) -> Self {
+        Self {
+            inner: T::default(),
+            output_len,
+        }
+    }
+}
+
+impl<T: Update + ExtendableOutputReset + Default + Clone + 'static> DynDigest for Xof<T> {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize_into(self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if buf.len() != self.output_len {
+            return Err(InvalidBufferSize);
+        }
+        self.inner.finalize_xof_into(buf);
+        Ok(())
+    }
+
+    fn finalize_into_reset(&mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if buf.len() != self.output_len {
+            return Err(InvalidBufferSize);
+        }
+        self.inner.finalize_xof_reset_into(buf);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        Reset::reset(&mut self.inner);
+    }
+
+    fn output_size(&self) -> usize {
+        self.output_len
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
+
+/// BLAKE3 не реализует типажи крейта digest той версии, что у остальных алгоритмов,
+/// поэтому DynDigest для него реализован поверх его собственного интерфейса
+#[derive(Clone)]
+struct Blake3(blake3::Hasher);
+
+impl DynDigest for Blake3 {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize_into(mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        self.finalize_into_reset(buf)
+    }
+
+    fn finalize_into_reset(&mut self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if buf.len() != blake3::OUT_LEN {
+            return Err(InvalidBufferSize);
+        }
+        buf.copy_from_slice(self.0.finalize().as_bytes());
+        self.0.reset();
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn output_size(&self) -> usize {
+        blake3::OUT_LEN
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use crate::algorythm::HashAlgorythm;
+use crate::stream::{Progress, CHUNK_SIZE};
+
+/// Сколько данных хэшируется за один замер
+pub const DATA_SIZE: usize = 16 * CHUNK_SIZE;
+
+/// Замер хватает хотя бы на столько: на коротких отрезках сильнее мешают
+/// другие процессы и разгон процессора
+pub const MIN_TIME: Duration = Duration::from_millis(300);
+
+/// Скорость алгоритма на этой машине
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Throughput {
+    pub algo: HashAlgorythm,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    /// Мегабайты (по 10^6 байт, как у дисков) в секунду
+    pub fn mb_per_sec(&self) -> f64 {
+        self.bytes as f64 / 1e6 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Данные для замера. Хэшам всё равно, что хэшировать, но нули выглядели бы подозрительно.
+pub fn sample_data(size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i % 251) as u8).collect()
+}
+
+/// Хэшировать `data` алгоритмом `algo` кусками по CHUNK_SIZE, как файл,
+/// пока не пройдёт хотя бы `min_time`
+pub fn measure(algo: HashAlgorythm, data: &[u8], min_time: Duration) -> Throughput {
+    let start = Instant::now();
+    let mut bytes = 0;
+    while bytes == 0 || start.elapsed() < min_time {
+        let mut digest = algo.digest(algo.default_output_len());
+        for chunk in data.chunks(CHUNK_SIZE) {
+            digest.update(chunk);
+        }
+        std::hint::black_box(digest.finalize());
+        bytes += data.len() as u64;
+    }
+
+    Throughput {
+        algo,
+        bytes,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Замерить алгоритмы по очереди. `report` вызывается после каждого замера,
+/// чтобы результаты можно было показывать по мере готовности. Отмена через `progress`
+/// останавливает замеры между алгоритмами.
+pub fn measure_all(
+    algos: &[HashAlgorythm],
+    data: &[u8],
+    min_time: Duration,
+    progress: &Progress,
+    mut report: impl FnMut(Throughput),
+) {
+    for &algo in algos {
+        if progress.is_cancelled() {
+            break;
+        }
+        report(measure(algo, data, min_time));
+    }
+}
@@ -0,0 +1,392 @@
+use std::fs::{self, File};
+use std::io::{self, IsTerminal as _};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use crate::algorythm::{BoxDigest, HashAlgorythm};
+use crate::hasher::{Change, HashError, HashPreviewOption, Hasher, TextEncoding};
+use crate::key::Key;
+use crate::manifest::{self, ManifestError, Status};
+use crate::password::{self, Params, PasswordAlgorithm};
+use crate::stream::{hash_reader, Progress};
+use crate::tree::{self, Tree};
+
+/// Прочитать ключ из аргумента или из файла. У файла отбрасывается
+/// завершающий перевод строки, который обычно дописывают редакторы и echo.
+pub fn read_key(
+    key: Option<String>,
+    key_file: Option<PathBuf>,
+    encoding: TextEncoding,
+) -> io::Result<Key> {
+    let text = match (key, key_file) {
+        (Some(key), _) => key,
+        (None, Some(path)) => {
+            let text = fs::read_to_string(path)?;
+            let text = text.strip_suffix('\n').unwrap_or(&text);
+            text.strip_suffix('\r').unwrap_or(text).to_string()
+        }
+        (None, None) => String::new(),
+    };
+
+    Ok(Key::new(&text, encoding))
+}
+
+/// Посчитать хэши файлов и вывести их в формате coreutils: «хэш  имя файла».
+/// Без файлов читается стандартный ввод.
+pub fn hash(
+    algo: HashAlgorythm,
+    length: Option<usize>,
+    encoding: HashPreviewOption,
+    files: &[PathBuf],
+) -> ExitCode {
+    let mut hasher = Hasher::new(algo, encoding);
+    if let Some(length) = length {
+        if !algo.is_xof() {
+            eprintln!("Ошибка: длину хэша можно задать только у SHAKE128 и SHAKE256");
+            return ExitCode::FAILURE;
+        }
+        if length == 0 {
+            eprintln!("Ошибка: длина хэша должна быть не меньше одного байта");
+            return ExitCode::FAILURE;
+        }
+        hasher.apply(Change::OutputLen(length));
+    }
+
+    hash_files(hasher, files)
+}
+
+/// Посчитать HMAC (или хэш BLAKE2/BLAKE3 с ключом) файлов и вывести результат
+/// в формате coreutils: «хэш  имя файла». Без файлов читается стандартный ввод.
+pub fn hmac(
+    algo: HashAlgorythm,
+    key: &Key,
+    encoding: HashPreviewOption,
+    files: &[PathBuf],
+) -> ExitCode {
+    let mut hasher = Hasher::new(algo, encoding);
+    hasher.apply(Change::Key(Some(key.clone())));
+
+    hash_files(hasher, files)
+}
+
+/// Захэшировать файлы с настройками модели `hasher` и вывести строки в формате coreutils.
+/// Если какой-то файл не прочитался, остальные всё равно хэшируются, но код выхода -- ошибка.
+pub fn hash_files(mut hasher: Hasher, files: &[PathBuf]) -> ExitCode {
+    // Ключ и вид хэша проверяются один раз: если они не подходят, не подойдут ни к одному файлу
+    if let Err(e) = hasher
+        .digest()
+        .map_err(HashError::from)
+        .and_then(|_| hasher.encode(hasher.algo(), &[]))
+    {
+        eprintln!("Ошибка: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let stdin = [PathBuf::from("-")];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+
+    let mut code = ExitCode::SUCCESS;
+    for path in files {
+        hasher.apply(Change::File(path.clone()));
+        let revision = hasher.revision();
+        match hash_path(path, hasher.digest().unwrap()) {
+            Ok(data) => hasher.apply(Change::FileHashed(revision, vec![data])),
+            Err(e) => hasher.apply(Change::FileFailed(revision, HashError::Read(e.to_string()))),
+        }
+
+        let view = match hasher.error() {
+            Some(e) => Err(e.clone()),
+            None => hasher.view(),
+        };
+        match view {
+            Ok(view) => println!("{}", manifest::format_line(&view, &path.to_string_lossy())),
+            Err(e) => {
+                eprintln!("Ошибка: {}: {e}", path.display());
+                code = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    code
+}
+
+/// Захэшировать файл, а если путь "-" -- стандартный ввод
+pub fn hash_path(path: &Path, digest: BoxDigest) -> io::Result<Vec<u8>> {
+    let progress = Progress::default();
+    if path == Path::new("-") {
+        hash_reader(io::stdin().lock(), digest, &progress)
+    } else {
+        hash_reader(File::open(path)?, digest, &progress)
+    }
+}
+
+/// Проверить файлы по манифестам с контрольными суммами, как `sha256sum --check`:
+/// для каждого файла выводится «имя: OK» или «имя: FAILED»
+pub fn check(manifests: &[PathBuf], default: HashAlgorythm) -> ExitCode {
+    let progress = Progress::default();
+    let (mut failed, mut unreadable) = (0, 0);
+
+    for path in manifests {
+        let entries = match manifest::load(path, default) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Ошибка: {}: {e}", path.display());
+                return ExitCode::FAILURE;
+            }
+        };
+
+        for entry in entries {
+            let status = manifest::check(&entry, &progress);
+            println!("{}: {status}", entry.name);
+            match status {
+                Status::Ok => {}
+                Status::Failed => failed += 1,
+                Status::Unreadable(e) => {
+                    eprintln!("Ошибка: {}: {e}", entry.name);
+                    unreadable += 1;
+                }
+            }
+        }
+    }
+
+    if unreadable > 0 {
+        eprintln!("Внимание: не удалось прочитать файлов: {unreadable}");
+    }
+    if failed > 0 {
+        eprintln!("Внимание: не совпало контрольных сумм: {failed}");
+    }
+
+    if failed + unreadable == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Вывести корень хэш-дерева каталога. Если задан `compare`, каталог сравнивается
+/// со снимком (с его алгоритмом и настройками) и выводятся изменения: A -- файл добавлен,
+/// D -- удалён, M -- изменён. Если задан `save`, в него записывается новый снимок.
+pub fn tree(
+    dir: &Path,
+    algo: HashAlgorythm,
+    metadata: bool,
+    encoding: HashPreviewOption,
+    save: Option<&Path>,
+    compare: Option<&Path>,
+) -> ExitCode {
+    let baseline = match compare.map(read_baseline).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("Ошибка: {}: {e}", compare.unwrap().display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let (algo, metadata) = match &baseline {
+        Some(baseline) => (baseline.algo, baseline.metadata),
+        None => (algo, metadata),
+    };
+
+    let current = match tree::hash_tree(dir, algo, metadata) {
+        Ok(tree) => tree,
+        Err(e) => {
+            eprintln!("Ошибка: {}: {e}", dir.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let name = format!("{}/", dir.display());
+    println!(
+        "{}",
+        manifest::format_line(&encoding.encode(&current.root()), &name)
+    );
+
+    if let Some(save) = save {
+        if let Err(e) = fs::write(save, current.to_baseline()) {
+            eprintln!("Ошибка: {}: {e}", save.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let Some(baseline) = baseline else {
+        return ExitCode::SUCCESS;
+    };
+    let diff = baseline.diff(&current);
+    for (mark, paths) in [
+        ("A", &diff.added),
+        ("D", &diff.removed),
+        ("M", &diff.modified),
+    ] {
+        for path in paths {
+            println!("{mark} {path}");
+        }
+    }
+
+    // Как у diff: код ошибки, если есть отличия
+    if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!(
+            "Добавлено: {}, удалено: {}, изменено: {}",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.modified.len()
+        );
+        ExitCode::FAILURE
+    }
+}
+
+fn read_baseline(path: &Path) -> Result<Tree, ManifestError> {
+    Tree::from_baseline(&fs::read_to_string(path)?)
+}
+
+/// Параметры стоимости хэширования паролей. Не заданные берутся из рекомендованных.
+#[derive(clap::Args, Debug, Default)]
+pub struct CostOptions {
+    /// Память Argon2id в КиБ
+    #[arg(long)]
+    pub memory_kib: Option<u32>,
+
+    /// Число проходов Argon2id
+    #[arg(long)]
+    pub iterations: Option<u32>,
+
+    /// Параллельность: число дорожек Argon2id или параметр p у scrypt
+    #[arg(long)]
+    pub parallelism: Option<u32>,
+
+    /// Логарифм N у scrypt
+    #[arg(long)]
+    pub log_n: Option<u8>,
+
+    /// Размер блока r у scrypt
+    #[arg(long)]
+    pub block_size: Option<u32>,
+
+    /// Стоимость bcrypt, от 4 до 31
+    #[arg(long)]
+    pub cost: Option<u32>,
+}
+
+impl CostOptions {
+    /// Рекомендованные параметры алгоритма с заменёнными значениями из опций.
+    /// Опции, которых у алгоритма нет, -- ошибка, а не молчаливое игнорирование.
+    pub fn params(&self, algo: PasswordAlgorithm) -> Result<Params, String> {
+        let supported: &[&str] = match algo {
+            PasswordAlgorithm::Argon2id => &["--memory-kib", "--iterations", "--parallelism"],
+            PasswordAlgorithm::Scrypt => &["--log-n", "--block-size", "--parallelism"],
+            PasswordAlgorithm::Bcrypt => &["--cost"],
+        };
+        let given = [
+            ("--memory-kib", self.memory_kib.is_some()),
+            ("--iterations", self.iterations.is_some()),
+            ("--parallelism", self.parallelism.is_some()),
+            ("--log-n", self.log_n.is_some()),
+            ("--block-size", self.block_size.is_some()),
+            ("--cost", self.cost.is_some()),
+        ];
+        if let Some((option, _)) = given
+            .iter()
+            .find(|(option, set)| *set && !supported.contains(option))
+        {
+            return Err(format!("у {algo} нет параметра {option}"));
+        }
+
+        Ok(match Params::recommended(algo) {
+            Params::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => Params::Argon2id {
+                memory_kib: self.memory_kib.unwrap_or(memory_kib),
+                iterations: self.iterations.unwrap_or(iterations),
+                parallelism: self.parallelism.unwrap_or(parallelism),
+            },
+            Params::Scrypt { log_n, r, p } => Params::Scrypt {
+                log_n: self.log_n.unwrap_or(log_n),
+                r: self.block_size.unwrap_or(r),
+                p: self.parallelism.unwrap_or(p),
+            },
+            Params::Bcrypt { cost } => Params::Bcrypt {
+                cost: self.cost.unwrap_or(cost),
+            },
+        })
+    }
+}
+
+/// Прочитать пароль из первой строки стандартного ввода. Из аргументов его не берём,
+/// чтобы он не попал в историю командной строки и в список процессов.
+fn read_password() -> io::Result<String> {
+    if io::stdin().is_terminal() {
+        eprint!("Пароль: ");
+    }
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let line = line.strip_suffix('\n').unwrap_or(&line);
+    Ok(line.strip_suffix('\r').unwrap_or(line).to_string())
+}
+
+/// Захэшировать пароль со стандартного ввода и вывести строку в формате PHC
+pub fn hash_password(algo: PasswordAlgorithm, cost: &CostOptions, salt: Option<&Key>) -> ExitCode {
+    let result = (|| {
+        let params = cost.params(algo)?;
+        let salt = salt
+            .map(|salt| salt.bytes().map_err(|e| format!("соль: {e}")))
+            .transpose()?;
+        let password = read_password().map_err(|e| e.to_string())?;
+        password::hash_password(password.as_bytes(), params, salt.as_deref())
+            .map_err(|e| e.to_string())
+    })();
+
+    match result {
+        Ok(hash) => {
+            println!("{hash}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Ошибка: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Проверить пароль со стандартного ввода по строке хэша. Код возврата -- результат проверки.
+pub fn verify_password(hash: &str) -> ExitCode {
+    let result = read_password()
+        .map_err(|e| e.to_string())
+        .and_then(|password| {
+            password::verify_password(password.as_bytes(), hash).map_err(|e| e.to_string())
+        });
+
+    match result {
+        Ok(true) => {
+            println!("OK");
+            ExitCode::SUCCESS
+        }
+        Ok(false) => {
+            println!("FAILED");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("Ошибка: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Подобрать параметры под время одного хэша на этой машине
+pub fn tune_password(algo: PasswordAlgorithm, target: Duration) -> ExitCode {
+    match password::tune(algo, target) {
+        Ok(tuning) => {
+            println!(
+                "{algo} {}  ({} мс)",
+                tuning.params,
+                tuning.elapsed.as_millis()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Ошибка: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use serde_json::json;
+
+use crate::hasher::Hasher;
+
+/// Строки таблицы сравнения, которые есть что показать: название функции
+/// (с ключом -- HMAC-SHA-256, keyed BLAKE3) и хэш. Строки с ошибками пропускаются.
+fn rows(hasher: &Hasher) -> Vec<(String, String)> {
+    hasher
+        .comparison()
+        .iter()
+        .filter_map(|row| {
+            let value = row.value.as_ref().ok()?;
+            Some((
+                hasher.function_name_for(row.algo),
+                hasher.encode(row.algo, value).ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Таблица сравнения в JSON, чтобы её можно было обработать скриптом.
+/// Если хэшировался текст, а не файл, `source` равен null. `normalization` --
+/// шаги нормализации текста, без которых хэш не повторить.
+pub fn to_json(hasher: &Hasher) -> String {
+    let digests: Vec<_> = rows(hasher)
+        .into_iter()
+        .map(|(algo, digest)| json!({ "algorithm": algo, "digest": digest }))
+        .collect();
+
+    let document = json!({
+        "source": hasher.source_name(),
+        "encoding": hasher.option().name(),
+        "normalization": hasher
+            .applied_normalization()
+            .map(|normalization| normalization.steps())
+            .unwrap_or_default(),
+        "digests": digests,
+    });
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Таблица сравнения в Markdown, чтобы вставить её в описание релиза
+pub fn to_release_notes(hasher: &Hasher) -> String {
+    let mut notes = match hasher.source_name() {
+        Some(name) => format!("### Контрольные суммы `{name}`\n\n"),
+        None => "### Контрольные суммы\n\n".to_string(),
+    };
+    if let Some(normalization) = hasher.applied_normalization() {
+        notes.push_str(&format!(
+            "Текст перед хэшированием нормализован: {}.\n\n",
+            normalization.steps().join(", ")
+        ));
+    }
+    notes.push_str("| Алгоритм | Хэш |\n|---|---|\n");
+    for (algo, digest) in rows(hasher) {
+        notes.push_str(&format!("| {algo} | `{digest}` |\n"));
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorythm::HashAlgorythm;
+    use crate::hasher::{Change, HashPreviewOption, TextEncoding};
+    use crate::key::Key;
+
+    const FOX: &str = "The quick brown fox jumps over the lazy dog";
+    const SHA256: &str = "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592";
+    const HMAC_SHA256: &str = "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8";
+
+    fn hasher(key: Option<&str>) -> Hasher {
+        let mut hasher = Hasher::new(HashAlgorythm::Sha3_256, HashPreviewOption::Hex);
+        hasher.apply(Change::Compare(vec![HashAlgorythm::Sha256]));
+        hasher.apply(Change::Key(
+            key.map(|key| Key::new(key, TextEncoding::Utf8)),
+        ));
+        hasher.apply(Change::Text(FOX.to_string()));
+        hasher
+    }
+
+    #[test]
+    fn unkeyed_rows_use_algorithm_names() {
+        let hasher = hasher(None);
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&hasher)).unwrap();
+        assert_eq!(json["source"], serde_json::Value::Null);
+        assert_eq!(json["encoding"], "hex");
+        assert_eq!(
+            json["digests"],
+            json!([{ "algorithm": "SHA-256", "digest": SHA256 }])
+        );
+
+        let notes = to_release_notes(&hasher);
+        assert!(
+            notes.contains(&format!("| SHA-256 | `{SHA256}` |\n")),
+            "{notes}"
+        );
+    }
+
+    #[test]
+    fn keyed_rows_use_keyed_names() {
+        let hasher = hasher(Some("key"));
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&hasher)).unwrap();
+        assert_eq!(
+            json["digests"],
+            json!([{ "algorithm": "HMAC-SHA-256", "digest": HMAC_SHA256 }])
+        );
+
+        let notes = to_release_notes(&hasher);
+        assert!(
+            notes.contains(&format!("| HMAC-SHA-256 | `{HMAC_SHA256}` |\n")),
+            "{notes}"
+        );
+        assert!(!notes.contains("| SHA-256 |"), "{notes}");
+    }
+}
@@ -0,0 +1,1578 @@
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use gtk::pango::{EllipsizeMode, WrapMode};
+use gtk::ToggleButton;
+use gtk::{gdk, gio, glib, Application, ApplicationWindow, CheckButton, DropDown, Label};
+use gtk::{prelude::*, Entry};
+use gtk::{
+    Button, DrawingArea, DropTarget, Expander, FileChooserAction, FileChooserDialog, FlowBox,
+    Orientation,
+};
+use gtk::{Grid, ListBox, PasswordEntry, ProgressBar, ResponseType, ScrolledWindow, SpinButton};
+use gtk4 as gtk;
+use qrcodegen::QrCode;
+
+use l4::algorythm::HashAlgorythm;
+use l4::bench::{self, Throughput};
+use l4::export;
+use l4::hasher::{
+    Change, HashError, HashPreviewOption, Hasher, Source, TextEncoding, Verification,
+};
+use l4::history::{History, Privacy};
+use l4::key::Key;
+use l4::manifest::{self, Entry as ManifestEntry, Status};
+use l4::normalize::{Normalization, UnicodeForm};
+use l4::password::{self, Params, PasswordAlgorithm, PasswordError, Tuning};
+use l4::qr::{self, ErrorCorrection};
+use l4::state::State;
+use l4::stream::{format_size, Job, Progress};
+
+/// Запустить графический интерфейс
+pub fn run() -> ExitCode {
+    let app = Application::builder()
+        .application_id("org.hasher.myhashapp")
+        .build();
+
+    app.connect_activate(ui);
+    if app.run() == glib::ExitCode::SUCCESS {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Элементы интерфейса, которые показывают результат и ход хэширования файла
+#[derive(Clone)]
+struct Widgets {
+    source_label: Label,
+    progress_bar: ProgressBar,
+    cancel_button: Button,
+    hash_title_label: Label,
+    hash_value_label: Label,
+    expected_entry: Entry,
+    verification_label: Label,
+    qr_area: DrawingArea,
+    qr_level: Rc<Cell<ErrorCorrection>>,
+    /// Код, который сейчас нарисован; его же сохраняют в PNG и SVG
+    qr: Rc<RefCell<Option<QrCode>>>,
+    qr_message_label: Label,
+    comparison_grid: Grid,
+}
+
+impl Widgets {
+    /// Показать или спрятать прогресс хэширования
+    fn set_busy(&self, busy: bool) {
+        self.progress_bar.set_visible(busy);
+        self.cancel_button.set_visible(busy);
+    }
+
+    /// Показать, что считается, и результат (или почему его нет)
+    fn show(&self, hasher: &Hasher) {
+        self.source_label.set_label(&match hasher.source() {
+            Source::Text => "Источник: текст (файл можно перетащить в окно)".to_string(),
+            Source::File(path) => {
+                let name = hasher.source_name().unwrap_or_default();
+                match path.metadata() {
+                    Ok(meta) => format!("Файл: {name} ({})", format_size(meta.len())),
+                    Err(_) => format!("Файл: {name}"),
+                }
+            }
+        });
+        // Нормализация меняет хэш, поэтому без неё результат не повторить
+        self.hash_title_label
+            .set_label(&match hasher.applied_normalization() {
+                Some(normalization) => format!(
+                    "Значение {} ({}):",
+                    hasher.function_name(),
+                    normalization.steps().join(", ")
+                ),
+                None => format!("Значение {}:", hasher.function_name()),
+            });
+        let view = match hasher.error() {
+            Some(e) => Err(e.clone()),
+            None => hasher.view(),
+        };
+        match view {
+            Ok(view) => {
+                self.hash_value_label.set_label(&view);
+                self.show_qr(&view);
+            }
+            Err(e) => {
+                self.hash_value_label.set_label(&format!("Ошибка: {e}"));
+                self.show_qr("");
+            }
+        }
+
+        // Поле с ожидаемым хэшем подсвечивается зелёным или красным (классы темы Adwaita)
+        self.expected_entry.remove_css_class("success");
+        self.expected_entry.remove_css_class("error");
+        let (class, text) = match hasher.verification() {
+            None => (None, ""),
+            Some(Verification::Match) => (Some("success"), "Хэш совпадает"),
+            Some(Verification::Mismatch) => (Some("error"), "Хэш не совпадает"),
+            Some(Verification::Invalid) => (Some("error"), "Ожидаемый хэш записан непонятно"),
+        };
+        if let Some(class) = class {
+            self.expected_entry.add_css_class(class);
+        }
+        self.verification_label.set_label(text);
+
+        self.show_comparison(hasher);
+    }
+
+    /// Перестроить таблицу сравнения: алгоритм, хэш и кнопка копирования в каждой строке
+    fn show_comparison(&self, hasher: &Hasher) {
+        while let Some(child) = self.comparison_grid.first_child() {
+            self.comparison_grid.remove(&child);
+        }
+
+        for (row, comparison) in hasher.comparison().iter().enumerate() {
+            let row = row as i32;
+            let value = comparison
+                .value
+                .clone()
+                .map_err(HashError::from)
+                .and_then(|value| hasher.encode(comparison.algo, &value));
+            let copy_button = Button::with_label("Копировать");
+            copy_button.set_sensitive(value.is_ok());
+            let value = value.unwrap_or_else(|e| format!("Ошибка: {e}"));
+            let name_label = Label::builder()
+                .label(hasher.function_name_for(comparison.algo))
+                .xalign(0.0)
+                .build();
+            let value_label = Label::builder()
+                .label(&value)
+                .xalign(0.0)
+                .hexpand(true)
+                .wrap(true)
+                .wrap_mode(WrapMode::Char)
+                .selectable(true)
+                .build();
+            copy_button.connect_clicked(move |button| button.clipboard().set_text(&value));
+
+            self.comparison_grid.attach(&name_label, 0, row, 1, 1);
+            self.comparison_grid.attach(&value_label, 1, row, 1, 1);
+            self.comparison_grid.attach(&copy_button, 2, row, 1, 1);
+        }
+    }
+
+    /// Перестроить QR-код для текста (пустой текст -- кода нет) и перерисовать его
+    fn show_qr(&self, text: &str) {
+        let qr = qr::encode(text, self.qr_level.get());
+        if qr.is_none() && !text.is_empty() {
+            self.qr_message_label
+                .set_label("Хэш не помещается в QR-код с таким уровнем коррекции");
+        } else {
+            self.qr_message_label.set_label("");
+        }
+        *self.qr.borrow_mut() = qr;
+        self.qr_area.queue_draw();
+    }
+}
+
+/// Фоновое хэширование файла: задача, если она идёт, и ревизия модели,
+/// для которой её запускали последний раз
+#[derive(Default)]
+struct FileHashing {
+    job: Option<Job>,
+    revision: Option<u64>,
+}
+
+type CurrentJob = Rc<RefCell<FileHashing>>;
+
+fn ui(app: &Application) {
+    // Соответствующие GUI элементы
+    let input_text_entry = Entry::builder().hexpand(true).build();
+    let input_encoding_drop_down = DropDown::from_strings(&TextEncoding::names());
+    let strip_bom_check_box = CheckButton::with_label("Убрать BOM");
+    let crlf_check_box = CheckButton::with_label("CRLF → LF");
+    let trim_check_box = CheckButton::with_label("Пробелы в конце строк");
+    let unicode_form_drop_down = DropDown::from_strings(&UnicodeForm::names());
+    let open_file_button = Button::with_label("Открыть файл…");
+    let check_sums_button = Button::with_label("Проверить файл SUMS…");
+    let benchmark_button = Button::with_label("Скорость…");
+    let passwords_button = Button::builder()
+        .label("Пароли…")
+        .tooltip_text("Пароли нельзя хранить как SHA3 или другой быстрый хэш")
+        .build();
+    let source_label = Label::builder()
+        .label("Источник: текст (файл можно перетащить в окно)")
+        .hexpand(true)
+        .xalign(0.0)
+        .build();
+    let progress_bar = ProgressBar::builder()
+        .show_text(true)
+        .visible(false)
+        .build();
+    let cancel_button = Button::builder().label("Отмена").visible(false).build();
+    let hash_algo_drop_down = DropDown::from_strings(&HashAlgorythm::names());
+    let output_len_spin_button = SpinButton::with_range(1.0, 1024.0, 1.0);
+    let hash_option_drop_down = DropDown::from_strings(&HashPreviewOption::names());
+    let key_check_box = CheckButton::with_label("Секретный ключ (HMAC)");
+    let key_entry = PasswordEntry::builder()
+        .show_peek_icon(true)
+        .hexpand(true)
+        .sensitive(false)
+        .build();
+    let key_encoding_drop_down = DropDown::from_strings(&TextEncoding::names());
+    key_encoding_drop_down.set_sensitive(false);
+    let hash_title_label = Label::new(Some("Значение хэша:"));
+    let hash_value_label = Label::builder()
+        .wrap(true)
+        .wrap_mode(WrapMode::Char)
+        .selectable(true)
+        .width_chars(64)
+        .max_width_chars(64)
+        .build();
+    let expected_entry = Entry::builder()
+        .placeholder_text("Ожидаемый хэш (hex или base64) для проверки")
+        .build();
+    let verification_label = Label::new(None);
+    let compare_check_boxes: Vec<CheckButton> = HashAlgorythm::ALL
+        .iter()
+        .map(|algo| CheckButton::with_label(algo.name()))
+        .collect();
+    let comparison_grid = Grid::builder().row_spacing(6).column_spacing(12).build();
+    let save_json_button = Button::with_label("Сохранить JSON…");
+    let save_notes_button = Button::with_label("Сохранить для release notes…");
+    let comparison_message_label = Label::builder().xalign(0.0).hexpand(true).build();
+    let history_list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .build();
+    let privacy_drop_down = DropDown::from_strings(&Privacy::names());
+    let clear_history_button = Button::with_label("Очистить");
+    let history_message_label = Label::builder().xalign(0.0).hexpand(true).build();
+
+    // Сравнение: отмеченные алгоритмы считаются за один проход вместе с основным
+    let compare_flow_box = FlowBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .max_children_per_line(7)
+        .build();
+    for check_box in &compare_check_boxes {
+        compare_flow_box.insert(check_box, -1);
+    }
+    let export_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    export_row.append(&comparison_message_label);
+    export_row.append(&save_json_button);
+    export_row.append(&save_notes_button);
+    let comparison_box = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(6)
+        .build();
+    comparison_box.append(&compare_flow_box);
+    comparison_box.append(&comparison_grid);
+    comparison_box.append(&export_row);
+    let comparison_expander = Expander::builder()
+        .label("Сравнить несколько алгоритмов")
+        .child(&comparison_box)
+        .build();
+    // История: настройки сверху, под ними список с прокруткой
+    let history_settings = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    history_settings.append(&Label::new(Some("Введённый текст:")));
+    history_settings.append(&privacy_drop_down);
+    history_settings.append(&history_message_label);
+    history_settings.append(&clear_history_button);
+    let history_box = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(6)
+        .build();
+    history_box.append(&history_settings);
+    history_box.append(
+        &ScrolledWindow::builder()
+            .child(&history_list)
+            .min_content_height(240)
+            .build(),
+    );
+    let history_expander = Expander::builder()
+        .label("История")
+        .child(&history_box)
+        .build();
+    let qr_area = DrawingArea::builder()
+        .content_width(240)
+        .content_height(240)
+        .build();
+    let qr_level_drop_down = DropDown::from_strings(&ErrorCorrection::names());
+    let save_png_button = Button::with_label("Сохранить PNG…");
+    let save_svg_button = Button::with_label("Сохранить SVG…");
+    let qr_message_label = Label::builder().wrap(true).max_width_chars(24).build();
+
+    // QR-код хэша, чтобы сверить его с телефона, и настройки рядом с ним
+    let qr_settings = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(6)
+        .valign(gtk::Align::Center)
+        .build();
+    qr_settings.append(&Label::new(Some("Коррекция ошибок:")));
+    qr_settings.append(&qr_level_drop_down);
+    qr_settings.append(&save_png_button);
+    qr_settings.append(&save_svg_button);
+    qr_settings.append(&qr_message_label);
+    let qr_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .halign(gtk::Align::Center)
+        .build();
+    qr_row.append(&qr_area);
+    qr_row.append(&qr_settings);
+
+    // Строка с выбором файла и строка с прогрессом
+    let file_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    file_row.append(&source_label);
+    file_row.append(&open_file_button);
+    file_row.append(&check_sums_button);
+    let progress_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    progress_bar.set_hexpand(true);
+    progress_bar.set_valign(gtk::Align::Center);
+    progress_row.append(&progress_bar);
+    progress_row.append(&cancel_button);
+
+    // Длина хэша в байтах, меняется только у SHAKE
+    let algo_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    hash_algo_drop_down.set_hexpand(true);
+    algo_row.append(&hash_algo_drop_down);
+    algo_row.append(&Label::new(Some("Длина, байт:")));
+    algo_row.append(&output_len_spin_button);
+    algo_row.append(&benchmark_button);
+    algo_row.append(&passwords_button);
+
+    // Ключ и его кодировка
+    let key_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    key_row.append(&key_check_box);
+    key_row.append(&key_entry);
+    key_row.append(&key_encoding_drop_down);
+
+    // Текст можно хэшировать как есть или как байты, записанные в hex или base64
+    let input_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    input_row.append(&input_text_entry);
+    input_row.append(&input_encoding_drop_down);
+
+    // Нормализация текста, чтобы один документ из Windows и Linux давал один хэш
+    let normalization_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    normalization_row.append(&Label::new(Some("Нормализация:")));
+    normalization_row.append(&strip_bom_check_box);
+    normalization_row.append(&crlf_check_box);
+    normalization_row.append(&trim_check_box);
+    normalization_row.append(&unicode_form_drop_down);
+
+    // Как показать хэш
+    let option_row = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    option_row.append(&Label::new(Some("Вид хэша:")));
+    option_row.append(&hash_option_drop_down);
+
+    // Контейнер для всех элементов
+    let container = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .spacing(12)
+        .build();
+    container.append(&input_row);
+    container.append(&normalization_row);
+    container.append(&file_row);
+    container.append(&progress_row);
+    container.append(&algo_row);
+    container.append(&key_row);
+    container.append(&option_row);
+    container.append(&hash_title_label);
+    container.append(&hash_value_label);
+    container.append(&expected_entry);
+    container.append(&verification_label);
+    container.append(&qr_row);
+    container.append(&comparison_expander);
+    container.append(&history_expander);
+
+    // Модель живёт в State: обработчики сигналов только сообщают ей об изменениях,
+    // а интерфейс обновляется подписчиками в одном месте.
+    let state = Rc::new(State::new(Hasher::new(
+        HashAlgorythm::Sha3_256,
+        HashPreviewOption::Hex,
+    )));
+    let job: CurrentJob = Rc::new(RefCell::new(FileHashing::default()));
+
+    // Список показывает тот же алгоритм, что выбран в модели
+    hash_algo_drop_down.set_selected(state.hasher().algo().index() as u32);
+    let widgets = Widgets {
+        source_label,
+        progress_bar,
+        cancel_button: cancel_button.clone(),
+        hash_title_label,
+        hash_value_label,
+        expected_entry: expected_entry.clone(),
+        verification_label,
+        qr_area: qr_area.clone(),
+        qr_level: Rc::new(Cell::new(ErrorCorrection::Medium)),
+        qr: Rc::new(RefCell::new(None)),
+        qr_message_label,
+        comparison_grid,
+    };
+    qr_level_drop_down.set_selected(widgets.qr_level.get() as u32);
+
+    // Подписчики: результат, длина хэша и фоновое хэширование файла
+    state.subscribe(glib::clone!(@strong widgets => move |hasher| widgets.show(hasher)));
+    state.subscribe(glib::clone!(@weak output_len_spin_button => move |hasher| {
+        output_len_spin_button.set_sensitive(hasher.algo().is_xof());
+        output_len_spin_button.set_value(hasher.output_len() as f64);
+    }));
+    state.subscribe(
+        glib::clone!(@weak state, @strong job, @strong widgets => move |hasher| {
+            update_file_job(&state, hasher, &job, &widgets);
+        }),
+    );
+
+    // История хранится в каталоге данных пользователя (~/.local/share на Linux)
+    let history = HistoryPanel::new(
+        glib::user_data_dir().join("l4").join("history.json"),
+        history_list,
+        history_message_label,
+    );
+    privacy_drop_down.set_selected(history.history.borrow().privacy as u32);
+    state.subscribe(glib::clone!(@weak state, @strong history => move |_| {
+        history.schedule(&state);
+    }));
+    privacy_drop_down.connect_selected_notify(glib::clone!(@strong history => move |drop_down| {
+        let Some(&privacy) = Privacy::ALL.get(drop_down.selected() as usize) else {
+            return;
+        };
+        history.change(|history| history.privacy = privacy);
+    }));
+    clear_history_button.connect_clicked(glib::clone!(@strong history => move |_| {
+        history.change(History::clear);
+    }));
+
+    // Подписываемся на событие изменения текстового ввода
+    input_text_entry.connect_changed(
+        // Это капец. GTK это ООП-библиотека на чистом C, что уже звучит страшно.
+        // А Rust язык с очень строгой типизацией и статической сборкой мусора (без сборщика мусора).
+        // Из-за этого для "удобства" вводится такой макрос, который клонирует объекты, которые можно
+        // изменять в нескольких местах (грубо говоря).
+        // Я не думаю, что я в ближайшее время буду пробовать GTK ещё раз, в общем.
+        glib::clone!(@strong state => move |entry| {
+            // Ввод текста переключает источник обратно на текст
+            state.apply(Change::Text(entry.text().to_string()));
+        }),
+    );
+
+    // Любой переключатель нормализации собирает настройки целиком и отдаёт их модели
+    let update_normalization = Rc::new(glib::clone!(
+        @weak strip_bom_check_box, @weak crlf_check_box, @weak trim_check_box,
+        @weak unicode_form_drop_down, @strong state => move || {
+            state.apply(Change::Normalization(Normalization {
+                strip_bom: strip_bom_check_box.is_active(),
+                crlf_to_lf: crlf_check_box.is_active(),
+                trim_trailing: trim_check_box.is_active(),
+                unicode: UnicodeForm::ALL
+                    .get(unicode_form_drop_down.selected() as usize)
+                    .copied()
+                    .unwrap_or_default(),
+            }));
+        }
+    ));
+    for check_box in [&strip_bom_check_box, &crlf_check_box, &trim_check_box] {
+        check_box.connect_toggled(glib::clone!(@strong update_normalization => move |_| {
+            update_normalization();
+        }));
+    }
+    unicode_form_drop_down.connect_selected_notify(
+        glib::clone!(@strong update_normalization => move |_| update_normalization()),
+    );
+
+    // Подписываемся на нажатие кнопки выбора файла
+    open_file_button.connect_clicked(glib::clone!(@strong state => move |button| {
+        let window = button.root().and_downcast::<gtk::Window>();
+        let dialog = FileChooserDialog::new(
+            Some("Выберите файл"),
+            window.as_ref(),
+            FileChooserAction::Open,
+            &[("Отмена", ResponseType::Cancel), ("Открыть", ResponseType::Accept)],
+        );
+        dialog.set_modal(true);
+
+        dialog.connect_response(glib::clone!(@strong state => move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    state.apply(Change::File(path));
+                }
+            }
+            dialog.close();
+        }));
+        dialog.present();
+    }));
+
+    // Файл можно перетащить в окно из файлового менеджера
+    let drop_target = DropTarget::new(gio::File::static_type(), gdk::DragAction::COPY);
+    drop_target.connect_drop(glib::clone!(@strong state => move |_, value, _, _| {
+        let Some(path) = value.get::<gio::File>().ok().and_then(|file| file.path()) else {
+            return false;
+        };
+        state.apply(Change::File(path));
+        true
+    }));
+    container.add_controller(drop_target);
+
+    // Код рисуется квадратами по модулям, по центру области и с целым размером модуля,
+    // чтобы между соседними модулями не было просветов
+    qr_area.set_draw_func(
+        glib::clone!(@strong widgets => move |_, cr, width, height| {
+            let Some(qr) = widgets.qr.borrow().clone() else {
+                return;
+            };
+            let size = qr::full_size(&qr);
+            let scale = (width.min(height) / size).max(1);
+            let (left, top) = ((width - size * scale) / 2, (height - size * scale) / 2);
+
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            cr.rectangle(left as f64, top as f64, (size * scale) as f64, (size * scale) as f64);
+            let _ = cr.fill();
+
+            cr.set_source_rgb(0.0, 0.0, 0.0);
+            for y in 0..size {
+                for x in 0..size {
+                    if qr::is_dark(&qr, x, y) {
+                        cr.rectangle(
+                            (left + x * scale) as f64,
+                            (top + y * scale) as f64,
+                            scale as f64,
+                            scale as f64,
+                        );
+                    }
+                }
+            }
+            let _ = cr.fill();
+        }),
+    );
+
+    // Подписываемся на выбор уровня коррекции ошибок
+    qr_level_drop_down.connect_selected_notify(
+        glib::clone!(@strong state, @strong widgets => move |drop_down| {
+            let Some(&level) = ErrorCorrection::ALL.get(drop_down.selected() as usize) else {
+                return;
+            };
+            widgets.qr_level.set(level);
+            widgets.show(&state.hasher());
+        }),
+    );
+
+    save_png_button.connect_clicked(glib::clone!(@strong widgets => move |button| {
+        save_qr(button, &widgets, "hash.png");
+    }));
+    save_svg_button.connect_clicked(glib::clone!(@strong widgets => move |button| {
+        save_qr(button, &widgets, "hash.svg");
+    }));
+
+    // Список сравниваемых алгоритмов собирается заново при любом изменении отметок
+    let compare_check_boxes = Rc::new(compare_check_boxes);
+    for check_box in compare_check_boxes.iter() {
+        check_box.connect_toggled(
+            glib::clone!(@strong state, @strong compare_check_boxes => move |_| {
+                let compare = HashAlgorythm::ALL
+                    .into_iter()
+                    .zip(compare_check_boxes.iter())
+                    .filter(|(_, check_box)| check_box.is_active())
+                    .map(|(algo, _)| algo)
+                    .collect();
+                state.apply(Change::Compare(compare));
+            }),
+        );
+    }
+
+    // Таблицу сравнения можно сохранить для скриптов (JSON) или для описания релиза (Markdown)
+    save_json_button.connect_clicked(
+        glib::clone!(@strong state, @weak comparison_message_label => move |button| {
+            save_file(button, "hash.json", &comparison_message_label, glib::clone!(@strong state => move |path| {
+                let hasher = state.hasher();
+                let json = export::to_json(&hasher);
+                fs::write(path, json).map_err(|e| e.to_string())
+            }));
+        }),
+    );
+    save_notes_button.connect_clicked(
+        glib::clone!(@strong state, @weak comparison_message_label => move |button| {
+            save_file(button, "checksums.md", &comparison_message_label, glib::clone!(@strong state => move |path| {
+                let hasher = state.hasher();
+                let notes = export::to_release_notes(&hasher);
+                fs::write(path, notes).map_err(|e| e.to_string())
+            }));
+        }),
+    );
+
+    // Ожидаемый хэш сравнивается с уже посчитанным, пересчитывать ничего не нужно
+    expected_entry.connect_changed(glib::clone!(@strong state => move |entry| {
+        state.apply(Change::Expected(entry.text().to_string()));
+    }));
+
+    // Подписываемся на нажатие кнопки проверки файлов по манифесту
+    check_sums_button.connect_clicked(glib::clone!(@strong state, @strong widgets => move |button| {
+        let window = button.root().and_downcast::<gtk::Window>();
+        let dialog = FileChooserDialog::new(
+            Some("Выберите файл с контрольными суммами"),
+            window.as_ref(),
+            FileChooserAction::Open,
+            &[("Отмена", ResponseType::Cancel), ("Открыть", ResponseType::Accept)],
+        );
+        dialog.set_modal(true);
+
+        dialog.connect_response(glib::clone!(@strong state, @strong widgets => move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    // Если алгоритм не понять по манифесту, берётся выбранный в окне
+                    let algo = state.hasher().algo();
+                    match manifest::load(&path, algo) {
+                        Ok(entries) => show_manifest(dialog.transient_for().as_ref(), entries),
+                        Err(e) => widgets
+                            .verification_label
+                            .set_label(&format!("Ошибка: {}: {e}", path.display())),
+                    }
+                }
+            }
+            dialog.close();
+        }));
+        dialog.present();
+    }));
+
+    // Подписываемся на нажатие кнопки замера скорости алгоритмов
+    benchmark_button.connect_clicked(|button| {
+        show_benchmark(button.root().and_downcast::<gtk::Window>().as_ref());
+    });
+
+    // Подписываемся на нажатие кнопки хэширования паролей
+    passwords_button.connect_clicked(|button| {
+        show_passwords(button.root().and_downcast::<gtk::Window>().as_ref());
+    });
+
+    // Подписываемся на нажатие кнопки отмены хэширования файла
+    cancel_button.connect_clicked(glib::clone!(@strong job, @strong widgets => move |_| {
+        if let Some(job) = job.borrow_mut().job.take() {
+            job.cancel();
+        }
+        widgets.set_busy(false);
+        widgets.hash_value_label.set_label("Хэширование отменено");
+    }));
+
+    // Подписываемся на событие выбора варианта из выпадающего списка
+    hash_algo_drop_down.connect_selected_notify(glib::clone!(@strong state => move |drop_down| {
+        // Выбираем алгоритм по номеру варианта в реестре. Если ничего не выбрано,
+        // номер будет GTK_INVALID_LIST_POSITION, и алгоритм не меняется.
+        if let Some(&algo) = HashAlgorythm::ALL.get(drop_down.selected() as usize) {
+            state.apply(Change::Algo(algo));
+        }
+    }));
+
+    // Подписываемся на изменение длины хэша
+    output_len_spin_button.connect_value_changed(
+        glib::clone!(@strong state => move |spin_button| {
+            state.apply(Change::OutputLen(spin_button.value_as_int() as usize));
+        }),
+    );
+
+    // Ключ обновляется при любом изменении: включении режима, тексте ключа или его кодировке
+    let update_key = Rc::new(glib::clone!(@strong state,
+        @weak key_check_box, @weak key_entry, @weak key_encoding_drop_down => move || {
+        let active = key_check_box.is_active();
+        key_entry.set_sensitive(active);
+        key_encoding_drop_down.set_sensitive(active);
+
+        let encoding = TextEncoding::ALL
+            .get(key_encoding_drop_down.selected() as usize)
+            .copied()
+            .unwrap_or(TextEncoding::Utf8);
+
+        state.apply(Change::Key(active.then(|| Key::new(&key_entry.text(), encoding))));
+    }));
+    key_check_box.connect_toggled(glib::clone!(@strong update_key => move |_| update_key()));
+    key_entry.connect_changed(glib::clone!(@strong update_key => move |_| update_key()));
+    key_encoding_drop_down.connect_selected_notify(move |_| update_key());
+
+    // Подписываемся на выбор кодировки введённого текста
+    input_encoding_drop_down.connect_selected_notify(
+        glib::clone!(@strong state => move |drop_down| {
+            if let Some(&encoding) = TextEncoding::ALL.get(drop_down.selected() as usize) {
+                state.apply(Change::InputEncoding(encoding));
+            }
+        }),
+    );
+
+    // Подписываемся на событие выбора вида хэша
+    hash_option_drop_down.connect_selected_notify(glib::clone!(@strong state => move |drop_down| {
+        if let Some(&option) = HashPreviewOption::ALL.get(drop_down.selected() as usize) {
+            state.apply(Change::Option(option));
+        }
+    }));
+
+    // Запихиваем всё в окно и показываем его
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title("Hasher")
+        .child(&container)
+        .build();
+    window.present();
+}
+
+/// Панель истории хэшей и файл, в котором история хранится между запусками
+struct HistoryPanel {
+    history: RefCell<History>,
+    path: PathBuf,
+    list: ListBox,
+    message_label: Label,
+    /// Хэш записывается, только когда ввод перестал меняться,
+    /// а не на каждую нажатую клавишу. Здесь ждущий таймер.
+    pending: RefCell<Option<glib::SourceId>>,
+}
+
+/// Сколько ввод должен не меняться, чтобы хэш попал в историю
+const HISTORY_DELAY: Duration = Duration::from_secs(1);
+
+impl HistoryPanel {
+    fn new(path: PathBuf, list: ListBox, message_label: Label) -> Rc<Self> {
+        let history = History::load(&path).unwrap_or_else(|e| {
+            message_label.set_label(&format!("Ошибка: {}: {e}", path.display()));
+            History::default()
+        });
+        let panel = Rc::new(Self {
+            history: RefCell::new(history),
+            path,
+            list,
+            message_label,
+            pending: RefCell::new(None),
+        });
+        panel.refresh();
+        panel
+    }
+
+    /// Записать хэш модели, когда она перестанет меняться
+    fn schedule(self: &Rc<Self>, state: &Rc<State>) {
+        if let Some(pending) = self.pending.take() {
+            pending.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            HISTORY_DELAY,
+            glib::clone!(@weak self as panel, @weak state => move || {
+                panel.pending.take();
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|time| time.as_secs())
+                    .unwrap_or_default();
+                if panel.history.borrow_mut().record(&state.hasher(), timestamp) {
+                    panel.save();
+                    panel.refresh();
+                }
+            }),
+        );
+        self.pending.replace(Some(source));
+    }
+
+    /// Изменить историю, сохранить её и показать заново
+    fn change(self: &Rc<Self>, change: impl FnOnce(&mut History)) {
+        change(&mut self.history.borrow_mut());
+        self.save();
+        self.refresh();
+    }
+
+    fn save(&self) {
+        match self.history.borrow().save(&self.path) {
+            Ok(()) => self.message_label.set_label(""),
+            Err(e) => self
+                .message_label
+                .set_label(&format!("Ошибка: {}: {e}", self.path.display())),
+        }
+    }
+
+    /// Построить список заново: записей немного, так проще, чем обновлять строки
+    fn refresh(self: &Rc<Self>) {
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+
+        for (index, entry) in self.history.borrow().entries().iter().enumerate() {
+            let time = glib::DateTime::from_unix_local(entry.timestamp as i64)
+                .and_then(|time| time.format("%d.%m.%Y %H:%M:%S"))
+                .map(|time| time.to_string())
+                .unwrap_or_default();
+            let input_label = Label::builder()
+                .label(&entry.input)
+                .xalign(0.0)
+                .ellipsize(EllipsizeMode::End)
+                .build();
+            let details_label = Label::builder()
+                .label(match entry.normalization.as_str() {
+                    "" => format!("{} · {} · {time}", entry.algorithm, entry.encoding.label()),
+                    normalization => format!(
+                        "{} · {normalization} · {} · {time}",
+                        entry.algorithm,
+                        entry.encoding.label()
+                    ),
+                })
+                .xalign(0.0)
+                .build();
+            details_label.add_css_class("dim-label");
+            let digest_label = Label::builder()
+                .label(&entry.digest)
+                .xalign(0.0)
+                .selectable(true)
+                .ellipsize(EllipsizeMode::Middle)
+                .build();
+            digest_label.add_css_class("monospace");
+
+            let copy_button = Button::builder()
+                .icon_name("edit-copy-symbolic")
+                .tooltip_text("Копировать хэш")
+                .valign(gtk::Align::Center)
+                .build();
+            let digest = entry.digest.clone();
+            copy_button.connect_clicked(move |button| button.clipboard().set_text(&digest));
+            let pin_button = ToggleButton::builder()
+                .icon_name("view-pin-symbolic")
+                .tooltip_text("Закрепить: запись не удалится при очистке")
+                .active(entry.pinned)
+                .valign(gtk::Align::Center)
+                .build();
+            pin_button.connect_toggled(glib::clone!(@weak self as panel => move |button| {
+                let pinned = button.is_active();
+                panel.change(|history| history.set_pinned(index, pinned));
+            }));
+            let remove_button = Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text("Удалить запись")
+                .valign(gtk::Align::Center)
+                .build();
+            remove_button.connect_clicked(glib::clone!(@weak self as panel => move |_| {
+                panel.change(|history| history.remove(index));
+            }));
+
+            let text_box = gtk::Box::builder()
+                .orientation(Orientation::Vertical)
+                .spacing(2)
+                .hexpand(true)
+                .build();
+            text_box.append(&input_label);
+            text_box.append(&details_label);
+            text_box.append(&digest_label);
+            let row = gtk::Box::builder()
+                .orientation(Orientation::Horizontal)
+                .spacing(6)
+                .margin_top(6)
+                .margin_bottom(6)
+                .build();
+            row.append(&text_box);
+            row.append(&copy_button);
+            row.append(&pin_button);
+            row.append(&remove_button);
+            self.list.append(&row);
+        }
+    }
+}
+
+/// Запустить или остановить фоновое хэширование файла так, чтобы оно соответствовало
+/// модели: файл хэшируется заново, только если с последнего запуска изменилась ревизия.
+fn update_file_job(state: &Rc<State>, hasher: &Hasher, job: &CurrentJob, widgets: &Widgets) {
+    let mut current = job.borrow_mut();
+
+    // Текст хэширует сама модель, а хэширование файла больше не нужно
+    let Source::File(path) = hasher.source() else {
+        if let Some(old) = current.job.take() {
+            old.cancel();
+        }
+        current.revision = None;
+        widgets.set_busy(false);
+        return;
+    };
+
+    let revision = hasher.revision();
+    if current.revision == Some(revision) {
+        return;
+    }
+    if let Some(old) = current.job.take() {
+        old.cancel();
+    }
+    current.revision = Some(revision);
+    widgets.set_busy(false);
+
+    // Ключ может не подойти алгоритму, тогда и читать файл незачем
+    let digests = match hasher.digests() {
+        Ok(digests) => digests,
+        Err(e) => return state.apply(Change::FileFailed(revision, e.into())),
+    };
+    let new_job = Job::spawn(path.clone(), digests);
+    let progress = new_job.progress.clone();
+    current.job = Some(new_job);
+
+    widgets.progress_bar.set_fraction(0.0);
+    widgets.set_busy(true);
+
+    // Поток не может трогать виджеты: GTK работает только в главном потоке.
+    // Поэтому главный поток сам несколько раз в секунду проверяет, как идут дела.
+    glib::timeout_add_local(
+        Duration::from_millis(50),
+        glib::clone!(@weak state, @strong job, @strong widgets => @default-return glib::ControlFlow::Break, move || {
+            let result = {
+                let mut current = job.borrow_mut();
+
+                // Задачу отменили или заменили новой -- её результат уже не нужен
+                let Some(running) = current.job.as_ref().filter(|job| Arc::ptr_eq(&job.progress, &progress)) else {
+                    return glib::ControlFlow::Break;
+                };
+
+                widgets.progress_bar.set_fraction(progress.fraction());
+                widgets.progress_bar.set_text(Some(&format!(
+                    "{} из {}",
+                    format_size(progress.read()),
+                    format_size(progress.total())
+                )));
+
+                let Some(result) = running.poll() else {
+                    return glib::ControlFlow::Continue;
+                };
+                current.job = None;
+                result
+            };
+            widgets.set_busy(false);
+
+            // Модель одалживается уже после того, как отпущена задача: подписчик,
+            // который запускает хэширование, тоже её одолжит
+            match result {
+                Ok(hashes) => state.apply(Change::FileHashed(revision, hashes)),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => state.apply(Change::FileFailed(revision, HashError::Read(e.to_string()))),
+            }
+
+            glib::ControlFlow::Break
+        }),
+    );
+}
+
+/// Спросить, куда сохранить QR-код, и сохранить его. Формат (PNG или SVG)
+/// берётся из расширения имени файла, `name` -- имя, предложенное по умолчанию.
+fn save_qr(button: &Button, widgets: &Widgets, name: &str) {
+    if widgets.qr.borrow().is_none() {
+        widgets.qr_message_label.set_label("Сначала посчитайте хэш");
+        return;
+    }
+
+    save_file(
+        button,
+        name,
+        &widgets.qr_message_label,
+        glib::clone!(@strong widgets => move |path| {
+            match widgets.qr.borrow().as_ref() {
+                Some(qr) => write_qr(qr, path),
+                None => Err("сначала посчитайте хэш".to_string()),
+            }
+        }),
+    );
+}
+
+/// Спросить, куда сохранить файл (`name` -- имя по умолчанию), и записать его функцией `write`.
+/// Что получилось, показывается в `message_label`.
+fn save_file(
+    button: &Button,
+    name: &str,
+    message_label: &Label,
+    write: impl Fn(&Path) -> Result<(), String> + 'static,
+) {
+    let window = button.root().and_downcast::<gtk::Window>();
+    let dialog = FileChooserDialog::new(
+        Some("Сохранить"),
+        window.as_ref(),
+        FileChooserAction::Save,
+        &[
+            ("Отмена", ResponseType::Cancel),
+            ("Сохранить", ResponseType::Accept),
+        ],
+    );
+    dialog.set_modal(true);
+    dialog.set_current_name(name);
+
+    dialog.connect_response(glib::clone!(@weak message_label => move |dialog, response| {
+        if response == ResponseType::Accept {
+            if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                match write(&path) {
+                    Ok(()) => message_label.set_label(&format!("Сохранено: {}", path.display())),
+                    Err(e) => message_label.set_label(&format!("Ошибка: {e}")),
+                }
+            }
+        }
+        dialog.close();
+    }));
+    dialog.present();
+}
+
+/// Записать QR-код в файл: SVG, если у файла расширение .svg, иначе PNG
+fn write_qr(qr: &QrCode, path: &Path) -> Result<(), String> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        return fs::write(path, qr::to_svg(qr)).map_err(|e| e.to_string());
+    }
+
+    // PNG кодирует сам GTK: картинка собирается из пикселей в памяти
+    let (width, pixels) = qr::to_rgb(qr, 8);
+    let texture = gdk::MemoryTexture::new(
+        width,
+        width,
+        gdk::MemoryFormat::R8g8b8,
+        &glib::Bytes::from_owned(pixels),
+        width as usize * 3,
+    );
+    texture.save_to_png(path).map_err(|e| e.to_string())
+}
+
+/// Показать окно с таблицей файлов из манифеста и проверить их в фоновом потоке.
+/// Если окно закрыть, проверка останавливается.
+fn show_manifest(parent: Option<&gtk::Window>, entries: Vec<ManifestEntry>) {
+    let grid = Grid::builder()
+        .row_spacing(6)
+        .column_spacing(24)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    for (column, title) in ["Файл", "Алгоритм", "Результат"].into_iter().enumerate()
+    {
+        let label = Label::builder().label(title).xalign(0.0).build();
+        label.add_css_class("heading");
+        grid.attach(&label, column as i32, 0, 1, 1);
+    }
+
+    // Ячейки с результатом, по одной на строку манифеста
+    let mut results = vec![];
+    for (row, entry) in entries.iter().enumerate() {
+        let row = row as i32 + 1;
+        let name = Label::builder().label(&entry.name).xalign(0.0).build();
+        let algo = Label::builder()
+            .label(entry.algo.name())
+            .xalign(0.0)
+            .build();
+        let result = Label::builder().label("…").xalign(0.0).build();
+        grid.attach(&name, 0, row, 1, 1);
+        grid.attach(&algo, 1, row, 1, 1);
+        grid.attach(&result, 2, row, 1, 1);
+        results.push(result);
+    }
+
+    let summary_label = Label::builder().xalign(0.0).margin_start(12).build();
+    let container = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(6)
+        .margin_bottom(12)
+        .build();
+    container.append(&ScrolledWindow::builder().child(&grid).vexpand(true).build());
+    container.append(&summary_label);
+
+    let window = gtk::Window::builder()
+        .title("Проверка контрольных сумм")
+        .default_width(640)
+        .default_height(480)
+        .child(&container)
+        .build();
+    window.set_transient_for(parent);
+
+    // Файлы проверяются по очереди в одном потоке, результаты приходят через канал
+    let progress = Arc::new(Progress::default());
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let total = entries.len();
+    std::thread::spawn(glib::clone!(@strong progress => move || {
+        for (index, entry) in entries.iter().enumerate() {
+            if progress.is_cancelled() || sender.send((index, manifest::check(entry, &progress))).is_err() {
+                break;
+            }
+        }
+    }));
+    window.connect_close_request(glib::clone!(@strong progress => move |_| {
+        progress.cancel();
+        glib::Propagation::Proceed
+    }));
+
+    let (mut done, mut failed) = (0, 0);
+    summary_label.set_label(&format!("Проверено 0 из {total}"));
+    glib::timeout_add_local(Duration::from_millis(50), move || {
+        if progress.is_cancelled() {
+            return glib::ControlFlow::Break;
+        }
+
+        while let Ok((index, status)) = receiver.try_recv() {
+            let label = &results[index];
+            match &status {
+                Status::Ok => label.add_css_class("success"),
+                _ => label.add_css_class("error"),
+            }
+            match &status {
+                Status::Unreadable(e) => label.set_label(&format!("{status}: {e}")),
+                _ => label.set_label(&status.to_string()),
+            }
+            done += 1;
+            if status != Status::Ok {
+                failed += 1;
+            }
+        }
+
+        if done < total {
+            summary_label.set_label(&format!("Проверено {done} из {total}"));
+            return glib::ControlFlow::Continue;
+        }
+        summary_label.set_label(&match failed {
+            0 => format!("Все файлы совпадают ({total})"),
+            _ => format!("Не совпало или не прочиталось: {failed} из {total}"),
+        });
+        glib::ControlFlow::Break
+    });
+
+    window.present();
+}
+
+/// Показать окно со скоростью всех алгоритмов на этой машине. Замеры идут
+/// в фоновом потоке по очереди, а столбики растут относительно самого быстрого.
+fn show_benchmark(parent: Option<&gtk::Window>) {
+    let grid = Grid::builder()
+        .row_spacing(6)
+        .column_spacing(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    let mut rows = vec![];
+    for (row, algo) in HashAlgorythm::ALL.iter().enumerate() {
+        let name = Label::builder().label(algo.name()).xalign(0.0).build();
+        let speed = Label::builder()
+            .label("…")
+            .xalign(1.0)
+            .width_chars(12)
+            .build();
+        let bar = gtk::LevelBar::builder()
+            .hexpand(true)
+            .valign(gtk::Align::Center)
+            .build();
+        grid.attach(&name, 0, row as i32, 1, 1);
+        grid.attach(&speed, 1, row as i32, 1, 1);
+        grid.attach(&bar, 2, row as i32, 1, 1);
+        rows.push((speed, bar));
+    }
+
+    let summary_label = Label::builder()
+        .label(format!(
+            "Каждый алгоритм хэширует {} кусками по 1 МиБ",
+            format_size(bench::DATA_SIZE as u64)
+        ))
+        .xalign(0.0)
+        .margin_start(12)
+        .margin_bottom(12)
+        .build();
+    let container = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(6)
+        .build();
+    container.append(&grid);
+    container.append(&summary_label);
+
+    let window = gtk::Window::builder()
+        .title("Скорость алгоритмов")
+        .default_width(480)
+        .child(&container)
+        .build();
+    window.set_transient_for(parent);
+
+    let progress = Arc::new(Progress::default());
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(glib::clone!(@strong progress => move || {
+        let data = bench::sample_data(bench::DATA_SIZE);
+        bench::measure_all(&HashAlgorythm::ALL, &data, bench::MIN_TIME, &progress, |result| {
+            let _ = sender.send(result);
+        });
+    }));
+    window.connect_close_request(glib::clone!(@strong progress => move |_| {
+        progress.cancel();
+        glib::Propagation::Proceed
+    }));
+
+    let mut results: Vec<Throughput> = vec![];
+    glib::timeout_add_local(Duration::from_millis(50), move || {
+        if progress.is_cancelled() {
+            return glib::ControlFlow::Break;
+        }
+
+        while let Ok(result) = receiver.try_recv() {
+            let (speed, _) = &rows[result.algo.index()];
+            speed.set_label(&format!("{:.1} МБ/с", result.mb_per_sec()));
+            results.push(result);
+        }
+        let fastest = results
+            .iter()
+            .map(Throughput::mb_per_sec)
+            .fold(0.0, f64::max);
+        for result in &results {
+            let (_, bar) = &rows[result.algo.index()];
+            bar.set_value(result.mb_per_sec() / fastest);
+        }
+
+        if results.len() < HashAlgorythm::ALL.len() {
+            return glib::ControlFlow::Continue;
+        }
+        if cfg!(debug_assertions) {
+            summary_label
+                .set_label("Готово. Это отладочная сборка, в --release всё в разы быстрее.");
+        } else {
+            summary_label.set_label("Готово");
+        }
+        glib::ControlFlow::Break
+    });
+
+    window.present();
+}
+
+/// Выполнить `work` в фоновом потоке, а потом отдать результат `done` в главном потоке.
+/// Хэширование паролей специально медленное, и без этого окно зависало бы.
+fn run_in_background<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+    done: impl FnOnce(T) + 'static,
+) {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+
+    let mut done = Some(done);
+    glib::timeout_add_local(Duration::from_millis(50), move || {
+        match receiver.try_recv() {
+            Ok(result) => {
+                if let Some(done) = done.take() {
+                    done(result);
+                }
+                glib::ControlFlow::Break
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => glib::ControlFlow::Break,
+        }
+    });
+}
+
+/// Поля с параметрами стоимости. Видна только строка выбранного алгоритма.
+struct CostWidgets {
+    argon2_row: gtk::Box,
+    memory: SpinButton,
+    iterations: SpinButton,
+    argon2_parallelism: SpinButton,
+    scrypt_row: gtk::Box,
+    log_n: SpinButton,
+    block_size: SpinButton,
+    scrypt_parallelism: SpinButton,
+    bcrypt_row: gtk::Box,
+    cost: SpinButton,
+}
+
+impl CostWidgets {
+    fn new() -> Self {
+        let row = |fields: &[(&str, &SpinButton)]| {
+            let row = gtk::Box::builder()
+                .orientation(Orientation::Horizontal)
+                .spacing(6)
+                .build();
+            for (title, spin_button) in fields {
+                row.append(&Label::new(Some(title)));
+                row.append(*spin_button);
+            }
+            row
+        };
+
+        let memory = SpinButton::with_range(8.0, 4.0 * 1024.0 * 1024.0, 1024.0);
+        let iterations = SpinButton::with_range(1.0, 100.0, 1.0);
+        let argon2_parallelism = SpinButton::with_range(1.0, 64.0, 1.0);
+        let log_n = SpinButton::with_range(1.0, 24.0, 1.0);
+        let block_size = SpinButton::with_range(1.0, 64.0, 1.0);
+        let scrypt_parallelism = SpinButton::with_range(1.0, 64.0, 1.0);
+        let cost = SpinButton::with_range(4.0, 31.0, 1.0);
+
+        Self {
+            argon2_row: row(&[
+                ("Память, КиБ:", &memory),
+                ("Проходов:", &iterations),
+                ("Потоков:", &argon2_parallelism),
+            ]),
+            scrypt_row: row(&[
+                ("log N:", &log_n),
+                ("r:", &block_size),
+                ("p:", &scrypt_parallelism),
+            ]),
+            bcrypt_row: row(&[("Стоимость:", &cost)]),
+            memory,
+            iterations,
+            argon2_parallelism,
+            log_n,
+            block_size,
+            scrypt_parallelism,
+            cost,
+        }
+    }
+
+    /// Показать параметры алгоритма
+    fn set(&self, params: Params) {
+        self.argon2_row
+            .set_visible(params.algorithm() == PasswordAlgorithm::Argon2id);
+        self.scrypt_row
+            .set_visible(params.algorithm() == PasswordAlgorithm::Scrypt);
+        self.bcrypt_row
+            .set_visible(params.algorithm() == PasswordAlgorithm::Bcrypt);
+
+        match params {
+            Params::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => {
+                self.memory.set_value(memory_kib as f64);
+                self.iterations.set_value(iterations as f64);
+                self.argon2_parallelism.set_value(parallelism as f64);
+            }
+            Params::Scrypt { log_n, r, p } => {
+                self.log_n.set_value(log_n as f64);
+                self.block_size.set_value(r as f64);
+                self.scrypt_parallelism.set_value(p as f64);
+            }
+            Params::Bcrypt { cost } => self.cost.set_value(cost as f64),
+        }
+    }
+
+    fn params(&self, algo: PasswordAlgorithm) -> Params {
+        let value = |spin_button: &SpinButton| spin_button.value_as_int() as u32;
+        match algo {
+            PasswordAlgorithm::Argon2id => Params::Argon2id {
+                memory_kib: value(&self.memory),
+                iterations: value(&self.iterations),
+                parallelism: value(&self.argon2_parallelism),
+            },
+            PasswordAlgorithm::Scrypt => Params::Scrypt {
+                log_n: value(&self.log_n) as u8,
+                r: value(&self.block_size),
+                p: value(&self.scrypt_parallelism),
+            },
+            PasswordAlgorithm::Bcrypt => Params::Bcrypt {
+                cost: value(&self.cost),
+            },
+        }
+    }
+}
+
+/// Окно хэширования паролей: Argon2id, scrypt или bcrypt с солью и параметрами,
+/// проверка пароля по строке хэша и подбор параметров под время на этой машине
+fn show_passwords(parent: Option<&gtk::Window>) {
+    let algo_drop_down = DropDown::from_strings(&PasswordAlgorithm::names());
+    let password_entry = PasswordEntry::builder()
+        .show_peek_icon(true)
+        .hexpand(true)
+        .build();
+    let costs = Rc::new(CostWidgets::new());
+    costs.set(Params::recommended(PasswordAlgorithm::Argon2id));
+    let salt_entry = Entry::builder()
+        .placeholder_text("Случайная (так и надо для настоящих паролей)")
+        .hexpand(true)
+        .build();
+    let salt_encoding_drop_down = DropDown::from_strings(&TextEncoding::names());
+    let target_spin_button = SpinButton::with_range(50.0, 10000.0, 50.0);
+    target_spin_button.set_value(500.0);
+    let tune_button = Button::with_label("Подобрать");
+    let tune_label = Label::builder().xalign(0.0).hexpand(true).build();
+    let hash_button = Button::with_label("Хэшировать");
+    let hash_label = Label::builder()
+        .wrap(true)
+        .wrap_mode(WrapMode::Char)
+        .selectable(true)
+        .max_width_chars(64)
+        .xalign(0.0)
+        .build();
+    let verify_entry = Entry::builder()
+        .placeholder_text("Строка хэша, например $argon2id$v=19$…")
+        .hexpand(true)
+        .build();
+    let verify_button = Button::with_label("Проверить");
+    let verify_label = Label::builder().xalign(0.0).build();
+
+    let row = |widgets: &[&gtk::Widget]| {
+        let row = gtk::Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .build();
+        for widget in widgets {
+            row.append(*widget);
+        }
+        row
+    };
+    let container = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .spacing(12)
+        .build();
+    container.append(&row(&[
+        Label::new(Some("Пароль:")).upcast_ref(),
+        password_entry.upcast_ref(),
+        algo_drop_down.upcast_ref(),
+    ]));
+    container.append(&costs.argon2_row);
+    container.append(&costs.scrypt_row);
+    container.append(&costs.bcrypt_row);
+    container.append(&row(&[
+        Label::new(Some("Время одного хэша, мс:")).upcast_ref(),
+        target_spin_button.upcast_ref(),
+        tune_button.upcast_ref(),
+        tune_label.upcast_ref(),
+    ]));
+    container.append(&row(&[
+        Label::new(Some("Соль:")).upcast_ref(),
+        salt_entry.upcast_ref(),
+        salt_encoding_drop_down.upcast_ref(),
+    ]));
+    container.append(&hash_button);
+    container.append(&hash_label);
+    container.append(&row(&[
+        verify_entry.upcast_ref(),
+        verify_button.upcast_ref(),
+    ]));
+    container.append(&verify_label);
+
+    let window = gtk::Window::builder()
+        .title("Хэширование паролей")
+        .default_width(640)
+        .child(&container)
+        .build();
+    window.set_transient_for(parent);
+
+    let selected_algo = glib::clone!(@weak algo_drop_down => @default-return PasswordAlgorithm::Argon2id, move || {
+        PasswordAlgorithm::ALL
+            .get(algo_drop_down.selected() as usize)
+            .copied()
+            .unwrap_or(PasswordAlgorithm::Argon2id)
+    });
+
+    // У каждого алгоритма свои параметры, начинаем с рекомендованных
+    algo_drop_down.connect_selected_notify(
+        glib::clone!(@strong costs, @weak tune_label => move |drop_down| {
+            let Some(&algo) = PasswordAlgorithm::ALL.get(drop_down.selected() as usize) else {
+                return;
+            };
+            costs.set(Params::recommended(algo));
+            tune_label.set_label("");
+        }),
+    );
+
+    tune_button.connect_clicked(glib::clone!(@strong costs, @strong selected_algo, @weak target_spin_button, @weak tune_label => move |button| {
+        let algo = selected_algo();
+        let target = Duration::from_millis(target_spin_button.value_as_int() as u64);
+        button.set_sensitive(false);
+        tune_label.set_label("Подбираем…");
+        run_in_background(move || password::tune(algo, target), glib::clone!(@strong costs, @weak button, @weak tune_label => move |result: Result<Tuning, PasswordError>| {
+            button.set_sensitive(true);
+            match result {
+                Ok(tuning) => {
+                    costs.set(tuning.params);
+                    tune_label.set_label(&format!("{} мс на этой машине", tuning.elapsed.as_millis()));
+                }
+                Err(e) => tune_label.set_label(&format!("Ошибка: {e}")),
+            }
+        }));
+    }));
+
+    hash_button.connect_clicked(glib::clone!(@strong costs, @strong selected_algo, @weak password_entry, @weak salt_entry, @weak salt_encoding_drop_down, @weak hash_label, @weak verify_entry => move |button| {
+        let params = costs.params(selected_algo());
+        let salt = match salt_entry.text().as_str() {
+            "" => None,
+            text => {
+                let encoding = TextEncoding::ALL
+                    .get(salt_encoding_drop_down.selected() as usize)
+                    .copied()
+                    .unwrap_or(TextEncoding::Utf8);
+                match encoding.decode(text) {
+                    Ok(salt) => Some(salt),
+                    Err(e) => {
+                        hash_label.set_label(&format!("Ошибка: соль: {e}"));
+                        return;
+                    }
+                }
+            }
+        };
+        let password = password_entry.text().to_string();
+
+        button.set_sensitive(false);
+        hash_label.set_label("Хэшируем…");
+        run_in_background(
+            move || password::hash_password(password.as_bytes(), params, salt.as_deref()),
+            glib::clone!(@weak button, @weak hash_label, @weak verify_entry => move |result: Result<String, PasswordError>| {
+                button.set_sensitive(true);
+                match result {
+                    Ok(hash) => {
+                        hash_label.set_label(&hash);
+                        // Сразу можно проверить, что пароль подходит к хэшу
+                        verify_entry.set_text(&hash);
+                    }
+                    Err(e) => hash_label.set_label(&format!("Ошибка: {e}")),
+                }
+            }),
+        );
+    }));
+
+    verify_button.connect_clicked(
+        glib::clone!(@weak password_entry, @weak verify_entry, @weak verify_label => move |button| {
+            let password = password_entry.text().to_string();
+            let hash = verify_entry.text().to_string();
+
+            button.set_sensitive(false);
+            verify_label.set_label("Проверяем…");
+            verify_label.remove_css_class("success");
+            verify_label.remove_css_class("error");
+            run_in_background(
+                move || password::verify_password(password.as_bytes(), &hash),
+                glib::clone!(@weak button, @weak verify_label => move |result: Result<bool, PasswordError>| {
+                    button.set_sensitive(true);
+                    let (text, class) = match result {
+                        Ok(true) => ("Пароль подходит".to_string(), "success"),
+                        Ok(false) => ("Пароль не подходит".to_string(), "error"),
+                        Err(e) => (format!("Ошибка: {e}"), "error"),
+                    };
+                    verify_label.set_label(&text);
+                    verify_label.add_css_class(class);
+                }),
+            );
+        }),
+    );
+
+    window.present();
+}
@@ -0,0 +1,922 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use base64::alphabet;
+use base64::engine::{general_purpose, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine as _;
+use data_encoding::{BASE32, BASE32_NOPAD, HEXUPPER};
+
+use crate::algorythm::{BoxDigest, HashAlgorythm};
+use crate::key::{Key, KeyError};
+use crate::normalize::Normalization;
+
+/// Base64, в котором выравнивание знаками '=' необязательно. Так читается и то,
+/// что выводит сама программа (без '='), и то, что выводят другие (с '=').
+pub const BASE64_ANY_PADDING: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// То же для варианта Base64 для URL, где вместо '+' и '/' используются '-' и '_'
+pub const BASE64_URL_ANY_PADDING: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Способ отображения хэшированных данных
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashPreviewOption {
+    /// Base64 без выравнивания знаками '='
+    Base64,
+    Base64Padded,
+    /// Base64 для URL и имён файлов: '-' и '_' вместо '+' и '/', без '='
+    Base64Url,
+    Hex,
+    HexUpper,
+    /// Байты через двоеточие, как отпечатки сертификатов: AB:CD:EF
+    HexColon,
+    /// Base32 из RFC 4648: заглавные буквы и цифры 2-7
+    Base32,
+    /// Base58 из Bitcoin: без похожих символов 0, O, I и l
+    Base58,
+    /// Multibase: первый символ говорит, как записано остальное ('z' -- base58btc)
+    Multibase,
+    /// Multihash: перед хэшем стоят код алгоритма и длина, всё записано в multibase
+    Multihash,
+}
+
+impl HashPreviewOption {
+    /// Варианты для drop-down. Порядок важен и при разборе: строка, которая
+    /// читается несколькими способами, прочитается первым подходящим.
+    pub const ALL: [HashPreviewOption; 10] = [
+        HashPreviewOption::Hex,
+        HashPreviewOption::HexUpper,
+        HashPreviewOption::HexColon,
+        HashPreviewOption::Base64,
+        HashPreviewOption::Base64Padded,
+        HashPreviewOption::Base64Url,
+        HashPreviewOption::Base32,
+        HashPreviewOption::Base58,
+        HashPreviewOption::Multibase,
+        HashPreviewOption::Multihash,
+    ];
+
+    /// Название для командной строки и экспорта
+    pub fn name(self) -> &'static str {
+        match self {
+            HashPreviewOption::Base64 => "base64",
+            HashPreviewOption::Base64Padded => "base64-padded",
+            HashPreviewOption::Base64Url => "base64url",
+            HashPreviewOption::Hex => "hex",
+            HashPreviewOption::HexUpper => "hex-upper",
+            HashPreviewOption::HexColon => "hex-colon",
+            HashPreviewOption::Base32 => "base32",
+            HashPreviewOption::Base58 => "base58",
+            HashPreviewOption::Multibase => "multibase",
+            HashPreviewOption::Multihash => "multihash",
+        }
+    }
+
+    /// Название для интерфейса
+    pub fn label(self) -> &'static str {
+        match self {
+            HashPreviewOption::Base64 => "Base64",
+            HashPreviewOption::Base64Padded => "Base64 с '='",
+            HashPreviewOption::Base64Url => "Base64 для URL",
+            HashPreviewOption::Hex => "hex",
+            HashPreviewOption::HexUpper => "HEX",
+            HashPreviewOption::HexColon => "HEX через двоеточие",
+            HashPreviewOption::Base32 => "Base32",
+            HashPreviewOption::Base58 => "Base58",
+            HashPreviewOption::Multibase => "Multibase (base58btc)",
+            HashPreviewOption::Multihash => "Multihash (base58btc)",
+        }
+    }
+
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|option| option.label()).collect()
+    }
+
+    /// Прочитать байты хэша, записанные в этом виде. Пробелы по краям не учитываются.
+    /// У Multihash возвращается сам хэш без кода алгоритма и длины.
+    pub fn decode(self, text: &str) -> Option<Vec<u8>> {
+        let text = text.trim();
+        match self {
+            HashPreviewOption::Base64 | HashPreviewOption::Base64Padded => {
+                BASE64_ANY_PADDING.decode(text).ok()
+            }
+            HashPreviewOption::Base64Url => BASE64_URL_ANY_PADDING.decode(text).ok(),
+            // hex::decode понимает и строчные, и заглавные буквы
+            HashPreviewOption::Hex | HashPreviewOption::HexUpper => hex::decode(text).ok(),
+            HashPreviewOption::HexColon => {
+                let bytes: Option<Vec<u8>> = text
+                    .split(':')
+                    .map(|byte| match byte.len() {
+                        2 => u8::from_str_radix(byte, 16).ok(),
+                        _ => None,
+                    })
+                    .collect();
+                bytes.filter(|bytes| bytes.len() > 1)
+            }
+            HashPreviewOption::Base32 => BASE32_NOPAD
+                .decode(text.trim_end_matches('=').to_ascii_uppercase().as_bytes())
+                .ok(),
+            HashPreviewOption::Base58 => bs58::decode(text).into_vec().ok(),
+            HashPreviewOption::Multibase => decode_multibase(text),
+            HashPreviewOption::Multihash => {
+                let multihash = decode_multibase(text)?;
+                let (_code, rest) = read_varint(&multihash)?;
+                let (len, digest) = read_varint(rest)?;
+                (digest.len() as u64 == len).then(|| digest.to_vec())
+            }
+        }
+    }
+
+    /// Записать байты хэша в этом виде. Для Multihash байты должны уже
+    /// начинаться с кода алгоритма и длины (см. multihash).
+    pub fn encode(self, data: &[u8]) -> String {
+        match self {
+            HashPreviewOption::Base64 => general_purpose::STANDARD_NO_PAD.encode(data),
+            HashPreviewOption::Base64Padded => general_purpose::STANDARD.encode(data),
+            HashPreviewOption::Base64Url => general_purpose::URL_SAFE_NO_PAD.encode(data),
+            HashPreviewOption::Hex => hex::encode(data),
+            HashPreviewOption::HexUpper => HEXUPPER.encode(data),
+            HashPreviewOption::HexColon => data
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(":"),
+            HashPreviewOption::Base32 => BASE32.encode(data),
+            HashPreviewOption::Base58 => bs58::encode(data).into_string(),
+            HashPreviewOption::Multibase | HashPreviewOption::Multihash => {
+                format!("z{}", bs58::encode(data).into_string())
+            }
+        }
+    }
+}
+
+impl FromStr for HashPreviewOption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let names: Vec<_> = Self::ALL.iter().map(|option| option.name()).collect();
+        Self::ALL
+            .into_iter()
+            .find(|option| option.name() == s.to_ascii_lowercase())
+            .ok_or_else(|| format!("unknown encoding '{s}', expected {}", names.join(", ")))
+    }
+}
+
+/// Прочитать строку multibase по её первому символу. Понимаются те же
+/// способы записи, что есть в HashPreviewOption.
+fn decode_multibase(text: &str) -> Option<Vec<u8>> {
+    let mut chars = text.chars();
+    let prefix = chars.next()?;
+    let rest = chars.as_str();
+    match prefix {
+        'f' | 'F' => hex::decode(rest).ok(),
+        'm' | 'M' => BASE64_ANY_PADDING.decode(rest).ok(),
+        'u' | 'U' => BASE64_URL_ANY_PADDING.decode(rest).ok(),
+        'b' | 'B' => BASE32_NOPAD
+            .decode(rest.to_ascii_uppercase().as_bytes())
+            .ok(),
+        'z' => bs58::decode(rest).into_vec().ok(),
+        _ => None,
+    }
+}
+
+/// Число в формате unsigned varint (по 7 битов в байте, старший бит -- «дальше ещё»)
+/// и то, что идёт после него
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+fn write_varint(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Хэш в формате multihash: код алгоритма, длина хэша, сам хэш
+pub fn multihash(code: u64, digest: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![];
+    write_varint(code, &mut bytes);
+    write_varint(digest.len() as u64, &mut bytes);
+    bytes.extend_from_slice(digest);
+    bytes
+}
+
+/// Как записаны байты, введённые текстом: хэшируемые данные или секретный ключ
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// Байты строки в UTF-8 как есть
+    Utf8,
+    Hex,
+    Base64,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 3] =
+        [TextEncoding::Utf8, TextEncoding::Hex, TextEncoding::Base64];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Hex => "Hex",
+            TextEncoding::Base64 => "Base64",
+        }
+    }
+
+    /// Названия для drop-down в том же порядке, что и в ALL
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|encoding| encoding.name()).collect()
+    }
+
+    /// Байты текста. Пробелы по краям у hex и base64 не учитываются,
+    /// а у UTF-8 считаются частью текста.
+    pub fn decode(self, text: &str) -> Result<Vec<u8>, String> {
+        match self {
+            TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            TextEncoding::Hex => hex::decode(text.trim()).map_err(|e| e.to_string()),
+            TextEncoding::Base64 => BASE64_ANY_PADDING
+                .decode(text.trim())
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(TextEncoding::Utf8),
+            "hex" => Ok(TextEncoding::Hex),
+            "base64" => Ok(TextEncoding::Base64),
+            _ => Err(format!(
+                "unknown text encoding '{s}', expected utf8, hex or base64"
+            )),
+        }
+    }
+}
+
+/// Почему хэш не посчитан или его нельзя показать
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashError {
+    Key(KeyError),
+    /// Введённый текст не читается в выбранной кодировке
+    Input(String),
+    /// У функции нет кода в таблице multihash (например, у HMAC)
+    NoMultihash(String),
+    /// Файл не удалось прочитать
+    Read(String),
+}
+
+impl Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::Key(e) => write!(f, "{e}"),
+            HashError::Input(msg) => write!(f, "invalid input: {msg}"),
+            HashError::NoMultihash(name) => write!(f, "{name} has no multihash code"),
+            HashError::Read(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl From<KeyError> for HashError {
+    fn from(e: KeyError) -> Self {
+        HashError::Key(e)
+    }
+}
+
+/// Результат сравнения хэша с ожидаемым
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verification {
+    Match,
+    Mismatch,
+    /// Ожидаемый хэш не читается ни одним из способов HashPreviewOption
+    Invalid,
+}
+
+/// Сравнить хэш с ожидаемым, записанным в любом из видов HashPreviewOption
+pub fn verify(data: &[u8], expected: &str) -> Verification {
+    let decoded: Vec<Vec<u8>> = HashPreviewOption::ALL
+        .iter()
+        .filter_map(|option| option.decode(expected))
+        .collect();
+
+    if decoded.is_empty() {
+        Verification::Invalid
+    } else if decoded.iter().any(|expected| expected == data) {
+        Verification::Match
+    } else {
+        Verification::Mismatch
+    }
+}
+
+/// Что хэшируется: текст из поля ввода или файл
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Text,
+    /// Файл читается в фоновом потоке (см. stream.rs), а результат
+    /// приходит в модель изменением Change::FileHashed
+    File(PathBuf),
+}
+
+/// Строка таблицы сравнения: хэш одного из выбранных алгоритмов
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comparison {
+    pub algo: HashAlgorythm,
+    /// Хэш или почему его нет (ключ подходит не всем алгоритмам)
+    pub value: Result<Vec<u8>, KeyError>,
+}
+
+/// Изменение модели. Модель меняется только через Hasher::apply, поэтому
+/// хэш пересчитывается (или сбрасывается) в одном месте, а не в каждом обработчике.
+#[derive(Clone, Debug)]
+pub enum Change {
+    /// Новый текст. Источником снова становится текст.
+    Text(String),
+    InputEncoding(TextEncoding),
+    /// Как нормализовать текст перед хэшированием
+    Normalization(Normalization),
+    /// Хэшировать файл вместо текста
+    File(PathBuf),
+    /// Другой алгоритм. Длина хэша сбрасывается на длину по умолчанию.
+    Algo(HashAlgorythm),
+    /// Длина хэша в байтах. Меняется только у алгоритмов с выводом произвольной длины.
+    OutputLen(usize),
+    Key(Option<Key>),
+    Option(HashPreviewOption),
+    Expected(String),
+    Compare(Vec<HashAlgorythm>),
+    /// Фоновое хэширование файла закончилось: номер ревизии, для которой оно
+    /// запускалось, и хэши в порядке хэшеров из digests
+    FileHashed(u64, Vec<Vec<u8>>),
+    /// Фоновое хэширование файла не удалось
+    FileFailed(u64, HashError),
+}
+
+/// Модель приложения
+#[derive(Clone)]
+pub struct Hasher {
+    algo: HashAlgorythm,
+    /// Длина хэша в байтах для алгоритмов с выводом произвольной длины (SHAKE)
+    output_len: usize,
+    option: HashPreviewOption,
+    text: String,
+    /// Как записан текст: его можно хэшировать как UTF-8 или сначала декодировать из hex или base64
+    input_encoding: TextEncoding,
+    /// Нормализация текста в UTF-8 перед хэшированием. К hex и base64 не применяется:
+    /// там записаны байты, а не текст.
+    normalization: Normalization,
+    source: Source,
+    /// Секретный ключ. Если он задан, считается HMAC или хэш BLAKE2/BLAKE3 с ключом.
+    key: Option<Key>,
+    /// Ожидаемый хэш для режима проверки (пустая строка -- проверка выключена)
+    expected: String,
+    /// Алгоритмы, которые считаются вместе с основным для сравнения (пусто -- сравнение выключено)
+    compare: Vec<HashAlgorythm>,
+    /// Номер состояния входных данных. Растёт при каждом изменении, после которого
+    /// хэш нужно посчитать заново. По нему отбрасываются результаты устаревших
+    /// фоновых хэширований.
+    revision: u64,
+    data: Vec<u8>,
+    comparison: Vec<Comparison>,
+    /// Почему не удалось посчитать хэш (например, ключ не подходит алгоритму)
+    error: Option<HashError>,
+}
+
+impl Hasher {
+    pub fn new(algo: HashAlgorythm, option: HashPreviewOption) -> Self {
+        Self {
+            algo,
+            output_len: algo.default_output_len(),
+            option,
+            text: "".to_string(),
+            input_encoding: TextEncoding::Utf8,
+            normalization: Normalization::default(),
+            source: Source::Text,
+            key: None,
+            expected: "".to_string(),
+            compare: vec![],
+            revision: 0,
+            data: vec![],
+            comparison: vec![],
+            error: None,
+        }
+    }
+
+    /// Применить изменение и привести хэш в соответствие с ним. Текст хэшируется
+    /// сразу, а хэш файла сбрасывается до тех пор, пока не придёт FileHashed
+    /// с новой ревизией.
+    pub fn apply(&mut self, change: Change) {
+        match change {
+            Change::Text(text) => {
+                self.text = text;
+                self.source = Source::Text;
+            }
+            Change::InputEncoding(encoding) => self.input_encoding = encoding,
+            Change::Normalization(normalization) => self.normalization = normalization,
+            Change::File(path) => self.source = Source::File(path),
+            Change::Algo(algo) => {
+                self.algo = algo;
+                self.output_len = algo.default_output_len();
+            }
+            Change::OutputLen(output_len) => {
+                if !self.algo.is_xof() || self.output_len == output_len {
+                    return;
+                }
+                self.output_len = output_len;
+            }
+            Change::Key(key) => self.key = key,
+            Change::Compare(compare) => self.compare = compare,
+
+            // Вид хэша и ожидаемое значение на сам хэш не влияют
+            Change::Option(option) => {
+                self.option = option;
+                return;
+            }
+            Change::Expected(expected) => {
+                self.expected = expected;
+                return;
+            }
+
+            // Результат для старой ревизии уже никому не нужен
+            Change::FileHashed(revision, results) => {
+                if revision == self.revision && matches!(self.source, Source::File(_)) {
+                    self.set_results(results);
+                }
+                return;
+            }
+            Change::FileFailed(revision, error) => {
+                if revision == self.revision && matches!(self.source, Source::File(_)) {
+                    self.set_error(error);
+                }
+                return;
+            }
+        }
+
+        self.revision += 1;
+        self.recalculate();
+    }
+
+    pub fn algo(&self) -> HashAlgorythm {
+        self.algo
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    pub fn option(&self) -> HashPreviewOption {
+        self.option
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// Нормализация, которая действительно применяется к текущему хэшу: None,
+    /// если она выключена, хэшируется файл или текст записан в hex или base64
+    pub fn applied_normalization(&self) -> Option<Normalization> {
+        let applies = matches!(self.source, Source::Text)
+            && self.input_encoding == TextEncoding::Utf8
+            && !self.normalization.is_empty();
+        applies.then_some(self.normalization)
+    }
+
+    /// Введённый текст (даже если сейчас хэшируется файл)
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Хэшер для текущего алгоритма, с ключом, если он задан
+    pub fn digest(&self) -> Result<BoxDigest, KeyError> {
+        self.digest_for(self.algo)
+    }
+
+    /// Хэшер для любого алгоритма с теми же настройками (ключом и длиной хэша).
+    /// Длина, выбранная пользователем, относится только к текущему алгоритму.
+    fn digest_for(&self, algo: HashAlgorythm) -> Result<BoxDigest, KeyError> {
+        let output_len = if algo == self.algo {
+            self.output_len
+        } else {
+            algo.default_output_len()
+        };
+        match &self.key {
+            Some(key) => algo.keyed_digest(&key.bytes()?),
+            None => Ok(algo.digest(output_len)),
+        }
+    }
+
+    /// Хэшеры для хэширования файла за один проход: сначала текущий алгоритм,
+    /// потом те из сравниваемых, для которых хэшер удалось создать.
+    /// Результат передаётся обратно в FileHashed в том же порядке.
+    pub fn digests(&self) -> Result<Vec<BoxDigest>, KeyError> {
+        let mut digests = vec![self.digest()?];
+        digests.extend(
+            self.compare
+                .iter()
+                .filter_map(|&algo| self.digest_for(algo).ok()),
+        );
+        Ok(digests)
+    }
+
+    /// Записать хэши, посчитанные хэшерами из digests
+    fn set_results(&mut self, results: Vec<Vec<u8>>) {
+        let mut results = results.into_iter();
+        self.data = results.next().unwrap_or_default();
+        self.error = None;
+
+        self.comparison = self
+            .compare
+            .iter()
+            .map(|&algo| Comparison {
+                algo,
+                value: self
+                    .digest_for(algo)
+                    .map(|_| results.next().unwrap_or_default()),
+            })
+            .collect();
+    }
+
+    /// Название того, что считается: SHA3-256, HMAC-SHA-256, keyed BLAKE3
+    pub fn function_name(&self) -> String {
+        self.function_name_for(self.algo)
+    }
+
+    /// Название функции для алгоритма `algo` с текущим ключом, например для строки сравнения
+    pub fn function_name_for(&self, algo: HashAlgorythm) -> String {
+        match self.key {
+            Some(_) => algo.keyed_name(),
+            None => algo.name().to_string(),
+        }
+    }
+
+    /// Посчитать хэш заново. Старый хэш и старая ошибка в любом случае сбрасываются:
+    /// пустой текст даёт пустой хэш, а хэш файла придёт позже из фонового потока.
+    fn recalculate(&mut self) {
+        self.clear();
+        if matches!(self.source, Source::File(_)) || self.text.is_empty() {
+            return;
+        }
+
+        let text = match self.applied_normalization() {
+            Some(normalization) => Cow::Owned(normalization.apply(&self.text)),
+            None => Cow::Borrowed(self.text.as_str()),
+        };
+        let input = match self.input_encoding.decode(&text) {
+            Ok(input) => input,
+            Err(e) => return self.set_error(HashError::Input(e)),
+        };
+
+        match self.digests() {
+            Ok(digests) => {
+                let results = digests
+                    .into_iter()
+                    .map(|mut digest| {
+                        digest.update(&input);
+                        digest.finalize().to_vec()
+                    })
+                    .collect();
+                self.set_results(results);
+            }
+            Err(e) => self.set_error(e.into()),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.data.clear();
+        self.comparison.clear();
+        self.error = None;
+    }
+
+    /// Запомнить, почему хэш не посчитан. Старый хэш при этом больше не верен.
+    fn set_error(&mut self, error: HashError) {
+        self.clear();
+        self.error = Some(error);
+    }
+
+    pub fn error(&self) -> Option<&HashError> {
+        self.error.as_ref()
+    }
+
+    /// Имя файла-источника или None, если хэшируется текст
+    pub fn source_name(&self) -> Option<String> {
+        match &self.source {
+            Source::File(path) => Some(
+                path.file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            Source::Text => None,
+        }
+    }
+
+    /// Хэши выбранных для сравнения алгоритмов
+    pub fn comparison(&self) -> &[Comparison] {
+        &self.comparison
+    }
+
+    /// Сравнить хэш с ожидаемым. Если сравнивать нечего, возвращает None.
+    pub fn verification(&self) -> Option<Verification> {
+        if self.expected.trim().is_empty() || self.data.is_empty() {
+            return None;
+        }
+        Some(verify(&self.data, &self.expected))
+    }
+
+    /// Записать хэш алгоритма `algo` в выбранном виде. Для multihash нужен код
+    /// алгоритма, а у функций с ключом его нет.
+    pub fn encode(&self, algo: HashAlgorythm, data: &[u8]) -> Result<String, HashError> {
+        if self.option != HashPreviewOption::Multihash {
+            return Ok(self.option.encode(data));
+        }
+
+        match (&self.key, algo.multihash_code()) {
+            (None, Some(code)) => Ok(self.option.encode(&multihash(code, data))),
+            (Some(_), _) => Err(HashError::NoMultihash(algo.keyed_name())),
+            (None, None) => Err(HashError::NoMultihash(algo.name().to_string())),
+        }
+    }
+
+    /// Отобразить захэшированный текст в зависимости от свойства option
+    /// Если текста нет, то ничего не произойдёт
+    pub fn view(&self) -> Result<String, HashError> {
+        // Если хэша, ничего не делать
+        if self.data.is_empty() {
+            return Ok("".to_string());
+        }
+
+        self.encode(self.algo, &self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalize::UnicodeForm;
+
+    fn hasher_with_text(text: &str) -> Hasher {
+        let mut hasher = Hasher::new(HashAlgorythm::Sha3_256, HashPreviewOption::Hex);
+        hasher.apply(Change::Text(text.to_string()));
+        hasher
+    }
+
+    #[test]
+    fn clearing_text_clears_digest() {
+        let mut hasher = hasher_with_text("abc");
+        assert_ne!(hasher.view(), Ok("".to_string()));
+
+        hasher.apply(Change::Text("".to_string()));
+        assert_eq!(hasher.view(), Ok("".to_string()));
+        assert!(hasher.error().is_none());
+        assert!(hasher.comparison().is_empty());
+    }
+
+    #[test]
+    fn clearing_text_clears_error() {
+        let mut hasher = hasher_with_text("");
+        hasher.apply(Change::InputEncoding(TextEncoding::Hex));
+        hasher.apply(Change::Text("zz".to_string()));
+        assert!(matches!(hasher.error(), Some(HashError::Input(_))));
+
+        hasher.apply(Change::Text("".to_string()));
+        assert!(hasher.error().is_none());
+    }
+
+    #[test]
+    fn switching_algorithm_recomputes_digest() {
+        let mut hasher = hasher_with_text("abc");
+        hasher.apply(Change::Algo(HashAlgorythm::Sha256));
+        assert_eq!(
+            hasher.view(),
+            Ok("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string())
+        );
+
+        hasher.apply(Change::Algo(HashAlgorythm::Shake128));
+        assert_eq!(hasher.output_len(), 32);
+        assert_eq!(hasher.view().unwrap().len(), 64);
+    }
+
+    #[test]
+    fn switching_algorithm_with_empty_text_keeps_digest_empty() {
+        let mut hasher = hasher_with_text("abc");
+        hasher.apply(Change::Text("".to_string()));
+        hasher.apply(Change::Algo(HashAlgorythm::Blake3));
+        assert_eq!(hasher.view(), Ok("".to_string()));
+    }
+
+    #[test]
+    fn switching_algorithm_drops_stale_file_digest() {
+        let mut hasher = hasher_with_text("");
+        hasher.apply(Change::File(PathBuf::from("file")));
+        let revision = hasher.revision();
+        hasher.apply(Change::FileHashed(revision, vec![vec![1, 2, 3]]));
+        assert_eq!(hasher.view(), Ok("010203".to_string()));
+
+        // Хэш файла для старого алгоритма больше не показывается, а опоздавший результат
+        // хэширования старым алгоритмом отбрасывается
+        hasher.apply(Change::Algo(HashAlgorythm::Sha256));
+        assert_eq!(hasher.view(), Ok("".to_string()));
+        assert_ne!(hasher.revision(), revision);
+        hasher.apply(Change::FileHashed(revision, vec![vec![1, 2, 3]]));
+        assert_eq!(hasher.view(), Ok("".to_string()));
+
+        hasher.apply(Change::FileHashed(hasher.revision(), vec![vec![4, 5]]));
+        assert_eq!(hasher.view(), Ok("0405".to_string()));
+    }
+
+    #[test]
+    fn switching_encoding_keeps_digest() {
+        let mut hasher = hasher_with_text("abc");
+        let revision = hasher.revision();
+        let hex = hasher.view().unwrap();
+
+        hasher.apply(Change::Option(HashPreviewOption::Base64Padded));
+        assert_eq!(hasher.revision(), revision);
+        let base64 = hasher.view().unwrap();
+        assert_eq!(
+            HashPreviewOption::Base64Padded.decode(&base64),
+            HashPreviewOption::Hex.decode(&hex)
+        );
+
+        hasher.apply(Change::Option(HashPreviewOption::Hex));
+        assert_eq!(hasher.view(), Ok(hex));
+    }
+
+    #[test]
+    fn switching_encoding_keeps_file_digest() {
+        let mut hasher = hasher_with_text("");
+        hasher.apply(Change::File(PathBuf::from("file")));
+        hasher.apply(Change::FileHashed(hasher.revision(), vec![vec![0xff]]));
+
+        hasher.apply(Change::Option(HashPreviewOption::HexUpper));
+        assert_eq!(hasher.view(), Ok("FF".to_string()));
+    }
+
+    #[test]
+    fn switching_input_encoding_recomputes_digest() {
+        let mut hasher = hasher_with_text("616263");
+        hasher.apply(Change::InputEncoding(TextEncoding::Hex));
+        let from_hex = hasher.view();
+
+        hasher.apply(Change::InputEncoding(TextEncoding::Utf8));
+        hasher.apply(Change::Text("abc".to_string()));
+        assert_eq!(hasher.view(), from_hex);
+    }
+
+    #[test]
+    fn normalization_makes_windows_text_hash_like_unix_text() {
+        let unix = hasher_with_text("й\nstring\n");
+        let mut windows = hasher_with_text("\u{feff}и\u{306}\r\nstring  \r\n");
+        assert_ne!(windows.view(), unix.view());
+
+        windows.apply(Change::Normalization(Normalization {
+            strip_bom: true,
+            crlf_to_lf: true,
+            trim_trailing: true,
+            unicode: UnicodeForm::Nfc,
+        }));
+        assert_eq!(windows.view(), unix.view());
+        assert!(windows.applied_normalization().is_some());
+    }
+
+    #[test]
+    fn normalization_does_not_apply_to_hex_input() {
+        let mut hasher = hasher_with_text("");
+        hasher.apply(Change::InputEncoding(TextEncoding::Hex));
+        hasher.apply(Change::Text("0d0a".to_string()));
+        let view = hasher.view();
+
+        hasher.apply(Change::Normalization(Normalization {
+            crlf_to_lf: true,
+            ..Default::default()
+        }));
+        assert_eq!(hasher.view(), view);
+        assert!(hasher.applied_normalization().is_none());
+    }
+
+    #[test]
+    fn base32_and_base58_known_answers() {
+        assert_eq!(
+            HashPreviewOption::Base32.encode(b"foobar"),
+            "MZXW6YTBOI======"
+        );
+        for text in ["MZXW6YTBOI======", "MZXW6YTBOI", "mzxw6ytboi"] {
+            assert_eq!(
+                HashPreviewOption::Base32.decode(text),
+                Some(b"foobar".to_vec())
+            );
+        }
+        assert_eq!(HashPreviewOption::Base32.decode("MZXW1"), None);
+
+        assert_eq!(
+            HashPreviewOption::Base58.encode(b"hello world"),
+            "StV1DL6CwTryKyV"
+        );
+        assert_eq!(
+            HashPreviewOption::Base58.decode(" StV1DL6CwTryKyV\n"),
+            Some(b"hello world".to_vec())
+        );
+        // В алфавите base58 нет нуля, 'O', 'I' и 'l'
+        assert_eq!(HashPreviewOption::Base58.decode("0OIl"), None);
+    }
+
+    #[test]
+    fn multibase_prefixes() {
+        let data = b"foobar".to_vec();
+        for text in [
+            "f666f6f626172",
+            "F666F6F626172",
+            "mZm9vYmFy",
+            "uZm9vYmFy",
+            "bmzxw6ytboi",
+            "zt1Zv2yaZ",
+        ] {
+            assert_eq!(
+                HashPreviewOption::Multibase.decode(text),
+                Some(data.clone()),
+                "{text}"
+            );
+        }
+        assert_eq!(HashPreviewOption::Multibase.encode(&data), "zt1Zv2yaZ");
+        assert_eq!(HashPreviewOption::Multibase.decode("x666f6f"), None);
+        assert_eq!(HashPreviewOption::Multibase.decode(""), None);
+    }
+
+    #[test]
+    fn varints() {
+        for (value, bytes) in [
+            (0, vec![0x00]),
+            (0x7f, vec![0x7f]),
+            (0x80, vec![0x80, 0x01]),
+            (300, vec![0xac, 0x02]),
+            (0x1e, vec![0x1e]),
+            (0xb220, vec![0xa0, 0xe4, 0x02]),
+        ] {
+            let mut written = vec![];
+            write_varint(value, &mut written);
+            assert_eq!(written, bytes, "{value}");
+            written.push(0xff);
+            assert_eq!(read_varint(&written), Some((value, &[0xff][..])), "{value}");
+        }
+        // Незаконченное число и число длиннее 9 байтов
+        assert_eq!(read_varint(&[0x80]), None);
+        assert_eq!(read_varint(&[0xff; 10]), None);
+    }
+
+    #[test]
+    fn multihash_round_trip() {
+        let digest = [0xab; 32];
+        let bytes = multihash(0x12, &digest);
+        assert_eq!(bytes[..2], [0x12, 0x20]);
+        let text = HashPreviewOption::Multihash.encode(&bytes);
+        assert!(text.starts_with("zQm"), "{text}");
+        assert_eq!(
+            HashPreviewOption::Multihash.decode(&text),
+            Some(digest.to_vec())
+        );
+
+        // Длина в заголовке не совпадает с хэшем
+        let text = HashPreviewOption::Multibase.encode(&bytes[..33]);
+        assert_eq!(HashPreviewOption::Multihash.decode(&text), None);
+    }
+
+    #[test]
+    fn multihash_view_needs_code() {
+        let mut hasher = hasher_with_text("abc");
+        hasher.apply(Change::Algo(HashAlgorythm::Sha256));
+        hasher.apply(Change::Option(HashPreviewOption::Multihash));
+        let view = hasher.view().unwrap();
+        assert_eq!(
+            HashPreviewOption::Multihash.decode(&view),
+            HashPreviewOption::Hex
+                .decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+
+        hasher.apply(Change::Key(Some(Key::new("key", TextEncoding::Utf8))));
+        assert!(matches!(hasher.view(), Err(HashError::NoMultihash(_))));
+    }
+}
@@ -0,0 +1,397 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::hasher::{HashPreviewOption, Hasher, Source};
+
+/// Сколько символов ввода показывать в истории. Длинный ввод обрезается.
+pub const PREVIEW_CHARS: usize = 40;
+
+/// Сколько незакреплённых записей хранить. Закреплённые не удаляются никогда.
+pub const LIMIT: usize = 100;
+
+/// Что делать с введённым текстом в истории. Хэш пароля или токена сам по себе
+/// тоже может быть секретом, поэтому такой ввод можно вообще не сохранять.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Privacy {
+    /// Показывать начало текста
+    Show,
+    /// Показывать только длину текста
+    Mask,
+    /// Не записывать хэши текста в историю (хэши файлов записываются)
+    Exclude,
+}
+
+impl Privacy {
+    pub const ALL: [Privacy; 3] = [Privacy::Show, Privacy::Mask, Privacy::Exclude];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Privacy::Show => "Показывать",
+            Privacy::Mask => "Скрывать",
+            Privacy::Exclude => "Не сохранять",
+        }
+    }
+
+    /// Названия для drop-down в том же порядке, что и в ALL
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|privacy| privacy.name()).collect()
+    }
+
+    /// Имя для файла истории
+    fn id(self) -> &'static str {
+        match self {
+            Privacy::Show => "show",
+            Privacy::Mask => "mask",
+            Privacy::Exclude => "exclude",
+        }
+    }
+}
+
+/// Запись истории. Введённый текст в ней уже обрезан или скрыт, поэтому
+/// полностью он не попадает ни в окно, ни в файл.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub input: String,
+    /// Функция с алгоритмом, например "SHA3-256" или "HMAC-SHA-256"
+    pub algorithm: String,
+    /// Шаги нормализации текста через запятую (пусто, если её не было)
+    pub normalization: String,
+    pub encoding: HashPreviewOption,
+    pub digest: String,
+    /// Время в секундах от 1970 года (UTC)
+    pub timestamp: u64,
+    pub pinned: bool,
+}
+
+impl Entry {
+    /// Запись для текущего хэша модели. None, если хэша нет или ввод исключён из истории.
+    pub fn from_hasher(hasher: &Hasher, privacy: Privacy, timestamp: u64) -> Option<Self> {
+        let digest = hasher.view().ok().filter(|digest| !digest.is_empty())?;
+        let input = match hasher.source() {
+            Source::File(_) => format!("Файл {}", hasher.source_name()?),
+            Source::Text => preview(hasher.text(), privacy)?,
+        };
+
+        Some(Self {
+            input,
+            algorithm: hasher.function_name(),
+            normalization: hasher
+                .applied_normalization()
+                .map(|normalization| normalization.steps().join(", "))
+                .unwrap_or_default(),
+            encoding: hasher.option(),
+            digest,
+            timestamp,
+            pinned: false,
+        })
+    }
+
+    /// Одна и та же ли это запись, если не считать времени и закрепления
+    fn same_as(&self, other: &Entry) -> bool {
+        self.input == other.input
+            && self.algorithm == other.algorithm
+            && self.normalization == other.normalization
+            && self.encoding == other.encoding
+            && self.digest == other.digest
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "input": self.input,
+            "algorithm": self.algorithm,
+            "normalization": self.normalization,
+            "encoding": self.encoding.name(),
+            "digest": self.digest,
+            "timestamp": self.timestamp,
+            "pinned": self.pinned,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            input: value["input"].as_str()?.to_string(),
+            algorithm: value["algorithm"].as_str()?.to_string(),
+            normalization: value["normalization"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            encoding: value["encoding"].as_str()?.parse().ok()?,
+            digest: value["digest"].as_str()?.to_string(),
+            timestamp: value["timestamp"].as_u64()?,
+            pinned: value["pinned"].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+/// Как показать введённый текст. Переводы строк заменяются на '⏎',
+/// чтобы запись помещалась в одну строку.
+pub fn preview(text: &str, privacy: Privacy) -> Option<String> {
+    let chars = text.chars().count();
+    match privacy {
+        Privacy::Exclude => None,
+        Privacy::Mask => Some(format!("•••••• ({chars} симв.)")),
+        Privacy::Show if chars > PREVIEW_CHARS => {
+            let start: String = text.chars().take(PREVIEW_CHARS).collect();
+            Some(format!("{}… ({chars} симв.)", start.replace('\n', "⏎")))
+        }
+        Privacy::Show => Some(text.replace('\n', "⏎")),
+    }
+}
+
+/// История хэшей, новые записи в начале
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct History {
+    entries: Vec<Entry>,
+    pub privacy: Privacy,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            privacy: Privacy::Show,
+        }
+    }
+}
+
+impl History {
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Записать текущий хэш модели. Возвращает false, если записывать нечего
+    /// или такая запись и так последняя.
+    pub fn record(&mut self, hasher: &Hasher, timestamp: u64) -> bool {
+        match Entry::from_hasher(hasher, self.privacy, timestamp) {
+            Some(entry)
+                if !self
+                    .entries
+                    .first()
+                    .is_some_and(|last| last.same_as(&entry)) =>
+            {
+                self.push(entry);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Добавить запись в начало. Такая же запись не дублируется, а поднимается наверх
+    /// (и остаётся закреплённой, если была). Старые незакреплённые записи сверх LIMIT удаляются.
+    pub fn push(&mut self, mut entry: Entry) {
+        if let Some(index) = self.entries.iter().position(|old| old.same_as(&entry)) {
+            entry.pinned |= self.entries.remove(index).pinned;
+        }
+        self.entries.insert(0, entry);
+
+        let mut unpinned = 0;
+        self.entries.retain(|entry| {
+            unpinned += usize::from(!entry.pinned);
+            entry.pinned || unpinned <= LIMIT
+        });
+    }
+
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.pinned = pinned;
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+        }
+    }
+
+    /// Удалить все записи, кроме закреплённых
+    pub fn clear(&mut self) {
+        self.entries.retain(|entry| entry.pinned);
+    }
+
+    pub fn to_json(&self) -> String {
+        let entries: Vec<_> = self.entries.iter().map(Entry::to_json).collect();
+        let document = json!({
+            "privacy": self.privacy.id(),
+            "entries": entries,
+        });
+        serde_json::to_string_pretty(&document).unwrap()
+    }
+
+    /// Прочитать историю из JSON. Записи, которые не удалось разобрать, пропускаются.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let document: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let privacy = Privacy::ALL
+            .into_iter()
+            .find(|privacy| document["privacy"].as_str() == Some(privacy.id()))
+            .unwrap_or(Privacy::Show);
+        let entries = document["entries"]
+            .as_array()
+            .ok_or("missing 'entries' array")?
+            .iter()
+            .filter_map(Entry::from_json)
+            .collect();
+
+        Ok(Self { entries, privacy })
+    }
+
+    /// Прочитать историю из файла. Если файла ещё нет, история пустая.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                Self::from_json(&text).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Сохранить историю в файл, создав каталог, если его нет
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorythm::HashAlgorythm;
+    use crate::hasher::Change;
+
+    fn entry(input: &str, timestamp: u64) -> Entry {
+        Entry {
+            input: input.to_string(),
+            algorithm: "SHA3-256".to_string(),
+            normalization: String::new(),
+            encoding: HashPreviewOption::Hex,
+            digest: format!("digest of {input}"),
+            timestamp,
+            pinned: false,
+        }
+    }
+
+    fn inputs(history: &History) -> Vec<&str> {
+        history
+            .entries()
+            .iter()
+            .map(|entry| entry.input.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn push_moves_duplicate_to_top() {
+        let mut history = History::default();
+        history.push(entry("a", 1));
+        history.push(entry("b", 2));
+        history.set_pinned(1, true);
+
+        history.push(entry("a", 3));
+        assert_eq!(inputs(&history), ["a", "b"]);
+        assert_eq!(history.entries()[0].timestamp, 3);
+        assert!(history.entries()[0].pinned);
+        assert!(!history.entries()[1].pinned);
+
+        // Другая кодировка -- уже другая запись
+        history.push(Entry {
+            encoding: HashPreviewOption::Base64Padded,
+            ..entry("a", 4)
+        });
+        assert_eq!(inputs(&history), ["a", "a", "b"]);
+    }
+
+    #[test]
+    fn limit_keeps_pinned_entries() {
+        let mut history = History::default();
+        history.push(entry("pinned", 0));
+        history.set_pinned(0, true);
+        for i in 0..LIMIT + 10 {
+            history.push(entry(&i.to_string(), i as u64 + 1));
+        }
+
+        let entries = history.entries();
+        assert_eq!(entries.len(), LIMIT + 1);
+        assert_eq!(entries[0].input, (LIMIT + 9).to_string());
+        assert_eq!(entries[LIMIT - 1].input, "10");
+        assert_eq!(entries[LIMIT].input, "pinned");
+
+        history.clear();
+        assert_eq!(inputs(&history), ["pinned"]);
+    }
+
+    #[test]
+    fn record_skips_repeats_and_excluded_text() {
+        let mut hasher = Hasher::new(HashAlgorythm::Sha3_256, HashPreviewOption::Hex);
+        let mut history = History::default();
+        assert!(!history.record(&hasher, 1));
+
+        hasher.apply(Change::Text("secret\npassword".to_string()));
+        assert!(history.record(&hasher, 1));
+        assert!(!history.record(&hasher, 2));
+        assert_eq!(inputs(&history), ["secret⏎password"]);
+
+        history.privacy = Privacy::Mask;
+        hasher.apply(Change::Text("other".to_string()));
+        assert!(history.record(&hasher, 3));
+        assert_eq!(history.entries()[0].input, "•••••• (5 симв.)");
+
+        history.privacy = Privacy::Exclude;
+        hasher.apply(Change::Text("third".to_string()));
+        assert!(!history.record(&hasher, 4));
+        assert_eq!(history.entries().len(), 2);
+    }
+
+    #[test]
+    fn preview_truncates_long_text() {
+        let text = "я".repeat(PREVIEW_CHARS + 1);
+        let expected = format!(
+            "{}… ({} симв.)",
+            "я".repeat(PREVIEW_CHARS),
+            PREVIEW_CHARS + 1
+        );
+        assert_eq!(preview(&text, Privacy::Show), Some(expected));
+        assert_eq!(preview(&text, Privacy::Exclude), None);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut history = History {
+            privacy: Privacy::Mask,
+            ..Default::default()
+        };
+        history.push(Entry {
+            normalization: "BOM, CRLF → LF".to_string(),
+            ..entry("a", 1)
+        });
+        history.push(entry("b", 2));
+        history.set_pinned(1, true);
+
+        assert_eq!(History::from_json(&history.to_json()), Ok(history));
+    }
+
+    #[test]
+    fn from_json_skips_broken_entries() {
+        let text = r#"{
+            "privacy": "unknown",
+            "entries": [
+                {"input": "a", "algorithm": "SHA-256", "encoding": "Hex",
+                 "digest": "00", "timestamp": 5},
+                {"input": "b", "algorithm": "SHA-256", "encoding": "no such encoding",
+                 "digest": "00", "timestamp": 5},
+                {"input": "c"}
+            ]
+        }"#;
+        let history = History::from_json(text).unwrap();
+        assert_eq!(history.privacy, Privacy::Show);
+        assert_eq!(inputs(&history), ["a"]);
+        assert_eq!(history.entries()[0].normalization, "");
+        assert!(!history.entries()[0].pinned);
+
+        assert!(History::from_json("{}").is_err());
+        assert!(History::from_json("not json").is_err());
+    }
+}
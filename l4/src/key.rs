@@ -0,0 +1,174 @@
+use std::fmt::{self, Display};
+
+use crate::hasher::TextEncoding;
+
+/// Ошибка ключа: его не удалось декодировать, или он не подходит алгоритму
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    /// Ключ записан не в той кодировке, которая выбрана
+    Decode(String),
+    /// Длина ключа не подходит: алгоритм, длина ключа, допустимая длина
+    Length(&'static str, usize, String),
+    /// У алгоритма нет режима с ключом
+    Unsupported(&'static str),
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::Decode(msg) => write!(f, "invalid key: {msg}"),
+            KeyError::Length(algo, len, expected) => {
+                write!(f, "{algo} key must be {expected} bytes, got {len}")
+            }
+            KeyError::Unsupported(algo) => write!(f, "{algo} has no keyed mode"),
+        }
+    }
+}
+
+/// Секретный ключ в том виде, в каком его ввёл пользователь
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub text: String,
+    pub encoding: TextEncoding,
+}
+
+impl Key {
+    pub fn new(text: &str, encoding: TextEncoding) -> Self {
+        Self {
+            text: text.to_string(),
+            encoding,
+        }
+    }
+
+    /// Байты ключа. Пробелы по краям у hex и base64 не учитываются,
+    /// а у UTF-8 считаются частью ключа.
+    pub fn bytes(&self) -> Result<Vec<u8>, KeyError> {
+        self.encoding.decode(&self.text).map_err(KeyError::Decode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorythm::HashAlgorythm;
+    use crate::hasher::{Change, HashError, HashPreviewOption, Hasher};
+
+    fn keyed(algo: HashAlgorythm, key: &[u8], data: &[u8]) -> Result<String, KeyError> {
+        let mut digest = algo.keyed_digest(key)?;
+        digest.update(data);
+        Ok(hex::encode(digest.finalize()))
+    }
+
+    /// RFC 4231, тесты 1-4, 6 и 7 (в пятом хэш обрезан до 128 бит)
+    #[test]
+    fn hmac_sha256_rfc4231() {
+        let long_key = [0xaa; 131];
+        let vectors: [(&[u8], &[u8], &str); 6] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                &[0xaa; 20],
+                &[0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+            (
+                &[
+                    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+                    0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+                ],
+                &[0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            ),
+            (
+                &long_key,
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+            (
+                &long_key,
+                b"This is a test using a larger than block-size key and a larger than \
+                  block-size data. The key needs to be hashed before being used by the \
+                  HMAC algorithm.",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ),
+        ];
+        for (key, data, expected) in vectors {
+            assert_eq!(
+                keyed(HashAlgorythm::Sha256, key, data),
+                Ok(expected.to_string())
+            );
+        }
+    }
+
+    /// Вектор из test_vectors.json проекта BLAKE3 для пустого сообщения
+    #[test]
+    fn blake3_keyed_hash() {
+        assert_eq!(
+            keyed(
+                HashAlgorythm::Blake3,
+                b"whats the Elvish word for friend",
+                b""
+            ),
+            Ok("92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26".to_string())
+        );
+    }
+
+    #[test]
+    fn key_length_is_checked() {
+        for len in [0, 16, 31, 33] {
+            assert_eq!(
+                keyed(HashAlgorythm::Blake3, &vec![0; len], b"").map(|_| ()),
+                Err(KeyError::Length("BLAKE3", len, "exactly 32".to_string()))
+            );
+        }
+        assert!(keyed(HashAlgorythm::Blake2s, &[0; 33], b"").is_err());
+        assert!(keyed(HashAlgorythm::Blake2b, &[0; 64], b"").is_ok());
+        assert_eq!(
+            keyed(HashAlgorythm::Shake128, b"key", b"").map(|_| ()),
+            Err(KeyError::Unsupported("SHAKE128"))
+        );
+        // HMAC подходит ключ любой длины, даже пустой
+        assert!(keyed(HashAlgorythm::Sha256, b"", b"").is_ok());
+    }
+
+    #[test]
+    fn wrong_key_is_shown_as_error() {
+        let mut hasher = Hasher::new(HashAlgorythm::Blake3, HashPreviewOption::Hex);
+        hasher.apply(Change::Text("abc".to_string()));
+        hasher.apply(Change::Key(Some(Key::new("short", TextEncoding::Utf8))));
+        assert!(matches!(
+            hasher.error(),
+            Some(HashError::Key(KeyError::Length("BLAKE3", 5, _)))
+        ));
+
+        hasher.apply(Change::Key(Some(Key::new("zz", TextEncoding::Hex))));
+        assert!(matches!(
+            hasher.error(),
+            Some(HashError::Key(KeyError::Decode(_)))
+        ));
+    }
+
+    #[test]
+    fn key_bytes() {
+        assert_eq!(
+            Key::new(" k ", TextEncoding::Utf8).bytes(),
+            Ok(b" k ".to_vec())
+        );
+        assert_eq!(
+            Key::new(" 4a65 ", TextEncoding::Hex).bytes(),
+            Ok(b"Je".to_vec())
+        );
+        assert_eq!(
+            Key::new("SmVmZQ", TextEncoding::Base64).bytes(),
+            Ok(b"Jefe".to_vec())
+        );
+    }
+}
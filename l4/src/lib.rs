@@ -0,0 +1,18 @@
+//! Хэширование строк и файлов: модель приложения, алгоритмы и способы записи хэша.
+//! Библиотека отделена от приложения (main.rs), чтобы командная строка работала
+//! без GTK, например на CI.
+
+pub mod algorythm;
+pub mod bench;
+pub mod cli;
+pub mod export;
+pub mod hasher;
+pub mod history;
+pub mod key;
+pub mod manifest;
+pub mod normalize;
+pub mod password;
+pub mod qr;
+pub mod state;
+pub mod stream;
+pub mod tree;
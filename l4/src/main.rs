@@ -1,191 +1,223 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use gtk::pango::WrapMode;
-use gtk::{glib, Application, ApplicationWindow, CheckButton, DropDown, Label, Orientation};
-use gtk::{prelude::*, Entry};
-use gtk4 as gtk;
-
-use base64::{engine::general_purpose, Engine as _};
-use sha3::{Digest, Sha3_224, Sha3_256, Sha3_384, Sha3_512};
-
-/// Способ отображения хэшированных данных
-#[derive(Clone, Copy)]
-pub enum HashPreviewOption {
-    Base64,
-    Hex,
+#[cfg(feature = "gui")]
+mod gui;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser as _, Subcommand};
+
+use l4::algorythm::HashAlgorythm;
+use l4::cli::{self, CostOptions};
+use l4::hasher::{HashPreviewOption, TextEncoding};
+use l4::key::Key;
+use l4::password::PasswordAlgorithm;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    about = "Хэширование строк и файлов. Без команды запускается графический интерфейс.",
+    long_about = None
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-/// Хэш-алгоритм
-#[derive(Clone, Copy)]
-pub enum HashAlgorythm {
-    Sha3_224,
-    Sha3_256,
-    Sha3_384,
-    Sha3_512,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Хэши файлов или стандартного ввода в формате coreutils (как у sha256sum)
+    Hash {
+        /// Алгоритм, например sha-256, sha3-384 или blake3
+        #[arg(short, long, default_value = "sha3-256")]
+        algo: HashAlgorythm,
+
+        /// Длина хэша в байтах для SHAKE128 и SHAKE256
+        #[arg(short, long)]
+        length: Option<usize>,
+
+        /// Как вывести результат: hex, hex-upper, hex-colon, base64, base64-padded,
+        /// base64url, base32, base58, multibase или multihash
+        #[arg(short, long, default_value = "hex")]
+        encoding: HashPreviewOption,
+
+        /// Файлы. Без файлов или с "-" читается стандартный ввод
+        files: Vec<PathBuf>,
+    },
+    /// HMAC (для BLAKE2 и BLAKE3 -- их собственный режим с ключом) файлов или стандартного ввода
+    Hmac {
+        /// Алгоритм, например sha-256, sha3-256 или blake3
+        #[arg(short, long, default_value = "sha-256")]
+        algo: HashAlgorythm,
+
+        /// Секретный ключ
+        #[arg(
+            short,
+            long,
+            conflicts_with = "key_file",
+            required_unless_present = "key_file"
+        )]
+        key: Option<String>,
+
+        /// Файл с секретным ключом (так ключ не попадёт в историю командной строки)
+        #[arg(long, value_name = "FILE")]
+        key_file: Option<PathBuf>,
+
+        /// Как записан ключ: utf8, hex или base64
+        #[arg(long, default_value = "utf8")]
+        key_encoding: TextEncoding,
+
+        /// Как вывести результат: hex, hex-upper, hex-colon, base64, base64-padded,
+        /// base64url, base32, base58 или multibase
+        #[arg(short, long, default_value = "hex")]
+        encoding: HashPreviewOption,
+
+        /// Файлы. Без файлов или с "-" читается стандартный ввод
+        files: Vec<PathBuf>,
+    },
+    /// Хэш-дерево (дерево Меркла) каталога и сравнение со снимком
+    Tree {
+        /// Каталог
+        dir: PathBuf,
+
+        /// Алгоритм. При сравнении со снимком берётся алгоритм снимка.
+        #[arg(short, long, default_value = "sha3-256")]
+        algo: HashAlgorythm,
+
+        /// Учитывать размер, права и время изменения файлов, а не только содержимое
+        #[arg(short, long)]
+        metadata: bool,
+
+        /// Как вывести корень дерева
+        #[arg(short, long, default_value = "hex")]
+        encoding: HashPreviewOption,
+
+        /// Сохранить снимок каталога в файл
+        #[arg(long, value_name = "BASELINE")]
+        save: Option<PathBuf>,
+
+        /// Сравнить каталог со снимком и вывести добавленные, удалённые и изменённые файлы
+        #[arg(long, value_name = "BASELINE")]
+        compare: Option<PathBuf>,
+    },
+    /// Хэширование паролей: Argon2id, scrypt и bcrypt. Пароль читается со стандартного ввода.
+    #[command(subcommand)]
+    Password(PasswordCommand),
+    /// Проверить файлы по манифесту с контрольными суммами (SHA256SUMS, SHA3-256SUMS, B2SUMS)
+    Check {
+        /// Алгоритм, если его нельзя понять ни по строкам манифеста, ни по его имени
+        #[arg(short, long, default_value = "sha3-256")]
+        algo: HashAlgorythm,
+
+        /// Манифесты в формате coreutils
+        #[arg(required = true)]
+        manifests: Vec<PathBuf>,
+    },
 }
 
-/// Список хэш-алгортмов для drop-down
-const HASH_ALGORYTHMS: [&str; 4] = ["SHA3-224", "SHA3-256", "SHA3-384", "SHA3-512"];
-
-/// Модель приложения
-#[derive(Clone)]
-pub struct Hasher {
-    pub algo: HashAlgorythm,
-    pub option: HashPreviewOption,
-    pub text: String,
-    data: Vec<u8>,
+#[derive(Subcommand, Debug)]
+enum PasswordCommand {
+    /// Захэшировать пароль и вывести строку в формате PHC
+    Hash {
+        /// Алгоритм: argon2id, scrypt или bcrypt
+        #[arg(short, long, default_value = "argon2id")]
+        algo: PasswordAlgorithm,
+
+        #[command(flatten)]
+        cost: CostOptions,
+
+        /// Соль. Без неё берётся случайная, так и надо делать для настоящих паролей.
+        #[arg(long)]
+        salt: Option<String>,
+
+        /// Как записана соль: utf8, hex или base64
+        #[arg(long, default_value = "utf8")]
+        salt_encoding: TextEncoding,
+    },
+    /// Проверить пароль по строке хэша
+    Verify {
+        /// Строка хэша, например $argon2id$v=19$... или $2b$10$...
+        hash: String,
+    },
+    /// Подобрать параметры, с которыми один хэш считается на этой машине за заданное время
+    Tune {
+        /// Алгоритм: argon2id, scrypt или bcrypt
+        #[arg(short, long, default_value = "argon2id")]
+        algo: PasswordAlgorithm,
+
+        /// Желаемое время одного хэша в миллисекундах
+        #[arg(short, long, default_value_t = 500)]
+        target_ms: u64,
+    },
 }
 
-impl Hasher {
-    pub fn new(algo: HashAlgorythm, option: HashPreviewOption) -> Self {
-        Self {
-            algo,
-            option,
-            text: "".to_string(),
-            data: vec![],
-        }
-    }
-
-    /// Захэшировать текст в зависимости от свойства algo
-    pub fn calculate(&mut self) {
-        // Если текста нет, ничего не делать
-        if self.text.is_empty() {
-            return;
-        }
-
-        self.data = match self.algo {
-            HashAlgorythm::Sha3_224 => Sha3_224::new().chain_update(&self.text).finalize().to_vec(),
-            HashAlgorythm::Sha3_256 => Sha3_256::new().chain_update(&self.text).finalize().to_vec(),
-            HashAlgorythm::Sha3_384 => Sha3_384::new().chain_update(&self.text).finalize().to_vec(),
-            HashAlgorythm::Sha3_512 => Sha3_512::new().chain_update(&self.text).finalize().to_vec(),
-        };
-    }
+fn main() -> ExitCode {
+    let args = Args::parse();
 
-    /// Отобразить захэшированный текст в зависимости от свойства option
-    /// Если текста нет, то ничего не произойдёт
-    pub fn view(&self) -> String {
-        // Если хэша, ничего не делать
-        if self.data.is_empty() {
-            return "".to_string();
+    match args.command {
+        None => run_gui(),
+        Some(Command::Hash {
+            algo,
+            length,
+            encoding,
+            files,
+        }) => cli::hash(algo, length, encoding, &files),
+        Some(Command::Hmac {
+            algo,
+            key,
+            key_file,
+            key_encoding,
+            encoding,
+            files,
+        }) => match cli::read_key(key, key_file, key_encoding) {
+            Ok(key) => cli::hmac(algo, &key, encoding, &files),
+            Err(e) => {
+                eprintln!("Ошибка: {e}");
+                ExitCode::FAILURE
+            }
+        },
+        Some(Command::Check { algo, manifests }) => cli::check(&manifests, algo),
+        Some(Command::Password(PasswordCommand::Hash {
+            algo,
+            cost,
+            salt,
+            salt_encoding,
+        })) => {
+            let salt = salt.map(|salt| Key::new(&salt, salt_encoding));
+            cli::hash_password(algo, &cost, salt.as_ref())
         }
-
-        match self.option {
-            HashPreviewOption::Base64 => general_purpose::STANDARD_NO_PAD.encode(&self.data),
-            HashPreviewOption::Hex => hex::encode(&self.data),
+        Some(Command::Password(PasswordCommand::Verify { hash })) => cli::verify_password(&hash),
+        Some(Command::Password(PasswordCommand::Tune { algo, target_ms })) => {
+            cli::tune_password(algo, Duration::from_millis(target_ms))
         }
+        Some(Command::Tree {
+            dir,
+            algo,
+            metadata,
+            encoding,
+            save,
+            compare,
+        }) => cli::tree(
+            &dir,
+            algo,
+            metadata,
+            encoding,
+            save.as_deref(),
+            compare.as_deref(),
+        ),
     }
 }
 
-fn main() -> glib::ExitCode {
-    let app = Application::builder()
-        .application_id("org.hasher.myhashapp")
-        .build();
-
-    app.connect_activate(|app| ui(app));
-    app.run()
+#[cfg(feature = "gui")]
+fn run_gui() -> ExitCode {
+    gui::run()
 }
 
-fn ui(app: &Application) {
-    // Соответствующие GUI элементы
-    let input_text_entry = Entry::builder().build();
-    let hash_algo_drop_down = DropDown::from_strings(&HASH_ALGORYTHMS);
-    let hash_option_check_box = CheckButton::with_label("Base64");
-    let hash_value_label = Label::builder()
-        .wrap(true)
-        .wrap_mode(WrapMode::Char)
-        .selectable(true)
-        .width_chars(64)
-        .max_width_chars(64)
-        .build();
-
-    // Контейнер для всех элементов
-    let container = gtk::Box::builder()
-        .orientation(Orientation::Vertical)
-        .margin_top(12)
-        .margin_bottom(12)
-        .margin_start(12)
-        .margin_end(12)
-        .spacing(12)
-        .build();
-    container.append(&input_text_entry);
-    container.append(&hash_algo_drop_down);
-    container.append(&hash_option_check_box);
-    container.append(&Label::new(Some("Значение хэша:")));
-    container.append(&hash_value_label);
-
-    // Мы создаём счётчик ссылок Rc и вкладываем в AppState возможность
-    // изменяться (mutate) в нескольких местах RefCell (Rust по умолчанию запрещает такое).
-    let hasher = Rc::new(RefCell::new(Hasher::new(
-        HashAlgorythm::Sha3_256,
-        HashPreviewOption::Hex,
-    )));
-
-    // Подписываемся на событие изменения текстового ввода
-    input_text_entry.connect_changed(
-        // Это капец. GTK это ООП-библиотека на чистом C, что уже звучит страшно.
-        // А Rust язык с очень строгой типизацией и статической сборкой мусора (без сборщика мусора).
-        // Из-за этого для "удобства" вводится такой макрос, который клонирует объекты, которые можно
-        // изменять в нескольких местах (грубо говоря).
-        // Я не думаю, что я в ближайшее время буду пробовать GTK ещё раз, в общем.
-        glib::clone!(@strong hasher, @weak hash_value_label => move |entry| {
-            let text = entry.text();
-
-            // Одалживаем состояние приложения
-            let mut hasher = hasher.borrow_mut();
-
-            // Сохраняем текст
-            hasher.text = text.to_string();
-
-            // Хэшируем текст и отображаем хэш в интерфейсе
-            hasher.calculate();
-            hash_value_label.set_label(&hasher.view());
-        }),
-    );
-
-    // Подписываемся на событие выбора варианта из выпадающего списка
-    hash_algo_drop_down.connect_selected_notify(
-        glib::clone!(@strong hasher, @weak hash_value_label => move |drop_down| {
-            // Одалживаем состояние приложения
-            let mut hasher = hasher.borrow_mut();
-
-            // Выбираем алгоритм в соответствии с номером варианта из списка
-            hasher.algo = match drop_down.selected() {
-                0 => HashAlgorythm::Sha3_224,
-                1 => HashAlgorythm::Sha3_256,
-                2 => HashAlgorythm::Sha3_384,
-                3 => HashAlgorythm::Sha3_512,
-                _ => panic!("There is no such drop down value!"),
-            };
-
-            // Хэшируем текст и отображаем хэш в интерфейсе
-            hasher.calculate();
-            hash_value_label.set_label(&hasher.view());
-        }),
-    );
-
-    // Подписываемся на событие нажатия на кнопку
-    hash_option_check_box.connect_toggled(move |button| {
-        // Одалживаем состояние приложения
-        let mut hasher = hasher.borrow_mut();
-
-        // Выбираем алгоритм отображения
-        hasher.option = if button.is_active() {
-            HashPreviewOption::Base64
-        } else {
-            HashPreviewOption::Hex
-        };
-
-        // Хэшируем текст и отображаем хэш в интерфейсе
-        hasher.calculate();
-        hash_value_label.set_label(&hasher.view());
-    });
-
-    // Запихиваем всё в окно и показываем его
-    let window = ApplicationWindow::builder()
-        .application(app)
-        .title("SHA3 hasher")
-        .child(&container)
-        .build();
-    window.present();
+/// Без GTK (например, на CI) остаётся только командная строка
+#[cfg(not(feature = "gui"))]
+fn run_gui() -> ExitCode {
+    use clap::CommandFactory as _;
+
+    eprintln!("Программа собрана без графического интерфейса (его включает feature \"gui\"), укажите команду.\n");
+    let _ = Args::command().print_help();
+    ExitCode::FAILURE
 }
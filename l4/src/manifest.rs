@@ -0,0 +1,355 @@
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::algorythm::{normalize, HashAlgorythm};
+use crate::hasher::HashPreviewOption;
+use crate::stream::{hash_reader, Progress};
+
+/// Строка файла с контрольными суммами (SHA256SUMS, SHA3-256SUMS, B2SUMS, ...)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// Имя файла так, как оно записано в манифесте
+    pub name: String,
+    /// Путь к файлу относительно каталога манифеста
+    pub path: PathBuf,
+    pub algo: HashAlgorythm,
+    pub expected: Vec<u8>,
+}
+
+/// Ошибка чтения манифеста
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(io::Error),
+    /// Строка с таким номером (с единицы) не похожа на контрольную сумму
+    Syntax(usize, String),
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(e) => write!(f, "{e}"),
+            ManifestError::Syntax(line, msg) => write!(f, "line {line}: {msg}"),
+        }
+    }
+}
+
+impl From<io::Error> for ManifestError {
+    fn from(e: io::Error) -> Self {
+        ManifestError::Io(e)
+    }
+}
+
+/// Результат проверки одного файла
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Failed,
+    /// Файл не удалось прочитать
+    Unreadable(String),
+}
+
+impl Display for Status {
+    /// Как в выводе `sha256sum --check`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "OK"),
+            Status::Failed => write!(f, "FAILED"),
+            Status::Unreadable(_) => write!(f, "FAILED open or read"),
+        }
+    }
+}
+
+/// Прочитать манифест. Алгоритм берётся из строки (формат с тегом BSD),
+/// потом из имени манифеста, а если не получилось -- `default`.
+pub fn load(path: &Path, default: HashAlgorythm) -> Result<Vec<Entry>, ManifestError> {
+    let text = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let manifest_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    parse(&text, dir, &manifest_name, default)
+}
+
+/// Разобрать строки манифеста. Понимаются оба формата coreutils:
+/// `хэш  имя` (или `хэш *имя` для двоичного режима) и `АЛГОРИТМ (имя) = хэш`.
+pub fn parse(
+    text: &str,
+    dir: &Path,
+    manifest_name: &str,
+    default: HashAlgorythm,
+) -> Result<Vec<Entry>, ManifestError> {
+    let mut entries = vec![];
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let syntax = |msg: &str| ManifestError::Syntax(number + 1, msg.to_string());
+
+        // Имена с переводом строки или обратной косой чертой coreutils экранирует
+        // и ставит '\' в начало строки
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        // Сначала формат без тега: иначе имя файла вроде `x (1) = y` приняли бы за тег.
+        // Строку с тегом за него не принять, потому что после тега идёт ` (`.
+        let (tag, name, digest) = match (parse_untagged(line), parse_tagged(line)) {
+            (Ok((digest, name)), _) => (None, name, digest),
+            (Err(_), Some((tag, name, digest))) => {
+                (Some(parse_tag(tag).map_err(|e| syntax(&e))?), name, digest)
+            }
+            (Err(msg), None) => return Err(syntax(msg)),
+        };
+
+        let expected = decode(digest).ok_or_else(|| syntax("unrecognized digest encoding"))?;
+        let name = if escaped {
+            unescape(name)
+        } else {
+            name.to_string()
+        };
+        let algo = tag
+            .or_else(|| guess_algorithm(manifest_name, expected.len()))
+            .unwrap_or(default);
+
+        // Укороченные хэши (`b2sum -l 256`) не поддерживаются: такой файл
+        // иначе всегда считался бы изменённым
+        let len = algo.default_output_len();
+        if !algo.is_xof() && expected.len() != len {
+            return Err(syntax(&format!(
+                "{algo} digest must be {len} bytes, got {} (truncated digests are not supported)",
+                expected.len()
+            )));
+        }
+
+        entries.push(Entry {
+            path: dir.join(&name),
+            name,
+            algo,
+            expected,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Хэш в любом виде из HashPreviewOption
+fn decode(digest: &str) -> Option<Vec<u8>> {
+    HashPreviewOption::ALL
+        .iter()
+        .find_map(|option| option.decode(digest))
+}
+
+/// Строка вида `хэш  имя` или `хэш *имя`: хэш и имя файла
+fn parse_untagged(line: &str) -> Result<(&str, &str), &'static str> {
+    let (digest, rest) = line.split_once(' ').ok_or("expected '<digest>  <file>'")?;
+    // Второй символ -- пробел (текстовый режим) или '*' (двоичный)
+    let name = rest
+        .strip_prefix(' ')
+        .or_else(|| rest.strip_prefix('*'))
+        .ok_or("expected two spaces or ' *' after the digest")?;
+    decode(digest).ok_or("unrecognized digest encoding")?;
+    Ok((digest, name))
+}
+
+/// Алгоритм по тегу BSD. `b2sum -l 256 --tag` пишет тег `BLAKE2b-256`.
+fn parse_tag(tag: &str) -> Result<HashAlgorythm, String> {
+    match tag.strip_prefix("BLAKE2b-") {
+        Some(bits) if bits.parse::<usize>().is_ok() => Ok(HashAlgorythm::Blake2b),
+        _ => tag.parse(),
+    }
+}
+
+/// Строка вида `SHA256 (имя) = хэш`
+fn parse_tagged(line: &str) -> Option<(&str, &str, &str)> {
+    let (tag, rest) = line.split_once(" (")?;
+    let (name, digest) = rest.rsplit_once(") = ")?;
+    Some((tag, name, digest))
+}
+
+/// Строка манифеста `хэш  имя` так, как её пишет coreutils: если в имени есть
+/// перевод строки или '\\', они экранируются, а в начало строки ставится '\\'
+pub fn format_line(digest: &str, name: &str) -> String {
+    if name.contains(['\\', '\n']) {
+        let name = name.replace('\\', "\\\\").replace('\n', "\\n");
+        format!("\\{digest}  {name}")
+    } else {
+        format!("{digest}  {name}")
+    }
+}
+
+fn unescape(name: &str) -> String {
+    let mut result = String::new();
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Угадать алгоритм по имени манифеста: SHA256SUMS, sha3-384sums.txt, B2SUMS.
+/// Для SHA3SUMS, где размер не указан, он определяется по длине хэша.
+pub fn guess_algorithm(manifest_name: &str, digest_len: usize) -> Option<HashAlgorythm> {
+    let name = normalize(manifest_name);
+
+    // Самое длинное подходящее название, чтобы SHA3-256 не принять за что-то короче
+    let by_name = HashAlgorythm::ALL
+        .into_iter()
+        .filter(|algo| name.starts_with(&normalize(algo.name())))
+        .max_by_key(|algo| algo.name().len());
+    if by_name.is_some() {
+        return by_name;
+    }
+
+    // Имена файлов, которые создают b2sum и b3sum
+    if name.starts_with("b2") {
+        return Some(HashAlgorythm::Blake2b);
+    }
+    if name.starts_with("b3") {
+        return Some(HashAlgorythm::Blake3);
+    }
+
+    let family = if name.starts_with("sha3") {
+        "SHA3-"
+    } else if name.starts_with("sha2") {
+        "SHA-"
+    } else {
+        return None;
+    };
+    format!("{family}{}", digest_len * 8).parse().ok()
+}
+
+/// Проверить файл из манифеста. Отмена через `progress` считается ошибкой чтения.
+pub fn check(entry: &Entry, progress: &Progress) -> Status {
+    // У SHAKE длина хэша не фиксирована, поэтому она берётся из манифеста
+    let digest = entry.algo.digest(entry.expected.len());
+
+    match File::open(&entry.path).and_then(|file| hash_reader(file, digest, progress)) {
+        Ok(data) if data == entry.expected => Status::Ok,
+        Ok(_) => Status::Failed,
+        Err(e) => Status::Unreadable(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA-256 и BLAKE2b от "abc"
+    const SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    const BLAKE2B: &str = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                           7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+
+    fn parse_one(line: &str, manifest_name: &str) -> Result<Entry, ManifestError> {
+        let mut entries = parse(line, Path::new("dir"), manifest_name, HashAlgorythm::Sha256)?;
+        assert_eq!(entries.len(), 1);
+        Ok(entries.remove(0))
+    }
+
+    #[test]
+    fn untagged_lines() {
+        let entry = parse_one(&format!("{SHA256}  a.txt"), "SUMS").unwrap();
+        assert_eq!(entry.name, "a.txt");
+        assert_eq!(entry.path, Path::new("dir").join("a.txt"));
+        assert_eq!(entry.algo, HashAlgorythm::Sha256);
+        assert_eq!(entry.expected, hex::decode(SHA256).unwrap());
+
+        let entry = parse_one(&format!("{SHA256} *b i n\r"), "SUMS").unwrap();
+        assert_eq!(entry.name, "b i n");
+    }
+
+    #[test]
+    fn tagged_lines() {
+        let entry = parse_one(&format!("SHA256 (a (1).txt) = {SHA256}"), "SUMS").unwrap();
+        assert_eq!(entry.name, "a (1).txt");
+        assert_eq!(entry.algo, HashAlgorythm::Sha256);
+
+        let entry = parse_one(&format!("BLAKE2b (a) = {BLAKE2B}"), "SHA256SUMS").unwrap();
+        assert_eq!(entry.algo, HashAlgorythm::Blake2b);
+    }
+
+    #[test]
+    fn names_that_look_like_tags() {
+        let entry = parse_one(&format!("{SHA256}  x (1) = y"), "SUMS").unwrap();
+        assert_eq!(entry.name, "x (1) = y");
+        assert_eq!(entry.algo, HashAlgorythm::Sha256);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let text = format!("# comment\n\n{SHA256}  a\n  \n{SHA256}  b\n");
+        let entries = parse(&text, Path::new(""), "SUMS", HashAlgorythm::Sha256).unwrap();
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn bad_lines_are_rejected() {
+        for line in [
+            "nonsense",
+            &format!("{SHA256} a"),
+            "zz  a",
+            &format!("MD5 (a) = {SHA256}"),
+        ] {
+            assert!(
+                matches!(parse_one(line, "SUMS"), Err(ManifestError::Syntax(1, _))),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_blake2b_is_rejected() {
+        let digest = &BLAKE2B[..64];
+        assert!(parse_one(&format!("{digest}  a"), "B2SUMS").is_err());
+        assert!(parse_one(&format!("BLAKE2b-256 (a) = {digest}"), "SUMS").is_err());
+        assert!(parse_one(&format!("BLAKE2b-512 (a) = {BLAKE2B}"), "SUMS").is_ok());
+    }
+
+    #[test]
+    fn escaped_names() {
+        let line = format_line(SHA256, "a\\b\nc");
+        assert_eq!(line, format!("\\{SHA256}  a\\\\b\\nc"));
+        assert_eq!(parse_one(&line, "SUMS").unwrap().name, "a\\b\nc");
+
+        assert_eq!(format_line(SHA256, "plain"), format!("{SHA256}  plain"));
+        assert_eq!(unescape("a\\\\n"), "a\\n");
+        assert_eq!(unescape("trailing\\"), "trailing\\");
+        assert_eq!(unescape("\\x"), "\\x");
+    }
+
+    #[test]
+    fn algorithm_from_manifest_name() {
+        let cases = [
+            ("SHA256SUMS", 32, Some(HashAlgorythm::Sha256)),
+            ("sha3-384sums.txt", 48, Some(HashAlgorythm::Sha3_384)),
+            ("SHA3-256SUMS", 32, Some(HashAlgorythm::Sha3_256)),
+            ("SHA3SUMS", 64, Some(HashAlgorythm::Sha3_512)),
+            ("SHA2SUMS", 48, Some(HashAlgorythm::Sha384)),
+            ("B2SUMS", 64, Some(HashAlgorythm::Blake2b)),
+            ("b3sums", 32, Some(HashAlgorythm::Blake3)),
+            ("SHA3SUMS", 20, None),
+            ("checksums.txt", 32, None),
+        ];
+        for (name, len, algo) in cases {
+            assert_eq!(guess_algorithm(name, len), algo, "{name}");
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Метка порядка байтов (BOM), которую Блокнот Windows ставит в начало файлов UTF-8
+const BOM: char = '\u{feff}';
+
+/// Нормальная форма Unicode. Одна и та же буква может быть записана одним символом
+/// («й», NFC) или буквой с отдельным знаком («и» + «̆», NFD): на экране их не отличить,
+/// а байты и хэши разные. macOS, например, хранит имена файлов в NFD.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnicodeForm {
+    /// Оставить как есть
+    #[default]
+    Unchanged,
+    Nfc,
+    Nfd,
+}
+
+impl UnicodeForm {
+    pub const ALL: [UnicodeForm; 3] = [UnicodeForm::Unchanged, UnicodeForm::Nfc, UnicodeForm::Nfd];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UnicodeForm::Unchanged => "Unicode как есть",
+            UnicodeForm::Nfc => "NFC",
+            UnicodeForm::Nfd => "NFD",
+        }
+    }
+
+    /// Названия для drop-down в том же порядке, что и в ALL
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|form| form.name()).collect()
+    }
+}
+
+/// Что сделать с текстом перед хэшированием, чтобы один и тот же документ,
+/// сохранённый в Windows и в Linux, давал один хэш
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Normalization {
+    /// Убрать BOM в начале текста
+    pub strip_bom: bool,
+    /// Переводы строк Windows (CRLF) заменить на LF
+    pub crlf_to_lf: bool,
+    /// Убрать пробелы и табуляции в конце строк
+    pub trim_trailing: bool,
+    pub unicode: UnicodeForm,
+}
+
+impl Normalization {
+    /// Ничего не меняется
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Нормализовать текст. Порядок шагов фиксирован: BOM, переводы строк,
+    /// пробелы в конце строк, форма Unicode.
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = if self.strip_bom {
+            text.strip_prefix(BOM).unwrap_or(text).to_string()
+        } else {
+            text.to_string()
+        };
+        if self.crlf_to_lf {
+            text = text.replace("\r\n", "\n");
+        }
+        if self.trim_trailing {
+            text = text
+                .split('\n')
+                .map(|line| match line.strip_suffix('\r') {
+                    // Перевод строки CRLF остаётся, если его не просили заменить
+                    Some(line) => format!("{}\r", line.trim_end_matches([' ', '\t'])),
+                    None => line.trim_end_matches([' ', '\t']).to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        match self.unicode {
+            UnicodeForm::Unchanged => text,
+            UnicodeForm::Nfc => text.nfc().collect(),
+            UnicodeForm::Nfd => text.nfd().collect(),
+        }
+    }
+
+    /// Короткие названия включённых шагов, чтобы показать их рядом с хэшем
+    pub fn steps(&self) -> Vec<&'static str> {
+        let mut steps = vec![];
+        if self.strip_bom {
+            steps.push("без BOM");
+        }
+        if self.crlf_to_lf {
+            steps.push("CRLF→LF");
+        }
+        if self.trim_trailing {
+            steps.push("без пробелов в конце строк");
+        }
+        if self.unicode != UnicodeForm::Unchanged {
+            steps.push(self.unicode.name());
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_changes_nothing() {
+        let normalization = Normalization::default();
+        assert!(normalization.is_empty());
+        assert!(normalization.steps().is_empty());
+
+        let text = "\u{feff}и\u{306} \r\n";
+        assert_eq!(normalization.apply(text), text);
+        assert!(!Normalization {
+            unicode: UnicodeForm::Nfc,
+            ..Default::default()
+        }
+        .is_empty());
+    }
+
+    #[test]
+    fn bom_is_stripped_only_at_start() {
+        let normalization = Normalization {
+            strip_bom: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.apply("\u{feff}a\u{feff}b"), "a\u{feff}b");
+        assert_eq!(normalization.apply("a\u{feff}"), "a\u{feff}");
+        assert_eq!(normalization.apply("\u{feff}\u{feff}"), "\u{feff}");
+    }
+
+    #[test]
+    fn crlf_to_lf() {
+        let normalization = Normalization {
+            crlf_to_lf: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.apply("a\r\nb\r\n"), "a\nb\n");
+        // Одиночный CR -- не перевод строки Windows
+        assert_eq!(normalization.apply("a\rb\n\r"), "a\rb\n\r");
+    }
+
+    #[test]
+    fn trailing_whitespace_is_trimmed() {
+        let normalization = Normalization {
+            trim_trailing: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.apply("a \t\n  b\t\nc  "), "a\n  b\nc");
+        // Без замены переводов строк CRLF остаётся на месте
+        assert_eq!(normalization.apply("a \r\nb\t\r\n"), "a\r\nb\r\n");
+        // Другие пробельные символы не трогаются
+        assert_eq!(normalization.apply("a\u{a0}\n"), "a\u{a0}\n");
+    }
+
+    #[test]
+    fn unicode_forms_round_trip() {
+        let nfc = Normalization {
+            unicode: UnicodeForm::Nfc,
+            ..Default::default()
+        };
+        let nfd = Normalization {
+            unicode: UnicodeForm::Nfd,
+            ..Default::default()
+        };
+        assert_eq!(nfc.apply("и\u{306}"), "й");
+        assert_eq!(nfd.apply("й"), "и\u{306}");
+        assert_eq!(nfc.apply(&nfd.apply("йод")), "йод");
+        assert_eq!(nfc.apply("й"), "й");
+    }
+
+    #[test]
+    fn steps_run_in_fixed_order() {
+        let all = Normalization {
+            strip_bom: true,
+            crlf_to_lf: true,
+            trim_trailing: true,
+            unicode: UnicodeForm::Nfc,
+        };
+        // Пробелы перед CRLF убираются уже после замены переводов строк,
+        // а BOM -- до приведения к NFC
+        assert_eq!(all.apply("\u{feff}и\u{306} \r\nb\t\r\n"), "й\nb\n");
+        assert_eq!(
+            all.steps(),
+            ["без BOM", "CRLF→LF", "без пробелов в конце строк", "NFC"]
+        );
+    }
+}
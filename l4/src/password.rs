@@ -0,0 +1,407 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use argon2::Argon2;
+use password_hash::rand_core::{OsRng, RngCore};
+use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use scrypt::Scrypt;
+
+/// Алгоритм хэширования паролей. В отличие от SHA3 и других быстрых хэшей, они
+/// специально медленные (а Argon2id и scrypt ещё и требуют много памяти), поэтому
+/// перебор паролей по утёкшей базе обходится дорого.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasswordAlgorithm {
+    Argon2id,
+    Scrypt,
+    Bcrypt,
+}
+
+impl PasswordAlgorithm {
+    pub const ALL: [PasswordAlgorithm; 3] = [
+        PasswordAlgorithm::Argon2id,
+        PasswordAlgorithm::Scrypt,
+        PasswordAlgorithm::Bcrypt,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PasswordAlgorithm::Argon2id => "Argon2id",
+            PasswordAlgorithm::Scrypt => "scrypt",
+            PasswordAlgorithm::Bcrypt => "bcrypt",
+        }
+    }
+
+    /// Названия для drop-down в том же порядке, что и в ALL
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|algo| algo.name()).collect()
+    }
+}
+
+impl Display for PasswordAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for PasswordAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|algo| algo.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!("unknown password algorithm '{s}', expected argon2id, scrypt or bcrypt")
+            })
+    }
+}
+
+/// Параметры стоимости: сколько времени и памяти уходит на один хэш
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Params {
+    Argon2id {
+        /// Память в КиБ
+        memory_kib: u32,
+        iterations: u32,
+        /// Число параллельных дорожек
+        parallelism: u32,
+    },
+    Scrypt {
+        /// Логарифм N: память и время растут как 2^log_n
+        log_n: u8,
+        /// Размер блока
+        r: u32,
+        /// Число независимых проходов
+        p: u32,
+    },
+    Bcrypt {
+        /// Логарифм числа раундов, от 4 до 31
+        cost: u32,
+    },
+}
+
+/// Самые маленькие параметры, с которых начинает подбор
+const MIN_ARGON2_MEMORY_KIB: u32 = 8 * 1024;
+const MIN_SCRYPT_LOG_N: u8 = 10;
+const MIN_BCRYPT_COST: u32 = 4;
+
+/// Дальше подбор не идёт, даже если машина очень быстрая: 4 ГиБ на хэш
+/// у Argon2id и 1 ГиБ у scrypt -- это уже больше, чем есть у многих серверов
+const MAX_ARGON2_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_BCRYPT_COST: u32 = 31;
+
+impl Params {
+    /// Параметры по рекомендациям OWASP (Password Storage Cheat Sheet)
+    pub fn recommended(algo: PasswordAlgorithm) -> Self {
+        match algo {
+            PasswordAlgorithm::Argon2id => Params::Argon2id {
+                memory_kib: 19 * 1024,
+                iterations: 2,
+                parallelism: 1,
+            },
+            PasswordAlgorithm::Scrypt => Params::Scrypt {
+                log_n: 17,
+                r: 8,
+                p: 1,
+            },
+            PasswordAlgorithm::Bcrypt => Params::Bcrypt { cost: 10 },
+        }
+    }
+
+    fn minimal(algo: PasswordAlgorithm) -> Self {
+        match algo {
+            PasswordAlgorithm::Argon2id => Params::Argon2id {
+                memory_kib: MIN_ARGON2_MEMORY_KIB,
+                iterations: 2,
+                parallelism: 1,
+            },
+            PasswordAlgorithm::Scrypt => Params::Scrypt {
+                log_n: MIN_SCRYPT_LOG_N,
+                r: 8,
+                p: 1,
+            },
+            PasswordAlgorithm::Bcrypt => Params::Bcrypt {
+                cost: MIN_BCRYPT_COST,
+            },
+        }
+    }
+
+    pub fn algorithm(&self) -> PasswordAlgorithm {
+        match self {
+            Params::Argon2id { .. } => PasswordAlgorithm::Argon2id,
+            Params::Scrypt { .. } => PasswordAlgorithm::Scrypt,
+            Params::Bcrypt { .. } => PasswordAlgorithm::Bcrypt,
+        }
+    }
+
+    /// Параметры, с которыми хэш считается примерно вдвое дольше.
+    /// None, если дальше увеличивать некуда.
+    fn doubled(&self) -> Option<Self> {
+        match *self {
+            Params::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => (memory_kib < MAX_ARGON2_MEMORY_KIB).then_some(Params::Argon2id {
+                memory_kib: memory_kib * 2,
+                iterations,
+                parallelism,
+            }),
+            Params::Scrypt { log_n, r, p } => {
+                (log_n < MAX_SCRYPT_LOG_N).then_some(Params::Scrypt {
+                    log_n: log_n + 1,
+                    r,
+                    p,
+                })
+            }
+            Params::Bcrypt { cost } => {
+                (cost < MAX_BCRYPT_COST).then_some(Params::Bcrypt { cost: cost + 1 })
+            }
+        }
+    }
+}
+
+impl Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Params::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => write!(f, "m={memory_kib},t={iterations},p={parallelism}"),
+            Params::Scrypt { log_n, r, p } => write!(f, "ln={log_n},r={r},p={p}"),
+            Params::Bcrypt { cost } => write!(f, "cost={cost}"),
+        }
+    }
+}
+
+/// Ошибка хэширования или проверки пароля
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordError {
+    /// Параметры или соль не подходят алгоритму
+    Params(String),
+    /// Строку хэша не удалось разобрать
+    Format(String),
+}
+
+impl Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordError::Params(msg) => write!(f, "invalid parameters: {msg}"),
+            PasswordError::Format(msg) => write!(f, "invalid password hash: {msg}"),
+        }
+    }
+}
+
+/// Случайная соль длиной 16 байт: столько ровно нужно bcrypt, и этого хватает остальным
+pub fn random_salt() -> Vec<u8> {
+    let mut salt = vec![0; 16];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Захэшировать пароль. Результат -- строка в формате PHC
+/// (`$argon2id$v=19$m=19456,t=2,p=1$соль$хэш`), в которой записаны алгоритм,
+/// параметры и соль, поэтому для проверки пароля ничего больше хранить не нужно.
+/// У bcrypt свой формат `$2b$10$...`, его понимают все библиотеки bcrypt.
+/// Без соли берётся случайная, и так и нужно делать, кроме тестов и сверки с другими программами.
+pub fn hash_password(
+    password: &[u8],
+    params: Params,
+    salt: Option<&[u8]>,
+) -> Result<String, PasswordError> {
+    let salt = salt.map(<[u8]>::to_vec).unwrap_or_else(random_salt);
+    let params_error = |e: &dyn Display| PasswordError::Params(e.to_string());
+
+    match params {
+        Params::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+        } => {
+            let params = argon2::Params::new(memory_kib, iterations, parallelism, None)
+                .map_err(|e| params_error(&e))?;
+            let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+            let salt = SaltString::encode_b64(&salt).map_err(|e| params_error(&e))?;
+            argon2
+                .hash_password(password, &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|e| params_error(&e))
+        }
+        Params::Scrypt { log_n, r, p } => {
+            let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN)
+                .map_err(|e| params_error(&e))?;
+            let salt = SaltString::encode_b64(&salt).map_err(|e| params_error(&e))?;
+            Scrypt
+                .hash_password_customized(password, None, None, params, &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|e| params_error(&e))
+        }
+        Params::Bcrypt { cost } => {
+            let salt: [u8; 16] = salt.try_into().map_err(|salt: Vec<u8>| {
+                PasswordError::Params(format!("bcrypt salt must be 16 bytes, got {}", salt.len()))
+            })?;
+            bcrypt::hash_with_salt(password, cost, salt)
+                .map(|hash| hash.format_for_version(bcrypt::Version::TwoB))
+                .map_err(|e| params_error(&e))
+        }
+    }
+}
+
+/// Проверить пароль по строке, которую вернул hash_password (или другая программа).
+/// Алгоритм и параметры берутся из самой строки.
+pub fn verify_password(password: &[u8], hash: &str) -> Result<bool, PasswordError> {
+    let hash = hash.trim();
+    if hash.starts_with("$2") {
+        return bcrypt::verify(password, hash).map_err(|e| PasswordError::Format(e.to_string()));
+    }
+
+    let parsed = PasswordHash::new(hash).map_err(|e| PasswordError::Format(e.to_string()))?;
+    let result = match parsed.algorithm.as_str() {
+        "argon2id" | "argon2i" | "argon2d" => Argon2::default().verify_password(password, &parsed),
+        "scrypt" => Scrypt.verify_password(password, &parsed),
+        other => {
+            return Err(PasswordError::Format(format!(
+                "unsupported algorithm '{other}'"
+            )))
+        }
+    };
+
+    match result {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(PasswordError::Format(e.to_string())),
+    }
+}
+
+/// Итог подбора параметров
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tuning {
+    pub params: Params,
+    /// Сколько занял один хэш с этими параметрами на этой машине
+    pub elapsed: Duration,
+}
+
+/// Подобрать параметры, с которыми один хэш считается на этой машине не дольше `target`.
+/// Начиная с самых маленьких параметров, стоимость удваивается (у Argon2id -- память,
+/// у scrypt -- N, у bcrypt -- cost), пока следующий шаг не выйдет за `target`.
+/// Подбор занимает примерно 2 * `target`. Если даже самые маленькие параметры
+/// медленнее `target`, возвращаются они.
+pub fn tune(algo: PasswordAlgorithm, target: Duration) -> Result<Tuning, PasswordError> {
+    let measure = |params: Params| -> Result<Duration, PasswordError> {
+        let start = Instant::now();
+        hash_password(b"password", params, Some(&[0; 16]))?;
+        Ok(start.elapsed())
+    };
+
+    let mut params = Params::minimal(algo);
+    let mut best = Tuning {
+        params,
+        elapsed: measure(params)?,
+    };
+    while let Some(next) = params.doubled() {
+        // Время растёт почти линейно, поэтому следующий шаг можно не считать,
+        // если он заведомо выйдет за цель
+        if best.elapsed * 2 > target {
+            break;
+        }
+        let elapsed = measure(next)?;
+        if elapsed > target {
+            break;
+        }
+        params = next;
+        best = Tuning { params, elapsed };
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Самые дешёвые параметры, чтобы тесты не ждали
+    const CHEAP: [Params; 3] = [
+        Params::Argon2id {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        },
+        Params::Scrypt {
+            log_n: 4,
+            r: 8,
+            p: 1,
+        },
+        Params::Bcrypt { cost: 4 },
+    ];
+
+    #[test]
+    fn round_trip() {
+        for params in CHEAP {
+            let hash = hash_password(b"correct horse", params, None).unwrap();
+            assert_eq!(verify_password(b"correct horse", &hash), Ok(true), "{hash}");
+            assert_eq!(
+                verify_password(b"correct horsf", &hash),
+                Ok(false),
+                "{hash}"
+            );
+            assert_eq!(verify_password(b"", &hash), Ok(false), "{hash}");
+        }
+    }
+
+    #[test]
+    fn hash_records_algorithm_and_params() {
+        let salt = [7; 16];
+        let prefixes = [
+            "$argon2id$v=19$m=64,t=1,p=1$",
+            "$scrypt$ln=4,r=8,p=1$",
+            "$2b$04$",
+        ];
+        for (params, prefix) in CHEAP.into_iter().zip(prefixes) {
+            let hash = hash_password(b"password", params, Some(&salt)).unwrap();
+            assert!(hash.starts_with(prefix), "{hash}");
+            // С одной и той же солью хэш повторяется, со случайной -- нет
+            assert_eq!(
+                hash_password(b"password", params, Some(&salt)),
+                Ok(hash.clone())
+            );
+            assert_ne!(hash_password(b"password", params, None), Ok(hash));
+        }
+    }
+
+    #[test]
+    fn verify_trims_whitespace() {
+        let hash = hash_password(b"password", CHEAP[2], None).unwrap();
+        assert_eq!(verify_password(b"password", &format!("{hash}\n")), Ok(true));
+    }
+
+    #[test]
+    fn bad_params_and_hashes_are_errors() {
+        assert!(matches!(
+            hash_password(b"password", CHEAP[2], Some(&[0; 8])),
+            Err(PasswordError::Params(_))
+        ));
+        assert!(matches!(
+            hash_password(b"password", Params::Bcrypt { cost: 3 }, None),
+            Err(PasswordError::Params(_))
+        ));
+        assert!(matches!(
+            verify_password(b"password", "not a hash"),
+            Err(PasswordError::Format(_))
+        ));
+        assert!(matches!(
+            verify_password(b"password", "$pbkdf2-sha256$i=1000$c2FsdA$aGFzaA"),
+            Err(PasswordError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn algorithm_names_round_trip() {
+        for algo in PasswordAlgorithm::ALL {
+            assert_eq!(algo.name().to_uppercase().parse(), Ok(algo));
+            assert_eq!(Params::recommended(algo).algorithm(), algo);
+        }
+        assert!("md5".parse::<PasswordAlgorithm>().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Write as _;
+
+use qrcodegen::{QrCode, QrCodeEcc};
+
+/// Ширина белой рамки вокруг кода в модулях. Стандарт требует не меньше четырёх,
+/// иначе телефон может не найти код на фоне.
+pub const BORDER: i32 = 4;
+
+/// Уровень коррекции ошибок: какую долю кода можно испортить (или закрыть пальцем),
+/// а его всё равно прочитают. Чем выше уровень, тем крупнее код.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCorrection {
+    Low,
+    Medium,
+    Quartile,
+    High,
+}
+
+impl ErrorCorrection {
+    pub const ALL: [ErrorCorrection; 4] = [
+        ErrorCorrection::Low,
+        ErrorCorrection::Medium,
+        ErrorCorrection::Quartile,
+        ErrorCorrection::High,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorCorrection::Low => "L (7%)",
+            ErrorCorrection::Medium => "M (15%)",
+            ErrorCorrection::Quartile => "Q (25%)",
+            ErrorCorrection::High => "H (30%)",
+        }
+    }
+
+    /// Названия для drop-down в том же порядке, что и в ALL
+    pub fn names() -> Vec<&'static str> {
+        Self::ALL.iter().map(|level| level.name()).collect()
+    }
+
+    fn ecc(self) -> QrCodeEcc {
+        match self {
+            ErrorCorrection::Low => QrCodeEcc::Low,
+            ErrorCorrection::Medium => QrCodeEcc::Medium,
+            ErrorCorrection::Quartile => QrCodeEcc::Quartile,
+            ErrorCorrection::High => QrCodeEcc::High,
+        }
+    }
+}
+
+/// QR-код для текста. Если текста нет или он не помещается даже в самый большой код, None.
+pub fn encode(text: &str, level: ErrorCorrection) -> Option<QrCode> {
+    if text.is_empty() {
+        return None;
+    }
+    // boost_ecl = false: уровень остаётся тем, который выбрал пользователь
+    QrCode::encode_segments_advanced(
+        &qrcodegen::QrSegment::make_segments(text),
+        level.ecc(),
+        qrcodegen::Version::MIN,
+        qrcodegen::Version::MAX,
+        None,
+        false,
+    )
+    .ok()
+}
+
+/// Размер кода вместе с рамкой в модулях
+pub fn full_size(qr: &QrCode) -> i32 {
+    qr.size() + BORDER * 2
+}
+
+/// Тёмный ли модуль в координатах с учётом рамки (рамка всегда светлая)
+pub fn is_dark(qr: &QrCode, x: i32, y: i32) -> bool {
+    qr.get_module(x - BORDER, y - BORDER)
+}
+
+/// Код в формате SVG: каждый тёмный модуль -- квадрат 1x1 в одном пути
+pub fn to_svg(qr: &QrCode) -> String {
+    let size = full_size(qr);
+    let mut path = String::new();
+    for y in 0..size {
+        for x in 0..size {
+            if is_dark(qr, x, y) {
+                write!(path, "M{x},{y}h1v1h-1z").unwrap();
+            }
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {size} {size}\" stroke=\"none\">\n\
+         \t<rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n\
+         \t<path d=\"{path}\" fill=\"#000000\"/>\n\
+         </svg>\n"
+    )
+}
+
+/// Пиксели кода в формате RGB по 3 байта, `scale` пикселей на модуль.
+/// Возвращает ширину (она же высота) картинки и сами пиксели.
+pub fn to_rgb(qr: &QrCode, scale: i32) -> (i32, Vec<u8>) {
+    let width = full_size(qr) * scale;
+    let mut pixels = Vec::with_capacity((width * width * 3) as usize);
+    for y in 0..width {
+        for x in 0..width {
+            let value = if is_dark(qr, x / scale, y / scale) {
+                0
+            } else {
+                255
+            };
+            pixels.extend([value; 3]);
+        }
+    }
+    (width, pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_limits() {
+        assert!(encode("", ErrorCorrection::Low).is_none());
+        assert!(encode(&"x".repeat(3000), ErrorCorrection::Low).is_none());
+
+        // Короткий текст помещается в самую маленькую версию, 21x21 модуль
+        let qr = encode("abc", ErrorCorrection::High).unwrap();
+        assert_eq!(qr.size(), 21);
+        assert_eq!(qr.error_correction_level(), QrCodeEcc::High);
+        assert_eq!(full_size(&qr), 21 + 2 * BORDER);
+    }
+
+    #[test]
+    fn border_is_light() {
+        let qr = encode("abc", ErrorCorrection::Low).unwrap();
+        let size = full_size(&qr);
+        for i in 0..size {
+            for edge in [0, BORDER - 1, size - BORDER, size - 1] {
+                assert!(!is_dark(&qr, i, edge));
+                assert!(!is_dark(&qr, edge, i));
+            }
+        }
+        // Угол поискового узора
+        assert!(is_dark(&qr, BORDER, BORDER));
+    }
+
+    #[test]
+    fn svg_has_square_per_dark_module() {
+        let qr = encode("abc", ErrorCorrection::Medium).unwrap();
+        let size = full_size(&qr);
+        let dark = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|&(x, y)| is_dark(&qr, x, y))
+            .count();
+
+        let svg = to_svg(&qr);
+        assert!(svg.contains(&format!("viewBox=\"0 0 {size} {size}\"")));
+        assert_eq!(svg.matches("h1v1h-1z").count(), dark);
+        assert!(svg.contains(&format!("M{BORDER},{BORDER}h1v1h-1z")));
+    }
+
+    #[test]
+    fn rgb_scales_modules() {
+        let qr = encode("abc", ErrorCorrection::Quartile).unwrap();
+        let scale = 3;
+        let (width, pixels) = to_rgb(&qr, scale);
+        assert_eq!(width, full_size(&qr) * scale);
+        assert_eq!(pixels.len(), (width * width * 3) as usize);
+
+        for y in 0..width {
+            for x in 0..width {
+                let offset = ((y * width + x) * 3) as usize;
+                let expected = if is_dark(&qr, x / scale, y / scale) {
+                    0
+                } else {
+                    255
+                };
+                assert_eq!(pixels[offset..offset + 3], [expected; 3], "({x}, {y})");
+            }
+        }
+    }
+}
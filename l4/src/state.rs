@@ -0,0 +1,149 @@
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::VecDeque;
+
+use crate::hasher::{Change, Hasher};
+
+/// Подписчик на изменения модели
+type Listener = Box<dyn Fn(&Hasher)>;
+
+/// Модель, за которой можно следить: после каждого изменения вызываются все подписчики.
+/// Обработчики сигналов только сообщают об изменении, а что показать, решают подписчики.
+pub struct State {
+    hasher: RefCell<Hasher>,
+    listeners: RefCell<Vec<Listener>>,
+    /// Изменения, которые пришли, пока подписчики разбирали предыдущее
+    pending: RefCell<VecDeque<Change>>,
+    notifying: Cell<bool>,
+}
+
+impl State {
+    pub fn new(hasher: Hasher) -> Self {
+        Self {
+            hasher: RefCell::new(hasher),
+            listeners: RefCell::new(vec![]),
+            pending: RefCell::new(VecDeque::new()),
+            notifying: Cell::new(false),
+        }
+    }
+
+    /// Подписаться на изменения. Подписчик сразу получает текущее состояние.
+    pub fn subscribe(&self, listener: impl Fn(&Hasher) + 'static) {
+        listener(&self.hasher.borrow());
+        self.listeners.borrow_mut().push(Box::new(listener));
+    }
+
+    pub fn hasher(&self) -> Ref<'_, Hasher> {
+        self.hasher.borrow()
+    }
+
+    /// Изменить модель и сообщить об этом подписчикам. Если подписчик сам меняет
+    /// модель (например, сообщает, что файл не удалось прочитать), изменение
+    /// применяется после того, как текущее увидят все подписчики, -- так никто
+    /// не получит состояние, которое уже устарело.
+    pub fn apply(&self, change: Change) {
+        self.pending.borrow_mut().push_back(change);
+        if self.notifying.replace(true) {
+            return;
+        }
+
+        loop {
+            let Some(change) = self.pending.borrow_mut().pop_front() else {
+                break;
+            };
+            self.hasher.borrow_mut().apply(change);
+
+            let hasher = self.hasher.borrow();
+            for listener in self.listeners.borrow().iter() {
+                listener(&hasher);
+            }
+        }
+
+        self.notifying.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::{Rc, Weak};
+
+    use super::*;
+    use crate::algorythm::HashAlgorythm;
+    use crate::hasher::HashPreviewOption;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    fn state() -> Rc<State> {
+        Rc::new(State::new(Hasher::new(
+            HashAlgorythm::Sha3_256,
+            HashPreviewOption::Hex,
+        )))
+    }
+
+    /// Подписчик, который записывает в журнал своё имя и текст модели
+    fn record(state: &State, log: &Log, name: &'static str) {
+        let log = log.clone();
+        state.subscribe(move |hasher| log.borrow_mut().push(format!("{name}:{}", hasher.text())));
+    }
+
+    /// Подписчик, который в ответ на текст `on` вводит тексты `then`
+    fn react(state: &Rc<State>, on: &'static str, then: &'static [&'static str]) {
+        let weak: Weak<State> = Rc::downgrade(state);
+        state.subscribe(move |hasher| {
+            if hasher.text() == on {
+                let state = weak.upgrade().unwrap();
+                for text in then {
+                    state.apply(Change::Text(text.to_string()));
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn listeners_are_notified_in_subscription_order() {
+        let state = state();
+        let log = Log::default();
+        record(&state, &log, "first");
+        record(&state, &log, "second");
+        // Подписчик сразу получает текущее состояние
+        assert_eq!(*log.borrow(), ["first:", "second:"]);
+
+        state.apply(Change::Text("abc".to_string()));
+        assert_eq!(
+            *log.borrow(),
+            ["first:", "second:", "first:abc", "second:abc"]
+        );
+        assert_eq!(state.hasher().text(), "abc");
+    }
+
+    #[test]
+    fn change_from_listener_is_applied_after_current_one() {
+        let state = state();
+        let log = Log::default();
+        record(&state, &log, "first");
+        react(&state, "a", &["b"]);
+        record(&state, &log, "last");
+        log.borrow_mut().clear();
+
+        state.apply(Change::Text("a".to_string()));
+        // Последний подписчик видит "a", хотя средний уже попросил ввести "b"
+        assert_eq!(*log.borrow(), ["first:a", "last:a", "first:b", "last:b"]);
+        assert_eq!(state.hasher().text(), "b");
+    }
+
+    #[test]
+    fn queued_changes_are_delivered_once_in_order() {
+        let state = state();
+        let log = Log::default();
+        react(&state, "1", &["2", "3"]);
+        react(&state, "2", &["4"]);
+        record(&state, &log, "log");
+        log.borrow_mut().clear();
+
+        state.apply(Change::Text("1".to_string()));
+        assert_eq!(*log.borrow(), ["log:1", "log:2", "log:3", "log:4"]);
+
+        // После разбора очереди новые изменения снова доставляются сразу
+        state.apply(Change::Text("5".to_string()));
+        assert_eq!(*log.borrow(), ["log:1", "log:2", "log:3", "log:4", "log:5"]);
+    }
+}
@@ -0,0 +1,303 @@
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use crate::algorythm::BoxDigest;
+
+/// Размер куска, которыми читается файл. Весь файл в память не загружается,
+/// поэтому можно хэшировать файлы в несколько гигабайт.
+pub const CHUNK_SIZE: usize = 1 << 20;
+
+/// Прогресс фонового хэширования. Фоновый поток его обновляет, а интерфейс читает.
+/// Атомарные переменные можно менять из разных потоков без блокировок.
+#[derive(Default)]
+pub struct Progress {
+    read: AtomicU64,
+    total: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Сколько байтов уже прочитано
+    pub fn read(&self) -> u64 {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    /// Размер входных данных, если он известен (у потока он может быть неизвестен, тогда 0)
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Доля прочитанного от 0 до 1
+    pub fn fraction(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.read() as f64 / total as f64,
+        }
+    }
+
+    /// Попросить поток остановиться. Он заметит это перед чтением следующего куска.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Захэшировать поток, читая его кусками по CHUNK_SIZE и отмечая прогресс.
+/// При отмене возвращает ошибку с видом Interrupted.
+pub fn hash_reader(
+    reader: impl Read,
+    digest: BoxDigest,
+    progress: &Progress,
+) -> io::Result<Vec<u8>> {
+    let mut hashes = hash_reader_all(reader, vec![digest], progress)?;
+    Ok(hashes.remove(0))
+}
+
+/// Захэшировать поток сразу несколькими хэшерами за один проход: каждый прочитанный
+/// кусок отдаётся всем хэшерам, так что файл читается с диска только один раз.
+/// Хэши возвращаются в том же порядке, что и хэшеры.
+pub fn hash_reader_all(
+    mut reader: impl Read,
+    mut digests: Vec<BoxDigest>,
+    progress: &Progress,
+) -> io::Result<Vec<Vec<u8>>> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+
+    loop {
+        if progress.is_cancelled() {
+            return Err(io::Error::new(ErrorKind::Interrupted, "hashing cancelled"));
+        }
+
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            // Чтение прервано сигналом, его нужно просто повторить
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for digest in &mut digests {
+            digest.update(&buffer[..n]);
+        }
+        progress.read.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    Ok(digests
+        .into_iter()
+        .map(|digest| digest.finalize().to_vec())
+        .collect())
+}
+
+/// Хэширование файла в фоновом потоке. Интерфейс не блокируется:
+/// он периодически спрашивает прогресс и результат через poll.
+pub struct Job {
+    pub progress: Arc<Progress>,
+    result: mpsc::Receiver<io::Result<Vec<Vec<u8>>>>,
+}
+
+impl Job {
+    /// Начать хэшировать файл `path` всеми хэшерами из `digests` за один проход
+    pub fn spawn(path: PathBuf, digests: Vec<BoxDigest>) -> Self {
+        let progress = Arc::new(Progress::default());
+        let (sender, result) = mpsc::channel();
+
+        // Прогресс общий у потока и интерфейса, поэтому он в Arc (счётчик ссылок для потоков)
+        let shared = progress.clone();
+        thread::spawn(move || {
+            let hash = File::open(&path).and_then(|file| {
+                let total = file.metadata()?.len();
+                shared.total.store(total, Ordering::Relaxed);
+                hash_reader_all(file, digests, &shared)
+            });
+
+            // Если интерфейс уже забыл про задачу, результат никому не нужен
+            let _ = sender.send(hash);
+        });
+
+        Self { progress, result }
+    }
+
+    /// Хэши в порядке хэшеров, если поток уже закончил работу
+    pub fn poll(&self) -> Option<io::Result<Vec<Vec<u8>>>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err(io::Error::other("hashing thread stopped unexpectedly")))
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+}
+
+/// Размер в удобных единицах: 512 Б, 1.5 МиБ, 3.2 ГиБ
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["Б", "КиБ", "МиБ", "ГиБ", "ТиБ"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[0])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::algorythm::HashAlgorythm;
+    use crate::bench::sample_data;
+    use crate::hasher::{Change, HashPreviewOption, Hasher};
+
+    /// Данные больше буфера и не кратные его размеру, чтобы последний кусок был неполным
+    fn large_data() -> Vec<u8> {
+        sample_data(2 * CHUNK_SIZE + 12345)
+    }
+
+    fn one_shot(algo: HashAlgorythm, data: &[u8]) -> Vec<u8> {
+        let mut digest = algo.digest(algo.default_output_len());
+        digest.update(data);
+        digest.finalize().to_vec()
+    }
+
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("l4-stream-{}-{name}", std::process::id()));
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn wait(job: &Job) -> io::Result<Vec<Vec<u8>>> {
+        loop {
+            if let Some(result) = job.poll() {
+                return result;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Поток, который отдаёт данные маленькими порциями и иногда прерывается сигналом
+    struct Choppy<'a> {
+        data: &'a [u8],
+        calls: usize,
+    }
+
+    impl Read for Choppy<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.calls += 1;
+            if self.calls.is_multiple_of(3) {
+                return Err(io::Error::new(ErrorKind::Interrupted, "signal"));
+            }
+            let n = buf.len().min(self.data.len()).min(1000);
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn chunked_hash_matches_one_shot() {
+        let data = large_data();
+        // По одному алгоритму из каждого семейства: в отладочной сборке хэши медленные
+        for algo in [
+            HashAlgorythm::Sha256,
+            HashAlgorythm::Sha3_256,
+            HashAlgorythm::Shake128,
+            HashAlgorythm::Blake2b,
+            HashAlgorythm::Blake3,
+        ] {
+            let digests = vec![algo.digest(algo.default_output_len())];
+            let progress = Progress::default();
+            let hashes = hash_reader_all(data.as_slice(), digests, &progress).unwrap();
+            assert_eq!(hashes, [one_shot(algo, &data)], "{algo}");
+            assert_eq!(progress.read(), data.len() as u64);
+        }
+
+        let algo = HashAlgorythm::Blake3;
+        let reader = Choppy {
+            data: &data[..10_000],
+            calls: 0,
+        };
+        let hash = hash_reader(reader, algo.digest(32), &Progress::default()).unwrap();
+        assert_eq!(hash, one_shot(algo, &data[..10_000]));
+    }
+
+    #[test]
+    fn cancelled_hashing_stops() {
+        let progress = Progress::default();
+        progress.cancel();
+        let algo = HashAlgorythm::Sha256;
+        let result = hash_reader(large_data().as_slice(), algo.digest(32), &progress);
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Interrupted);
+        assert_eq!(progress.read(), 0);
+    }
+
+    #[test]
+    fn background_job_hashes_file_in_one_pass() {
+        let data = large_data();
+        let path = temp_file("job", &data);
+        let algos = [HashAlgorythm::Sha3_256, HashAlgorythm::Sha256];
+        let digests = algos
+            .iter()
+            .map(|algo| algo.digest(algo.default_output_len()))
+            .collect();
+
+        let job = Job::spawn(path.clone(), digests);
+        let hashes = wait(&job).unwrap();
+        assert_eq!(hashes, algos.map(|algo| one_shot(algo, &data)));
+        assert_eq!(job.progress.total(), data.len() as u64);
+        assert_eq!(job.progress.fraction(), 1.0);
+
+        let missing = Job::spawn(path.with_extension("missing"), vec![]);
+        assert_eq!(wait(&missing).unwrap_err().kind(), ErrorKind::NotFound);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn stale_job_result_is_dropped() {
+        let data = large_data();
+        let path = temp_file("stale", &data);
+        let mut hasher = Hasher::new(HashAlgorythm::Sha3_256, HashPreviewOption::Hex);
+        hasher.apply(Change::File(path.clone()));
+
+        // Пока файл хэшируется, пользователь выбирает другой алгоритм
+        let stale = (
+            hasher.revision(),
+            Job::spawn(path.clone(), hasher.digests().unwrap()),
+        );
+        hasher.apply(Change::Algo(HashAlgorythm::Sha256));
+        let current = (
+            hasher.revision(),
+            Job::spawn(path.clone(), hasher.digests().unwrap()),
+        );
+
+        hasher.apply(Change::FileHashed(stale.0, wait(&stale.1).unwrap()));
+        assert_eq!(hasher.view(), Ok("".to_string()));
+
+        hasher.apply(Change::FileHashed(current.0, wait(&current.1).unwrap()));
+        assert_eq!(
+            hasher.view(),
+            Ok(hex::encode(one_shot(HashAlgorythm::Sha256, &data)))
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
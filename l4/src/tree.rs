@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, Metadata};
+use std::io;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::algorythm::HashAlgorythm;
+use crate::hasher::HashPreviewOption;
+use crate::manifest::{self, ManifestError};
+use crate::stream::{hash_reader, Progress};
+
+/// Хэш-дерево (дерево Меркла) каталога. Листья -- хэши файлов вместе с их путями,
+/// а корень -- хэш всех листьев, собранных попарно. Корень меняется, если изменился,
+/// появился, пропал или переименован хотя бы один файл.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tree {
+    pub algo: HashAlgorythm,
+    /// Учитываются ли размер, права и время изменения файлов, а не только содержимое
+    pub metadata: bool,
+    /// Листья по путям относительно каталога (через '/'). BTreeMap хранит их
+    /// отсортированными, поэтому корень не зависит от порядка обхода каталога.
+    pub leaves: BTreeMap<String, Vec<u8>>,
+}
+
+/// Чем отличаются два снимка каталога
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Захэшировать каталог `dir`. Символические ссылки не раскрываются: хэшируется
+/// путь, на который они указывают. Остальные особые файлы (сокеты, устройства) пропускаются.
+pub fn hash_tree(dir: &Path, algo: HashAlgorythm, metadata: bool) -> io::Result<Tree> {
+    let mut leaves = BTreeMap::new();
+    walk(dir, "", algo, metadata, &mut leaves)?;
+    Ok(Tree {
+        algo,
+        metadata,
+        leaves,
+    })
+}
+
+fn walk(
+    dir: &Path,
+    prefix: &str,
+    algo: HashAlgorythm,
+    metadata: bool,
+    leaves: &mut BTreeMap<String, Vec<u8>>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+        let meta = entry.path().symlink_metadata()?;
+
+        let (kind, content) = if meta.is_dir() {
+            walk(&entry.path(), &format!("{name}/"), algo, metadata, leaves)?;
+            continue;
+        } else if meta.is_file() {
+            let digest = algo.digest(algo.default_output_len());
+            let content = hash_reader(File::open(entry.path())?, digest, &Progress::default())?;
+            (b'f', content)
+        } else if meta.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            (
+                b'l',
+                hash_parts(algo, &[target.to_string_lossy().as_bytes()]),
+            )
+        } else {
+            continue;
+        };
+
+        // Лист: вид файла, путь, хэш содержимого и, если нужно, метаданные.
+        // Ноль после пути не даёт склеить разные пути и содержимое в одинаковые байты.
+        let meta = if metadata {
+            metadata_bytes(&meta)
+        } else {
+            vec![]
+        };
+        let leaf = hash_parts(algo, &[&[0, kind], name.as_bytes(), &[0], &content, &meta]);
+        leaves.insert(name, leaf);
+    }
+    Ok(())
+}
+
+/// Размер, права доступа и время изменения в секундах
+fn metadata_bytes(meta: &Metadata) -> Vec<u8> {
+    #[cfg(unix)]
+    let mode = std::os::unix::fs::PermissionsExt::mode(&meta.permissions());
+    #[cfg(not(unix))]
+    let mode: u32 = if meta.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    };
+
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|time| time.as_secs())
+        .unwrap_or_default();
+
+    let mut bytes = vec![];
+    bytes.extend(meta.len().to_le_bytes());
+    bytes.extend(mode.to_le_bytes());
+    bytes.extend(modified.to_le_bytes());
+    bytes
+}
+
+/// Хэш нескольких кусков данных подряд
+fn hash_parts(algo: HashAlgorythm, parts: &[&[u8]]) -> Vec<u8> {
+    let mut digest = algo.digest(algo.default_output_len());
+    for part in parts {
+        digest.update(part);
+    }
+    digest.finalize().to_vec()
+}
+
+impl Tree {
+    /// Корень дерева. Узлы уровня объединяются попарно: H(1 || левый || правый),
+    /// а узел без пары переходит на следующий уровень как есть (если его продублировать,
+    /// два разных набора файлов могли бы дать один корень). У пустого каталога корень -- H("").
+    pub fn root(&self) -> Vec<u8> {
+        let mut level: Vec<Vec<u8>> = self.leaves.values().cloned().collect();
+        if level.is_empty() {
+            return hash_parts(self.algo, &[]);
+        }
+
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_parts(self.algo, &[&[1], left, right]),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        level.remove(0)
+    }
+
+    /// Что изменилось в `newer` по сравнению с этим снимком
+    pub fn diff(&self, newer: &Tree) -> Diff {
+        let mut diff = Diff::default();
+        for (path, leaf) in &newer.leaves {
+            match self.leaves.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(old) if old != leaf => diff.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .leaves
+            .keys()
+            .filter(|path| !newer.leaves.contains_key(*path))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Снимок в текстовом виде: заголовок с настройками в комментариях
+    /// и строки листьев в формате манифеста coreutils
+    pub fn to_baseline(&self) -> String {
+        let hex = HashPreviewOption::Hex;
+        let mut text = format!(
+            "# algorithm: {}\n# metadata: {}\n# root: {}\n",
+            self.algo,
+            self.metadata,
+            hex.encode(&self.root())
+        );
+        for (path, leaf) in &self.leaves {
+            text.push_str(&manifest::format_line(&hex.encode(leaf), path));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Прочитать снимок, сохранённый через to_baseline
+    pub fn from_baseline(text: &str) -> Result<Self, ManifestError> {
+        let header = |key: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(&format!("# {key}: ")))
+                .ok_or_else(|| ManifestError::Syntax(1, format!("missing '# {key}:' header")))
+        };
+        let algo = header("algorithm")?
+            .parse()
+            .map_err(|e| ManifestError::Syntax(1, e))?;
+        let metadata = header("metadata")? == "true";
+
+        let leaves = manifest::parse(text, Path::new(""), "", algo)?
+            .into_iter()
+            .map(|entry| (entry.name, entry.expected))
+            .collect();
+        let tree = Self {
+            algo,
+            metadata,
+            leaves,
+        };
+
+        // Корень в заголовке защищает от случайной правки строк снимка
+        if HashPreviewOption::Hex.decode(header("root")?) != Some(tree.root()) {
+            return Err(ManifestError::Syntax(
+                1,
+                "root does not match the leaves".to_string(),
+            ));
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALGO: HashAlgorythm = HashAlgorythm::Sha256;
+
+    fn tree(leaves: &[(&str, &[u8])]) -> Tree {
+        Tree {
+            algo: ALGO,
+            metadata: false,
+            leaves: leaves
+                .iter()
+                .map(|(path, leaf)| (path.to_string(), leaf.to_vec()))
+                .collect(),
+        }
+    }
+
+    fn node(left: &[u8], right: &[u8]) -> Vec<u8> {
+        hash_parts(ALGO, &[&[1], left, right])
+    }
+
+    /// Временный каталог с файлами; удаляется в конце теста
+    fn make_dir(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("l4-tree-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn root_of_empty_and_single_leaf() {
+        assert_eq!(tree(&[]).root(), hash_parts(ALGO, &[]));
+        assert_eq!(tree(&[("a", b"A")]).root(), b"A");
+    }
+
+    #[test]
+    fn odd_node_is_promoted() {
+        let root = tree(&[("a", b"A"), ("b", b"B"), ("c", b"C")]).root();
+        assert_eq!(root, node(&node(b"A", b"B"), b"C"));
+
+        let root = tree(&[
+            ("a", b"A"),
+            ("b", b"B"),
+            ("c", b"C"),
+            ("d", b"D"),
+            ("e", b"E"),
+        ])
+        .root();
+        assert_eq!(
+            root,
+            node(&node(&node(b"A", b"B"), &node(b"C", b"D")), b"E")
+        );
+
+        // С дублированием последнего узла эти деревья дали бы один корень
+        let duplicated = tree(&[("a", b"A"), ("b", b"B"), ("c", b"C"), ("d", b"C")]).root();
+        assert_ne!(
+            duplicated,
+            tree(&[("a", b"A"), ("b", b"B"), ("c", b"C")]).root()
+        );
+    }
+
+    #[test]
+    fn root_is_deterministic() {
+        let files = [("b.txt", "bee"), ("a/x", "ex"), ("a/y", "why")];
+        let first = make_dir("first", &files);
+        let mut reversed = files;
+        reversed.reverse();
+        let second = make_dir("second", &reversed);
+
+        let first_tree = hash_tree(&first, ALGO, false).unwrap();
+        let second_tree = hash_tree(&second, ALGO, false).unwrap();
+        fs::remove_dir_all(&first).unwrap();
+        fs::remove_dir_all(&second).unwrap();
+
+        let paths: Vec<_> = first_tree.leaves.keys().map(String::as_str).collect();
+        assert_eq!(paths, ["a/x", "a/y", "b.txt"]);
+        assert_eq!(first_tree, second_tree);
+        assert_eq!(first_tree.root(), second_tree.root());
+    }
+
+    #[test]
+    fn renaming_changes_root() {
+        let dir = make_dir("rename", &[("a", "same")]);
+        let before = hash_tree(&dir, ALGO, false).unwrap();
+        fs::rename(dir.join("a"), dir.join("b")).unwrap();
+        let after = hash_tree(&dir, ALGO, false).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_ne!(before.root(), after.root());
+    }
+
+    #[test]
+    fn diff_detects_changes() {
+        let old = tree(&[("kept", b"1"), ("changed", b"2"), ("gone", b"3")]);
+        let new = tree(&[("kept", b"1"), ("changed", b"4"), ("new", b"5")]);
+
+        assert_eq!(
+            old.diff(&new),
+            Diff {
+                added: vec!["new".to_string()],
+                removed: vec!["gone".to_string()],
+                modified: vec!["changed".to_string()],
+            }
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let dir = make_dir(
+            "baseline",
+            &[
+                ("x (1) = y", "odd name"),
+                ("dir/a\\b", "slash"),
+                ("plain", ""),
+            ],
+        );
+        let tree = hash_tree(&dir, ALGO, true).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let loaded = Tree::from_baseline(&tree.to_baseline()).unwrap();
+        assert_eq!(loaded, tree);
+        assert!(tree.diff(&loaded).is_empty());
+    }
+
+    #[test]
+    fn tampered_baseline_is_rejected() {
+        let baseline = tree(&[("a", &[0xaa; 32]), ("b", &[0xbb; 32])]).to_baseline();
+        let tampered = baseline.replace(&"bb".repeat(32), &"cc".repeat(32));
+        assert!(Tree::from_baseline(&tampered).is_err());
+        assert!(Tree::from_baseline(&baseline.replace("# root", "# rot")).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Известные ответы для всех алгоритмов из tests/vectors/kat.rsp. Каждый вектор проверяется
+//! через модель (Hasher): сообщение вводится как hex и, если это текст, как UTF-8,
+//! а хэш сверяется и в hex, и в base64.
+
+use l4::algorythm::HashAlgorythm;
+use l4::hasher::{Change, HashPreviewOption, Hasher, TextEncoding};
+use l4::key::Key;
+
+/// Один вектор из файла
+#[derive(Debug, Default)]
+struct Vector {
+    algo: String,
+    /// Строка файла, с которой начинается вектор, чтобы найти его при ошибке
+    line: usize,
+    len_bits: usize,
+    msg: String,
+    key: Option<String>,
+    output_bits: Option<usize>,
+    md: String,
+    b64: String,
+}
+
+/// Разобрать файл в формате NIST CAVS: секции `[алгоритм]` и записи `Поле = значение`,
+/// разделённые пустыми строками
+fn parse(text: &str) -> Vec<Vector> {
+    let mut vectors = vec![];
+    let mut algo = String::new();
+    let mut current: Option<Vector> = None;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            vectors.extend(current.take());
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            algo = name.to_string();
+            continue;
+        }
+
+        let (field, value) = line
+            .split_once(" = ")
+            .unwrap_or_else(|| panic!("line {}: expected 'Field = value'", number + 1));
+        let vector = current.get_or_insert_with(|| Vector {
+            algo: algo.clone(),
+            line: number + 1,
+            ..Default::default()
+        });
+        let value = value.to_string();
+        match field {
+            "Len" => vector.len_bits = value.parse().unwrap(),
+            "Msg" => vector.msg = value,
+            "Key" => vector.key = Some(value),
+            "Outputlen" => vector.output_bits = Some(value.parse().unwrap()),
+            "MD" => vector.md = value,
+            "B64" => vector.b64 = value,
+            _ => panic!("line {}: unknown field '{field}'", number + 1),
+        }
+    }
+    vectors.extend(current);
+    vectors
+}
+
+fn vectors() -> Vec<Vector> {
+    parse(include_str!("vectors/kat.rsp"))
+}
+
+/// Модель, настроенная под вектор, но пока без сообщения
+fn hasher_for(vector: &Vector) -> Hasher {
+    let (algo, keyed) = match vector.algo.strip_prefix("HMAC ") {
+        Some(algo) => (algo, true),
+        None => (vector.algo.as_str(), false),
+    };
+    let algo: HashAlgorythm = algo.parse().unwrap();
+
+    let mut hasher = Hasher::new(algo, HashPreviewOption::Hex);
+    if let Some(bits) = vector.output_bits {
+        hasher.apply(Change::OutputLen(bits / 8));
+    }
+    assert_eq!(keyed, vector.key.is_some(), "line {}", vector.line);
+    if let Some(key) = &vector.key {
+        hasher.apply(Change::Key(Some(Key::new(key, TextEncoding::Hex))));
+    }
+    hasher
+}
+
+/// Хэш вектора в hex и base64, посчитанный моделью
+fn compute(vector: &Vector, input: TextEncoding, text: &str) -> (String, String) {
+    let mut hasher = hasher_for(vector);
+    hasher.apply(Change::InputEncoding(input));
+    hasher.apply(Change::Text(text.to_string()));
+    let hex = hasher.view().unwrap();
+    hasher.apply(Change::Option(HashPreviewOption::Base64Padded));
+    let base64 = hasher.view().unwrap();
+    (hex, base64)
+}
+
+#[test]
+fn vectors_cover_every_algorithm() {
+    let vectors = vectors();
+    for algo in HashAlgorythm::ALL {
+        assert!(
+            vectors.iter().any(|vector| vector.algo == algo.name()),
+            "no vectors for {algo}"
+        );
+    }
+}
+
+#[test]
+fn vectors_are_consistent() {
+    for vector in vectors() {
+        let msg = TextEncoding::Hex.decode(&vector.msg).unwrap();
+        let expected_bits = if vector.len_bits == 0 {
+            0
+        } else {
+            msg.len() * 8
+        };
+        assert_eq!(vector.len_bits, expected_bits, "line {}", vector.line);
+        assert_eq!(
+            HashPreviewOption::Base64Padded.decode(&vector.b64),
+            HashPreviewOption::Hex.decode(&vector.md),
+            "line {}",
+            vector.line
+        );
+    }
+}
+
+#[test]
+fn known_answers_from_hex_input() {
+    for vector in vectors().iter().filter(|vector| vector.len_bits > 0) {
+        let (hex, base64) = compute(vector, TextEncoding::Hex, &vector.msg);
+        assert_eq!(hex, vector.md, "{} at line {}", vector.algo, vector.line);
+        assert_eq!(
+            base64, vector.b64,
+            "{} at line {}",
+            vector.algo, vector.line
+        );
+    }
+}
+
+#[test]
+fn known_answers_from_text_input() {
+    for vector in vectors().iter().filter(|vector| vector.len_bits > 0) {
+        let msg = TextEncoding::Hex.decode(&vector.msg).unwrap();
+        let Ok(text) = String::from_utf8(msg) else {
+            continue;
+        };
+        let (hex, base64) = compute(vector, TextEncoding::Utf8, &text);
+        assert_eq!(hex, vector.md, "{} at line {}", vector.algo, vector.line);
+        assert_eq!(
+            base64, vector.b64,
+            "{} at line {}",
+            vector.algo, vector.line
+        );
+    }
+}
+
+/// Пустой текст в окне означает «нечего показывать», поэтому хэш пустого сообщения
+/// проверяется через хэшер модели напрямую
+#[test]
+fn known_answers_for_empty_message() {
+    for vector in vectors().iter().filter(|vector| vector.len_bits == 0) {
+        let hasher = hasher_for(vector);
+        let digest = hasher.digest().unwrap().finalize();
+        assert_eq!(
+            HashPreviewOption::Hex.encode(&digest),
+            vector.md,
+            "{} at line {}",
+            vector.algo,
+            vector.line
+        );
+        assert_eq!(
+            HashPreviewOption::Base64Padded.encode(&digest),
+            vector.b64,
+            "{} at line {}",
+            vector.algo,
+            vector.line
+        );
+    }
+}